@@ -0,0 +1,62 @@
+use reqwest::{Client, RequestBuilder};
+use url::Url;
+
+use crate::OsintError;
+
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const DEFAULT_API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// How an OpenAI-compatible API is addressed and authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    /// OpenAI and look-alikes such as Groq or OpenRouter: `<base>/chat/completions`
+    /// with a bearer token.
+    OpenAi,
+    /// Azure OpenAI: `<resource>/openai/deployments/<deployment>/chat/completions?api-version=...`
+    /// with an `api-key` header. The deployment picks the model.
+    Azure,
+}
+
+/// The chat completions endpoint of the analysis provider.
+#[derive(Debug)]
+pub struct Endpoint {
+    flavor: Flavor,
+    chat_url: Url,
+    api_key: String,
+}
+
+impl Endpoint {
+    /// Reads `--ai-base-url`: an API root like `https://api.groq.com/openai/v1`,
+    /// or an Azure deployment URL including its `api-version`. A URL already
+    /// ending in `/chat/completions` is used as is.
+    pub fn new(base_url: &str, api_key: String) -> Result<Endpoint, OsintError> {
+        let mut chat_url = Url::parse(base_url).map_err(|err| OsintError::InvalidTarget(format!("--ai-base-url {}: {}", base_url, err)))?;
+        let flavor = if chat_url.path().contains("/openai/deployments/") { Flavor::Azure } else { Flavor::OpenAi };
+        if flavor == Flavor::Azure && !chat_url.query_pairs().any(|(name, _)| name == "api-version") {
+            return Err(OsintError::InvalidTarget(format!("Azure OpenAI URL {} needs an api-version, e.g. ?api-version=2024-06-01", base_url)));
+        }
+        let path = chat_url.path().trim_end_matches('/').to_string();
+        if !path.ends_with("/chat/completions") {
+            chat_url.set_path(&format!("{}/chat/completions", path));
+        }
+        Ok(Endpoint { flavor, chat_url, api_key })
+    }
+
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Where completions are posted; carries no credentials.
+    pub fn url(&self) -> &Url {
+        &self.chat_url
+    }
+
+    /// A completions POST with the flavor's authentication.
+    pub fn post(&self, client: &Client) -> RequestBuilder {
+        let request = client.post(self.chat_url.clone());
+        match self.flavor {
+            Flavor::OpenAi => request.bearer_auth(&self.api_key),
+            Flavor::Azure => request.header("api-key", &self.api_key),
+        }
+    }
+}
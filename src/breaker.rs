@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Fast-fails requests to a host after `threshold` consecutive failures, so
+/// an outage costs one round of retries rather than one per request. After
+/// `cooldown` a single trial request is let through: success closes the
+/// circuit again, failure reopens it for another cooldown.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// A trial request went out at `since`. If it never reports back (its
+    /// future was dropped), another is allowed a cooldown later.
+    HalfOpen { since: Instant },
+}
+
+impl CircuitBreaker {
+    /// A `threshold` of 0 never opens the circuit.
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker { threshold, cooldown, circuits: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether a request to `host` may go out now, or else how long until
+    /// the next trial request.
+    pub fn allow(&self, host: &str) -> Result<(), Duration> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let Some(circuit) = circuits.get_mut(host) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } if until > now => Err(until - now),
+            Circuit::Open { .. } => {
+                info!(host, "circuit half-open, sending a trial request");
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+            Circuit::HalfOpen { since } if since + self.cooldown > now => Err(since + self.cooldown - now),
+            Circuit::HalfOpen { .. } => {
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+        }
+    }
+
+    pub fn record_success(&self, host: &str) {
+        if let Some(circuit) = self.circuits.lock().unwrap().remove(host) {
+            if !matches!(circuit, Circuit::Closed { .. }) {
+                info!(host, "host recovered, circuit closed");
+            }
+        }
+    }
+
+    pub fn record_failure(&self, host: &str) {
+        if self.threshold == 0 {
+            return;
+        }
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(host.to_string()).or_insert(Circuit::Closed { failures: 0 });
+        let failures = match *circuit {
+            Circuit::Closed { failures } => failures + 1,
+            // A request sent before the circuit opened.
+            Circuit::Open { .. } => return,
+            // The trial failed.
+            Circuit::HalfOpen { .. } => self.threshold,
+        };
+        *circuit = match failures >= self.threshold {
+            true => {
+                warn!(host, failures, cooldown_secs = self.cooldown.as_secs(), "circuit opened, failing fast");
+                Circuit::Open { until: Instant::now() + self.cooldown }
+            }
+            false => Circuit::Closed { failures },
+        };
+    }
+}
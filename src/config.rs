@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use crate::OsintError;
+
+/// Read from the working directory when neither `--config` nor
+/// `OSINT_CONFIG` names a file.
+pub const DEFAULT_PATH: &str = "osint.toml";
+
+/// Settings a config file can give, in `[defaults]` and in each profile.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// API keys by environment variable name, e.g. `SHODAN_API_KEY`.
+    pub keys: BTreeMap<String, String>,
+    /// The sources `all` runs, instead of every one that accepts the target.
+    pub sources: Option<Vec<String>>,
+    /// Where report files and screenshots are written.
+    pub output_dir: Option<PathBuf>,
+    /// HTTP(S) proxy for every request.
+    pub proxy: Option<String>,
+    /// Requests per minute to any one host.
+    pub rate_limit: Option<u32>,
+    /// DNS blocklist zones the dnsbl type checks besides its defaults.
+    pub dnsbl: Vec<String>,
+    /// Dork templates the dork type runs besides its defaults.
+    pub dorks: Vec<String>,
+}
+
+impl Settings {
+    /// `self` on top of `base`; keys, blocklists and dorks are merged, the
+    /// others replaced.
+    fn over(&self, base: &Settings) -> Settings {
+        let mut keys = base.keys.clone();
+        keys.extend(self.keys.clone());
+        let mut dnsbl = base.dnsbl.clone();
+        dnsbl.extend(self.dnsbl.iter().filter(|zone| !base.dnsbl.contains(zone)).cloned());
+        let mut dorks = base.dorks.clone();
+        dorks.extend(self.dorks.iter().filter(|dork| !base.dorks.contains(dork)).cloned());
+        Settings {
+            keys,
+            sources: self.sources.clone().or_else(|| base.sources.clone()),
+            output_dir: self.output_dir.clone().or_else(|| base.output_dir.clone()),
+            proxy: self.proxy.clone().or_else(|| base.proxy.clone()),
+            rate_limit: self.rate_limit.or(base.rate_limit),
+            dnsbl,
+            dorks,
+        }
+    }
+
+    fn check(&self, section: &str, sources: &[&str]) -> Result<(), OsintError> {
+        if let Some(unknown) = self.sources.iter().flatten().find(|source| !sources.contains(&source.as_str())) {
+            return Err(OsintError::Config(format!("[{}] lists unknown source {}", section, unknown)));
+        }
+        if let Some(zone) = self.dnsbl.iter().find(|zone| !zone.contains('.') || zone.contains(char::is_whitespace)) {
+            return Err(OsintError::Config(format!("[{}] dnsbl zone {:?} is not a DNS zone", section, zone)));
+        }
+        if let Some(dork) = self.dorks.iter().find(|dork| !dork.contains(crate::providers::serpapi::PLACEHOLDER)) {
+            return Err(OsintError::Config(format!("[{}] dork {:?} has no {}", section, dork, crate::providers::serpapi::PLACEHOLDER)));
+        }
+        if self.rate_limit == Some(0) {
+            return Err(OsintError::Config(format!("[{}] rate_limit must be at least 1 request per minute", section)));
+        }
+        Ok(())
+    }
+
+    /// One line of what the settings change, naming keys but never their values.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(sources) = &self.sources {
+            parts.push(format!("sources: {}", sources.join(",")));
+        }
+        if let Some(dir) = &self.output_dir {
+            parts.push(format!("output_dir: {}", dir.display()));
+        }
+        if let Some(proxy) = &self.proxy {
+            parts.push(format!("proxy: {}", crate::logging::redact_url(proxy)));
+        }
+        if let Some(rate_limit) = self.rate_limit {
+            parts.push(format!("rate_limit: {}/min", rate_limit));
+        }
+        if !self.dnsbl.is_empty() {
+            parts.push(format!("dnsbl: {}", self.dnsbl.join(",")));
+        }
+        if !self.dorks.is_empty() {
+            parts.push(format!("dorks: {}", self.dorks.len()));
+        }
+        if !self.keys.is_empty() {
+            parts.push(format!("keys: {}", self.keys.keys().cloned().collect::<Vec<_>>().join(",")));
+        }
+        match parts.is_empty() {
+            true => "(no overrides)".to_string(),
+            false => parts.join("; "),
+        }
+    }
+}
+
+/// An `osint.toml`: global `[defaults]` and named `[profiles.<name>]`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    pub defaults: Settings,
+    pub profiles: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    /// Loads `explicit` (`--config`), else `OSINT_CONFIG`, else `osint.toml`
+    /// when it exists. Without any of them the config is empty.
+    pub fn find(explicit: Option<&Path>, sources: &[&str]) -> Result<Config, OsintError> {
+        let named = explicit.map(Path::to_path_buf).or_else(|| env::var_os("OSINT_CONFIG").filter(|path| !path.is_empty()).map(PathBuf::from));
+        match named {
+            Some(path) => Config::load(&path, sources),
+            None if Path::new(DEFAULT_PATH).is_file() => Config::load(Path::new(DEFAULT_PATH), sources),
+            None => {
+                debug!("no config file");
+                Ok(Config::default())
+            }
+        }
+    }
+
+    pub fn load(path: &Path, sources: &[&str]) -> Result<Config, OsintError> {
+        let text = fs::read_to_string(path).map_err(|err| OsintError::Config(format!("cannot read {}: {}", path.display(), err)))?;
+        let mut config = Config::parse(&text, sources).map_err(|err| match err {
+            OsintError::Config(message) => OsintError::Config(format!("{}: {}", path.display(), message)),
+            err => err,
+        })?;
+        config.path = Some(path.to_path_buf());
+        info!(path = %path.display(), profiles = config.profiles.len(), "config loaded");
+        Ok(config)
+    }
+
+    /// Parses a config, checking every source it lists against `sources`.
+    pub fn parse(text: &str, sources: &[&str]) -> Result<Config, OsintError> {
+        let config: Config = toml::from_str(text).map_err(|err| {
+            let line = err.span().map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+            OsintError::Config(format!("line {}: {}", line, err.message()))
+        })?;
+        config.defaults.check("defaults", sources)?;
+        for (name, profile) in &config.profiles {
+            profile.check(&format!("profiles.{}", name), sources)?;
+        }
+        Ok(config)
+    }
+
+    /// The settings in effect: `profile` layered over `[defaults]`.
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings, OsintError> {
+        match profile {
+            None => Ok(self.defaults.clone()),
+            Some(name) => Ok(self.profile(name)?.over(&self.defaults)),
+        }
+    }
+
+    fn profile(&self, name: &str) -> Result<&Settings, OsintError> {
+        self.profiles.get(name).ok_or_else(|| {
+            let file = self.path.as_ref().map_or_else(|| "no config file found".to_string(), |path| path.display().to_string());
+            let available = match self.profiles.is_empty() {
+                true => "none".to_string(),
+                false => self.profiles.keys().cloned().collect::<Vec<_>>().join(", "),
+            };
+            OsintError::Config(format!("no profile {} ({}; available: {})", name, file, available))
+        })
+    }
+
+    /// Puts the API keys in the environment, where the sources read them.
+    /// `[defaults]` keys only fill variables that are unset or empty, so the
+    /// shell and `.env` win over them; a profile's keys replace both.
+    pub fn export_keys(&self, profile: Option<&str>) -> Result<(), OsintError> {
+        for (name, value) in &self.defaults.keys {
+            if env::var(name).map_or(true, |current| current.is_empty()) {
+                env::set_var(name, value);
+            }
+        }
+        if let Some(name) = profile {
+            for (key, value) in &self.profile(name)?.keys {
+                debug!(profile = name, key, "using the profile's key");
+                env::set_var(key, value);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::cost::{self, Estimator, Usage};
+use crate::summarize::DataSummarizer;
+use crate::{ai, OsintError};
+
+const CORRELATE_PROMPT_PREFIX: &str = "Correlate these OSINT reports on related targets. Point out patterns across them, such as shared infrastructure (IPs, ASNs, hosting, name servers, certificates), common registrars and registration dates, overlapping breaches and reused technologies, and name the targets each pattern links. Reports:\n";
+const MAP_PROMPT_PREFIX: &str = "Extract, per target, the facts from these OSINT reports that would link them to other targets: IPs, ASNs, hosting, name servers, certificates, registrars and dates, breaches, technologies and notable findings. Be terse. Reports:\n";
+/// Reduce rounds before whatever is left is sent as is.
+const MAX_ROUNDS: usize = 4;
+
+/// The correlated analysis of several reports.
+#[derive(Debug)]
+pub struct Correlation {
+    pub text: String,
+    pub usage: Usage,
+    /// Completion calls made, more than one when the reports had to be
+    /// summarized in chunks first.
+    pub calls: usize,
+    /// Reduce rounds needed before the final call.
+    pub rounds: usize,
+}
+
+/// Asks for the patterns across `reports` in one prompt. When they do not fit
+/// the model's context window together, they are summarized in chunks that
+/// do and the summaries correlated instead (map-reduce).
+pub async fn correlate(endpoint: &ai::Endpoint, model: &str, reports: &[Value], max_cost: Option<f64>) -> Result<Correlation, OsintError> {
+    let estimator = Estimator::for_model(model)?;
+    let fixed_tokens = [CORRELATE_PROMPT_PREFIX, MAP_PROMPT_PREFIX].iter().map(|prefix| estimator.prompt_tokens(&[crate::ANALYSIS_SYSTEM_PROMPT, prefix])).max().unwrap_or_default();
+    let budget = cost::context_window(model) - cost::MAX_COMPLETION_TOKENS - fixed_tokens;
+    correlate_within(endpoint, model, &estimator, reports, budget, max_cost).await
+}
+
+/// The correlation with a prompt budget of `budget` tokens for the reports,
+/// so tests can force chunking with small reports.
+pub async fn correlate_within(endpoint: &ai::Endpoint, model: &str, estimator: &Estimator, reports: &[Value], budget: usize, max_cost: Option<f64>) -> Result<Correlation, OsintError> {
+    let mut documents: Vec<String> = reports.iter().map(|report| describe(estimator, report, budget)).collect();
+    let mut correlation = Correlation { text: String::new(), usage: Usage::default(), calls: 0, rounds: 0 };
+    let mut spent = 0.0;
+    loop {
+        let chunks = pack(estimator, &documents, budget);
+        if chunks.len() <= 1 || correlation.rounds == MAX_ROUNDS {
+            if chunks.len() > 1 {
+                warn!(chunks = chunks.len(), "summaries still exceed the context window, sending them as they are");
+            }
+            let prompt = format!("{}{}", CORRELATE_PROMPT_PREFIX, documents.join("\n\n"));
+            correlation.text = call(endpoint, model, estimator, &prompt, max_cost, &mut spent, &mut correlation).await?;
+            info!(reports = reports.len(), calls = correlation.calls, rounds = correlation.rounds, "correlation done");
+            return Ok(correlation);
+        }
+        correlation.rounds += 1;
+        info!(round = correlation.rounds, chunks = chunks.len(), "reports exceed the context window, summarizing them in chunks");
+        let mut notes = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let prompt = format!("{}{}", MAP_PROMPT_PREFIX, chunk.join("\n\n"));
+            notes.push(call(endpoint, model, estimator, &prompt, max_cost, &mut spent, &mut correlation).await?);
+        }
+        documents = notes;
+    }
+}
+
+/// One completion, refused when the worst-case cost of all calls so far
+/// would pass `max_cost`.
+async fn call(endpoint: &ai::Endpoint, model: &str, estimator: &Estimator, prompt: &str, max_cost: Option<f64>, spent: &mut f64, correlation: &mut Correlation) -> Result<String, OsintError> {
+    let estimate = estimator.worst_case_cost(estimator.prompt_tokens(&[crate::ANALYSIS_SYSTEM_PROMPT, prompt]));
+    if let Some(max_cost) = max_cost.filter(|&max_cost| *spent + estimate > max_cost) {
+        return Err(OsintError::Analysis(format!("correlating would cost up to ${:.4} after {} call(s), over --max-cost ${:.4}", *spent + estimate, correlation.calls, max_cost)));
+    }
+    *spent += estimate;
+    let (text, usage) = crate::complete(endpoint, model, prompt).await?;
+    correlation.calls += 1;
+    correlation.usage.prompt_tokens += usage.prompt_tokens;
+    correlation.usage.completion_tokens += usage.completion_tokens;
+    Ok(text)
+}
+
+/// A report as one prompt section headed by its target, condensed until it
+/// fits `budget` on its own.
+fn describe(estimator: &Estimator, report: &Value, budget: usize) -> String {
+    let heading = format!("Target {} ({}):\n", report["target"].as_str().unwrap_or("unknown"), report["source"].as_str().unwrap_or("unknown type"));
+    let (payload, _) = DataSummarizer::default().fit(report, |payload| estimator.prompt_tokens(&[&heading, payload]) <= budget);
+    format!("{}{}", heading, payload)
+}
+
+/// Groups `documents`, in order, into chunks of at most `budget` tokens. A
+/// document over the budget gets a chunk of its own.
+fn pack(estimator: &Estimator, documents: &[String], budget: usize) -> Vec<Vec<String>> {
+    let mut chunks: Vec<Vec<String>> = Vec::new();
+    let mut used = 0;
+    for document in documents {
+        let tokens = estimator.prompt_tokens(&[document]);
+        match chunks.last_mut() {
+            Some(chunk) if used + tokens <= budget => {
+                used += tokens;
+                chunk.push(document.clone());
+            }
+            _ => {
+                used = tokens;
+                chunks.push(vec![document.clone()]);
+            }
+        }
+    }
+    chunks
+}
+
+/// The Markdown file the correlation is saved as.
+pub fn markdown(reports: &[Value], correlation: &Correlation) -> String {
+    let targets: Vec<&str> = reports.iter().map(|report| report["target"].as_str().unwrap_or("unknown")).collect();
+    format!("# Correlation analysis\n\nTargets: {}\n\n{}\n", targets.join(", "), correlation.text.trim_end())
+}
@@ -0,0 +1,101 @@
+use serde_json::Value;
+use tiktoken_rs::CoreBPE;
+
+use crate::OsintError;
+
+/// Completion tokens requested from the model, which bounds the reply's cost.
+pub const MAX_COMPLETION_TOKENS: usize = 1024;
+/// Tokens the chat format adds around each message.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct Pricing {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl Pricing {
+    /// Published prices for the models this tool uses; unknown models are
+    /// priced like `gpt-4o` so estimates err on the expensive side.
+    pub fn for_model(model: &str) -> Pricing {
+        match model {
+            "gpt-4o-mini" => Pricing { input: 0.15, output: 0.60 },
+            "gpt-3.5-turbo" => Pricing { input: 0.50, output: 1.50 },
+            _ => Pricing { input: 2.50, output: 10.00 },
+        }
+    }
+
+    pub fn cost(&self, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+        (prompt_tokens as f64 * self.input + completion_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// Prompt and reply tokens a model accepts in one call.
+pub fn context_window(model: &str) -> usize {
+    match model {
+        "gpt-3.5-turbo" => 16_385,
+        _ => 128_000,
+    }
+}
+
+/// Token usage the API reports for a completed call.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+}
+
+impl Usage {
+    pub fn from_response(reply: &Value) -> Usage {
+        let tokens = |field: &str| reply["usage"][field].as_u64().unwrap_or(0) as usize;
+        Usage { prompt_tokens: tokens("prompt_tokens"), completion_tokens: tokens("completion_tokens") }
+    }
+}
+
+/// Counts prompt tokens the way the model does and keeps prompts within a
+/// cost ceiling.
+pub struct Estimator {
+    bpe: CoreBPE,
+    pricing: Pricing,
+}
+
+impl Estimator {
+    pub fn for_model(model: &str) -> Result<Estimator, OsintError> {
+        let bpe = tiktoken_rs::get_bpe_from_model(model).or_else(|_| tiktoken_rs::o200k_base()).map_err(|err| OsintError::Analysis(format!("no tokenizer for {}: {}", model, err)))?;
+        Ok(Estimator { bpe, pricing: Pricing::for_model(model) })
+    }
+
+    /// Prompt tokens for a conversation made of `messages`.
+    pub fn prompt_tokens(&self, messages: &[&str]) -> usize {
+        messages.iter().map(|message| self.bpe.encode_with_special_tokens(message).len() + MESSAGE_OVERHEAD_TOKENS).sum()
+    }
+
+    /// Worst-case cost of a call: the prompt plus a full-length reply.
+    pub fn worst_case_cost(&self, prompt_tokens: usize) -> f64 {
+        self.pricing.cost(prompt_tokens, MAX_COMPLETION_TOKENS)
+    }
+
+    /// Cuts `text` to the most tokens that keep the call within `max_cost`,
+    /// given `fixed_tokens` spent on the rest of the prompt. `None` when not
+    /// even an empty payload fits.
+    pub fn truncate_to_budget(&self, text: &str, fixed_tokens: usize, max_cost: f64) -> Option<String> {
+        let reply_cost = self.pricing.cost(0, MAX_COMPLETION_TOKENS);
+        let affordable = ((max_cost - reply_cost) * 1_000_000.0 / self.pricing.input).floor();
+        let fixed_tokens = fixed_tokens + MESSAGE_OVERHEAD_TOKENS;
+        if affordable < fixed_tokens as f64 {
+            return None;
+        }
+        let budget = affordable as usize - fixed_tokens;
+        let mut tokens = self.bpe.encode_with_special_tokens(text);
+        tokens.truncate(budget);
+        // A cut can land inside a multi-byte character; back off until it decodes.
+        while !tokens.is_empty() {
+            if let Ok(truncated) = self.bpe.decode(tokens.clone()) {
+                return Some(truncated);
+            }
+            tokens.pop();
+        }
+        Some(String::new())
+    }
+}
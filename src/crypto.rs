@@ -0,0 +1,107 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+use crate::OsintError;
+
+/// Read instead of prompting when set.
+pub const PASSPHRASE_ENV: &str = "OSINT_ENCRYPTION_KEY";
+
+const MAGIC: &[u8; 8] = b"OSINTENC";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+/// Magic, version, the three Argon2 parameters, salt and nonce.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// Encrypts every report saved from now on with `passphrase`. Called once
+/// from `--encrypt`, so a stdin batch only asks for it once.
+pub fn use_passphrase(passphrase: String) {
+    let _ = PASSPHRASE.set(passphrase);
+}
+
+pub fn passphrase_in_use() -> Option<&'static str> {
+    PASSPHRASE.get().map(String::as_str)
+}
+
+/// The passphrase from `OSINT_ENCRYPTION_KEY`, else asked for on the
+/// terminal without echo, twice when `confirm` is set.
+pub fn passphrase(confirm: bool) -> Result<String, OsintError> {
+    if let Some(passphrase) = env::var(PASSPHRASE_ENV).ok().filter(|passphrase| !passphrase.is_empty()) {
+        return Ok(passphrase);
+    }
+    let missing = |_| OsintError::MissingApiKey(format!("{} (no terminal to ask for the passphrase on)", PASSPHRASE_ENV));
+    let passphrase = rpassword::prompt_password("Report passphrase: ").map_err(missing)?;
+    if passphrase.is_empty() {
+        return Err(OsintError::Crypto("the passphrase is empty".to_string()));
+    }
+    if confirm && rpassword::prompt_password("Repeat the passphrase: ").map_err(missing)? != passphrase {
+        return Err(OsintError::Crypto("the passphrases do not match".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// `report.json.enc` for `report.json`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".enc");
+    PathBuf::from(name)
+}
+
+/// Encrypts with Argon2id's default cost, which takes a moment on purpose.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, OsintError> {
+    encrypt_with(plaintext, passphrase, Params::default())
+}
+
+/// Encrypts `plaintext` with XChaCha20-Poly1305 under a key derived from
+/// `passphrase` by Argon2id with `params`. The header carries what
+/// decrypting needs besides the passphrase and is authenticated too.
+pub fn encrypt_with(plaintext: &[u8], passphrase: &str, params: Params) -> Result<Vec<u8>, OsintError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    for value in [params.m_cost(), params.t_cost(), params.p_cost()] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+    let cipher = cipher(passphrase, &salt, params)?;
+    let ciphertext = cipher.encrypt(&nonce, Payload { msg: plaintext, aad: &header }).map_err(|_| OsintError::Crypto("encryption failed".to_string()))?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, OsintError> {
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err(OsintError::Crypto("not an encrypted report".to_string()));
+    }
+    if data[MAGIC.len()] != FORMAT_VERSION {
+        return Err(OsintError::Crypto(format!("unsupported format version {}", data[MAGIC.len()])));
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let number = |index: usize| {
+        let start = MAGIC.len() + 1 + 4 * index;
+        u32::from_le_bytes(header[start..start + 4].try_into().unwrap_or_default())
+    };
+    let params = Params::new(number(0), number(1), number(2), None).map_err(|err| OsintError::Crypto(format!("bad key derivation parameters: {}", err)))?;
+    let salt_start = MAGIC.len() + 1 + 3 * 4;
+    let salt = &header[salt_start..salt_start + SALT_LEN];
+    let nonce = XNonce::from_slice(&header[salt_start + SALT_LEN..]);
+    cipher(passphrase, salt, params)?.decrypt(nonce, Payload { msg: ciphertext, aad: header }).map_err(|_| OsintError::Crypto("wrong passphrase, or the file was modified".to_string()))
+}
+
+fn cipher(passphrase: &str, salt: &[u8], params: Params) -> Result<XChaCha20Poly1305, OsintError> {
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(passphrase.as_bytes(), salt, &mut key).map_err(|err| OsintError::Crypto(format!("key derivation failed: {}", err)))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
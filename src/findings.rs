@@ -0,0 +1,427 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Ports whose exposure to the internet is almost always worth a look.
+const RISKY_PORTS: &[u64] = &[21, 23, 445, 1433, 3306, 3389, 5432, 5900, 6379, 9200, 11211, 27017];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+    /// The kind of finding, one of [`RULES`], for formats that group by it.
+    #[serde(skip)]
+    pub rule: &'static str,
+}
+
+/// The kinds of finding, as (id, name, description).
+pub const RULES: &[(&str, &str, &str)] = &[
+    ("exposed-service", "ExposedService", "A service that is rarely meant to face the internet, such as a database or remote desktop, is open."),
+    ("known-vulnerability", "KnownVulnerability", "A CVE is reported for the software the host exposes."),
+    ("breach-exposure", "BreachExposure", "The address appears in data breaches or pastes."),
+    ("email-authentication", "EmailAuthentication", "SPF, DKIM or DMARC is missing or failing, so mail from the domain can be spoofed."),
+    ("zone-transfer", "ZoneTransfer", "A nameserver hands the whole zone to anyone who asks."),
+    ("missing-security-header", "MissingSecurityHeader", "The site leaves out HTTP security headers."),
+    ("certificate-problem", "CertificateProblem", "The TLS certificate is expired, about to expire or self-signed."),
+    ("malicious-verdict", "MaliciousVerdict", "A reputation service classifies the target as malicious."),
+    ("abuse-reports", "AbuseReports", "The IP has been reported for abuse."),
+    ("threat-intelligence", "ThreatIntelligence", "Threat intelligence pulses mention the target."),
+    ("email-reputation", "EmailReputation", "The address has a poor reputation or is linked to suspicious activity."),
+    ("archived-leak", "ArchivedLeak", "Archived URLs point at files that may leak secrets or internals."),
+    ("disallowed-path", "DisallowedPath", "robots.txt names paths that look sensitive."),
+    ("code-mention", "CodeMention", "Public code mentions the target, possibly with credentials."),
+    ("anonymized-network", "AnonymizedNetwork", "The IP is a Tor exit, a VPN or a datacenter address."),
+    ("blocklisted", "Blocklisted", "The IP is listed on a DNS blocklist."),
+    ("known-phish", "KnownPhish", "The domain or URL is a known phishing site."),
+    ("known-malicious-infrastructure", "KnownMaliciousInfrastructure", "abuse.ch lists the target as malware infrastructure or a malware sample."),
+];
+
+impl Severity {
+    fn weight(self) -> u32 {
+        match self {
+            Severity::Low => 1,
+            Severity::Medium => 3,
+            Severity::High => 10,
+        }
+    }
+}
+
+impl Finding {
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Finding { severity, message: message.into(), rule: "" }
+    }
+}
+
+/// Extracts the notable findings from a source's result.
+pub fn collect(recon_type: &str, data: &Value) -> Vec<Finding> {
+    match recon_type {
+        "hibp" => tagged("breach-exposure", breaches(data)),
+        "all" => combined(data),
+        "shodan" => tagged("exposed-service", risky_ports(data["ports"].as_array().into_iter().flatten())).into_iter().chain(tagged("known-vulnerability", vulnerabilities(data))).collect(),
+        "zoomeye" | "binaryedge" => tagged("exposed-service", risky_ports(data["ports"].as_array().into_iter().flatten())),
+        "censys" => tagged("exposed-service", risky_ports(data["services"].as_array().into_iter().flatten().map(|service| &service["port"]))),
+        "emailauth" => tagged("email-authentication", failed_mechanisms(data)),
+        "axfr" => tagged("zone-transfer", zone_transfers(data)),
+        "http" => tagged("missing-security-header", missing_security_headers(data)),
+        "tlscert" => tagged("certificate-problem", certificate_problems(data)),
+        "virustotal" => tagged("malicious-verdict", engine_detections(data)),
+        "abuseipdb" => tagged("abuse-reports", abuse_confidence(data)),
+        "greynoise" => tagged("malicious-verdict", noise_classification(data)),
+        "anonymity" => tagged("anonymized-network", anonymized_network(data)),
+        "dnsbl" => tagged("blocklisted", blocklistings(data)),
+        "phish" => tagged("known-phish", known_phish(data)),
+        "threatfox" => tagged("known-malicious-infrastructure", abuse_ch_matches(data)),
+        "urlscan" => tagged("malicious-verdict", urlscan_verdict(data)),
+        "otx" => tagged("threat-intelligence", otx_pulses(data)),
+        "onyphe" => tagged("threat-intelligence", onyphe_threatlists(data)),
+        "hunter" => tagged("breach-exposure", pivoted_breaches(data)),
+        "intelx" => tagged("breach-exposure", intelx_records(data)),
+        "leakcheck" => tagged("breach-exposure", leaked_credentials(data)),
+        "emailrep" => tagged("email-reputation", email_reputation(data)),
+        "wayback" => tagged("archived-leak", archived_leaks(data)),
+        "robots" => tagged("disallowed-path", disallowed_paths(data)),
+        "github" => tagged("code-mention", code_mentions(data)),
+        _ => Vec::new(),
+    }
+}
+
+fn tagged(rule: &'static str, findings: Vec<Finding>) -> Vec<Finding> {
+    findings.into_iter().map(|finding| Finding { rule, ..finding }).collect()
+}
+
+/// Sums the findings into one score, weighting high severity heaviest.
+pub fn risk_score(findings: &[Finding]) -> u32 {
+    findings.iter().map(|finding| finding.severity.weight()).sum()
+}
+
+/// Findings from every source that succeeded in an `all` run.
+fn combined(data: &Value) -> Vec<Finding> {
+    data["sources"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, entry)| entry["status"] == "ok")
+        .flat_map(|(source, entry)| collect(source, &entry["data"]))
+        .collect()
+}
+
+/// Breaches rank by the data they leaked: credentials or payment data high,
+/// contact details alone medium.
+fn breaches(data: &Value) -> Vec<Finding> {
+    let (serious, contact): (Vec<&Value>, Vec<&Value>) = data["breaches"].as_array().into_iter().flatten().partition(|breach| crate::providers::hibp::exposes_secrets(breach));
+    let names = |breaches: &[&Value]| breaches.iter().filter_map(|breach| breach["Name"].as_str()).collect::<Vec<_>>().join(", ");
+    let mut findings = Vec::new();
+    if !serious.is_empty() {
+        findings.push(Finding::new(Severity::High, format!("{} breach(es) exposing credentials or payment data: {}", serious.len(), names(&serious))));
+    }
+    if !contact.is_empty() {
+        findings.push(Finding::new(Severity::Medium, format!("{} breach(es) exposing contact details: {}", contact.len(), names(&contact))));
+    }
+    let pastes = data["pastes"].as_array().map_or(0, Vec::len);
+    if pastes > 0 {
+        findings.push(Finding::new(Severity::Medium, format!("address appears in {} paste(s)", pastes)));
+    }
+    findings
+}
+
+fn risky_ports<'a>(ports: impl Iterator<Item = &'a Value>) -> Vec<Finding> {
+    ports
+        .filter_map(Value::as_u64)
+        .filter(|port| RISKY_PORTS.contains(port))
+        .map(|port| Finding::new(Severity::High, format!("risky port {} open", port)))
+        .collect()
+}
+
+/// The CVEs of a Shodan result; critical ones rank as high severity.
+fn vulnerabilities(data: &Value) -> Vec<Finding> {
+    data["cves"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|cve| {
+            let severity = match cve["severity"].as_str() {
+                Some("critical") => Severity::High,
+                Some("high") => Severity::Medium,
+                _ => Severity::Low,
+            };
+            let score = cve["cvss"].as_f64().map_or_else(|| "unscored".to_string(), |score| format!("CVSS {:.1}", score));
+            Some(Finding::new(severity, format!("{} ({})", cve["id"].as_str()?, score)))
+        })
+        .collect()
+}
+
+fn failed_mechanisms(data: &Value) -> Vec<Finding> {
+    let dkim = data["dkim"].as_array().into_iter().flatten();
+    [("SPF", &data["spf"]), ("DMARC", &data["dmarc"])]
+        .into_iter()
+        .chain(dkim.map(|check| ("DKIM", check)))
+        .filter(|(_, check)| check["status"] == "fail")
+        .map(|(name, check)| {
+            let note = check["notes"][0].as_str().unwrap_or("check failed");
+            Finding::new(Severity::Medium, format!("{}: {}", name, note))
+        })
+        .chain(spoofability(&data["assessment"]))
+        .collect()
+}
+
+fn spoofability(assessment: &Value) -> Option<Finding> {
+    let severity = match assessment["grade"].as_str()? {
+        "spoofable" => Severity::High,
+        "partially_protected" => Severity::Low,
+        _ => return None,
+    };
+    Some(Finding::new(severity, format!("email spoofing: {}", assessment["summary"].as_str().unwrap_or_default())))
+}
+
+fn missing_security_headers(data: &Value) -> Vec<Finding> {
+    missing_headers(data).into_iter().map(|header| Finding::new(Severity::Low, format!("missing security header {}", header))).collect()
+}
+
+/// Security headers missing from the page served over HTTPS, or from the
+/// first page reached when nothing was. Headers like HSTS only count there.
+pub fn missing_headers(data: &Value) -> Vec<&str> {
+    let probes: Vec<&Value> = data["probes"].as_array().into_iter().flatten().filter(|probe| probe["final_url"].is_string()).collect();
+    let page = probes.iter().find(|probe| probe["final_url"].as_str().is_some_and(|url| url.starts_with("https://"))).or(probes.first());
+    page.and_then(|page| page["security_headers"]["missing"].as_array()).into_iter().flatten().filter_map(Value::as_str).collect()
+}
+
+fn certificate_problems(data: &Value) -> Vec<Finding> {
+    let leaf = &data["chain"][0];
+    let mut findings = Vec::new();
+    let days = leaf["days_until_expiry"].as_i64().unwrap_or_default();
+    if leaf["expired"] == true {
+        findings.push(Finding::new(Severity::High, format!("TLS certificate expired {} day(s) ago", -days)));
+    } else if leaf["expiring_soon"] == true {
+        findings.push(Finding::new(Severity::Medium, format!("TLS certificate expires in {} day(s)", days)));
+    }
+    if leaf["self_signed"] == true {
+        findings.push(Finding::new(Severity::Medium, "self-signed TLS certificate"));
+    }
+    findings
+}
+
+fn engine_detections(data: &Value) -> Vec<Finding> {
+    let stats = &data["last_analysis_stats"];
+    [("malicious", Severity::High), ("suspicious", Severity::Medium)]
+        .into_iter()
+        .filter_map(|(verdict, severity)| {
+            let engines = stats[verdict].as_u64().filter(|&n| n > 0)?;
+            Some(Finding::new(severity, format!("flagged {} by {} engine(s)", verdict, engines)))
+        })
+        .collect()
+}
+
+fn abuse_confidence(data: &Value) -> Vec<Finding> {
+    let score = data["abuse_confidence_score"].as_u64().unwrap_or(0);
+    let Some(severity) = crate::providers::abuseipdb::severity(score) else {
+        return Vec::new();
+    };
+    let reports = data["total_reports"].as_u64().unwrap_or(0);
+    vec![Finding::new(severity, format!("AbuseIPDB confidence score {}% from {} report(s)", score, reports))]
+}
+
+fn noise_classification(data: &Value) -> Vec<Finding> {
+    if data["classification"] != "malicious" {
+        return Vec::new();
+    }
+    let actor = data["actor"].as_str().unwrap_or("an unknown actor");
+    vec![Finding::new(Severity::High, format!("GreyNoise classifies the IP as malicious ({})", actor))]
+}
+
+/// Traffic from a Tor exit or a VPN hides who is behind it; a datacenter
+/// address is at least not someone's home connection.
+fn anonymized_network(data: &Value) -> Vec<Finding> {
+    let network = data["network"].as_str().unwrap_or("a known provider");
+    let finding = match data["classification"].as_str() {
+        Some("tor-exit") => Finding::new(Severity::High, "the IP is a Tor exit node"),
+        Some("likely-vpn") => Finding::new(Severity::Medium, format!("the IP likely belongs to a VPN ({})", network)),
+        Some("datacenter") => Finding::new(Severity::Low, format!("the IP is in a datacenter ({})", network)),
+        _ => return Vec::new(),
+    };
+    vec![finding]
+}
+
+/// A listing for spam or a compromised host ranks high; an end-user range
+/// on a policy list such as Spamhaus PBL only low.
+fn blocklistings(data: &Value) -> Vec<Finding> {
+    data["checks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|check| check["status"] == "listed")
+        .map(|check| {
+            let severity = if crate::providers::dnsbl::policy_only(check) { Severity::Low } else { Severity::High };
+            let reasons = check["reasons"].as_array().into_iter().flatten().filter_map(Value::as_str).collect::<Vec<_>>().join("; ");
+            Finding::new(severity, format!("listed on {}: {}", check["list"].as_str().unwrap_or("a blocklist"), reasons))
+        })
+        .collect()
+}
+
+fn known_phish(data: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let count = data["openphish"]["match_count"].as_u64().unwrap_or(0);
+    if count > 0 {
+        findings.push(Finding::new(Severity::High, format!("{} URL(s) on the OpenPhish feed", count)));
+    }
+    let phishtank = &data["phishtank"];
+    if phishtank["in_database"] == true && phishtank["valid"] != false {
+        let brand = phishtank["target"].as_str().map_or_else(String::new, |brand| format!(" targeting {}", brand));
+        let verified = match phishtank["verified_at"].as_str() {
+            Some(at) => format!(", verified {}", at),
+            None => String::new(),
+        };
+        findings.push(Finding::new(Severity::High, format!("PhishTank lists the URL as a phish{}{}", brand, verified)));
+    }
+    findings
+}
+
+/// One finding per malware family ThreatFox or MalwareBazaar ties the target to.
+fn abuse_ch_matches(data: &Value) -> Vec<Finding> {
+    let service = if data["service"] == "malwarebazaar" { "MalwareBazaar" } else { "ThreatFox" };
+    let mut families: Vec<(String, Vec<&Value>)> = Vec::new();
+    for entry in data["matches"].as_array().into_iter().flatten() {
+        let family = entry["family"].as_str().unwrap_or("unknown malware").to_string();
+        match families.iter_mut().find(|(seen, _)| *seen == family) {
+            Some((_, entries)) => entries.push(entry),
+            None => families.push((family, vec![entry])),
+        }
+    }
+    families
+        .into_iter()
+        .map(|(family, entries)| {
+            let confidence = entries.iter().filter_map(|entry| entry["confidence"].as_u64()).max().map_or_else(String::new, |confidence| format!(", confidence {}%", confidence));
+            let threat = entries.iter().find_map(|entry| entry["threat_type"].as_str()).map_or_else(String::new, |threat| format!(" {}", threat));
+            Finding::new(Severity::High, format!("{} ties the target to {}{} ({} entr{}{})", service, family, threat, entries.len(), if entries.len() == 1 { "y" } else { "ies" }, confidence))
+        })
+        .collect()
+}
+
+fn urlscan_verdict(data: &Value) -> Vec<Finding> {
+    if data["verdicts"]["malicious"] != true {
+        return Vec::new();
+    }
+    let score = data["verdicts"]["score"].as_i64().unwrap_or(0);
+    vec![Finding::new(Severity::High, format!("urlscan verdict malicious (score {})", score))]
+}
+
+/// A nameserver that hands its zone to anyone is a misconfiguration.
+fn zone_transfers(data: &Value) -> Vec<Finding> {
+    data["attempts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|attempt| attempt["outcome"] == "transferred")
+        .map(|attempt| Finding::new(Severity::High, format!("{} ({}) allows zone transfers ({} records)", attempt["nameserver"].as_str().unwrap_or("a nameserver"), attempt["address"].as_str().unwrap_or("unknown address"), attempt["record_count"])))
+        .collect()
+}
+
+fn otx_pulses(data: &Value) -> Vec<Finding> {
+    let pulses = data["pulse_count"].as_u64().unwrap_or(0);
+    let families: Vec<&str> = data["malware_families"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+    let mut findings = Vec::new();
+    if pulses > 0 {
+        findings.push(Finding::new(Severity::Medium, format!("referenced in {} OTX pulse(s)", pulses)));
+    }
+    if !families.is_empty() {
+        findings.push(Finding::new(Severity::High, format!("associated with malware families: {}", families.join(", "))));
+    }
+    findings
+}
+
+/// One finding per threat list Onyphe has the target on.
+fn onyphe_threatlists(data: &Value) -> Vec<Finding> {
+    data["threatlists"].as_array().into_iter().flatten().filter_map(Value::as_str).map(|list| Finding::new(Severity::High, format!("Onyphe lists the target on threat list {}", list))).collect()
+}
+
+/// One finding per IntelX bucket group holding records of the target;
+/// leaks are high severity, pastes and darknet mentions medium.
+fn intelx_records(data: &Value) -> Vec<Finding> {
+    data["buckets"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(group, bucket)| bucket["count"].as_u64().filter(|&count| count > 0).map(|count| (group, count)))
+        .map(|(group, count)| {
+            let severity = if group == "leaks" { Severity::High } else { Severity::Medium };
+            Finding::new(severity, format!("IntelX holds {} record(s) of the target in {}", count, group))
+        })
+        .collect()
+}
+
+/// Leak sources that exposed a password for the target, then those that
+/// exposed other fields only.
+fn leaked_credentials(data: &Value) -> Vec<Finding> {
+    let (with_password, others): (Vec<&Value>, Vec<&Value>) = data["sources"].as_array().into_iter().flatten().partition(|source| source["password_present"] == true);
+    let names = |sources: &[&Value]| sources.iter().filter_map(|source| source["source"].as_str()).collect::<Vec<_>>().join(", ");
+    let mut findings = Vec::new();
+    if !with_password.is_empty() {
+        findings.push(Finding::new(Severity::High, format!("{} leak(s) exposing a password: {}", with_password.len(), names(&with_password))));
+    }
+    if !others.is_empty() {
+        findings.push(Finding::new(Severity::Medium, format!("{} leak(s) exposing other fields: {}", others.len(), names(&others))));
+    }
+    findings
+}
+
+/// Breaches of the addresses a `--pivot-hibp` run checked.
+fn pivoted_breaches(data: &Value) -> Vec<Finding> {
+    data["hibp"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(email, result)| Some((email, result["breaches"].as_array()?)))
+        .filter(|(_, breaches)| !breaches.is_empty())
+        .map(|(email, breaches)| {
+            let severity = if breaches.iter().any(crate::providers::hibp::exposes_secrets) { Severity::High } else { Severity::Medium };
+            Finding::new(severity, format!("{}: {} breach(es) found", email, breaches.len()))
+        })
+        .collect()
+}
+
+fn email_reputation(data: &Value) -> Vec<Finding> {
+    if data["suspicious"] != true {
+        return Vec::new();
+    }
+    let reputation = data["reputation"].as_str().unwrap_or("unknown");
+    vec![Finding::new(Severity::Medium, format!("EmailRep flags the address as suspicious (reputation {})", reputation))]
+}
+
+fn archived_leaks(data: &Value) -> Vec<Finding> {
+    data["interesting"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(extension, urls)| Some((extension, urls.as_array()?.len())))
+        .map(|(extension, count)| Finding::new(Severity::Medium, format!("{} archived {} file(s) in the Wayback Machine", count, extension)))
+        .collect()
+}
+
+fn disallowed_paths(data: &Value) -> Vec<Finding> {
+    data["interesting"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["path"].as_str())
+        .map(|path| Finding::new(Severity::Low, format!("robots.txt disallows {}", path)))
+        .collect()
+}
+
+fn code_mentions(data: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let repositories = data["repositories"].as_array().map(Vec::len).unwrap_or(0);
+    if repositories > 0 {
+        let files = data["fetched"].as_u64().unwrap_or(0);
+        findings.push(Finding::new(Severity::Low, format!("mentioned in {} public file(s) across {} GitHub repo(s)", files, repositories)));
+    }
+    for search in data["secrets"].as_array().into_iter().flatten().filter(|search| search["fetched"].as_u64().unwrap_or(0) > 0) {
+        findings.push(Finding::new(Severity::Medium, format!("{} public GitHub file(s) mention it next to `{}`", search["fetched"], search["pattern"].as_str().unwrap_or_default())));
+    }
+    findings
+}
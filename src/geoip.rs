@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use maxminddb::{geoip2, Reader};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::iocs;
+use crate::OsintError;
+
+/// Where an IP is and who runs it, whichever database or API answered.
+/// Fields the source does not know are omitted.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct GeoLocation {
+    /// ISO 3166-1 alpha-2 code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    /// The ISP or other organization holding the address.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+}
+
+impl GeoLocation {
+    pub fn is_empty(&self) -> bool {
+        *self == GeoLocation::default()
+    }
+}
+
+static DATABASE: OnceLock<Option<GeoIp>> = OnceLock::new();
+
+/// Makes the databases opened at startup available to the `geoip` source.
+pub fn install(database: Option<GeoIp>) -> Option<&'static GeoIp> {
+    DATABASE.get_or_init(|| database).as_ref()
+}
+
+/// The databases given to `install`, if any.
+pub fn installed() -> Option<&'static GeoIp> {
+    DATABASE.get().and_then(Option::as_ref)
+}
+
+/// The IP target and every IP found in `data`, such as resolved A records.
+pub fn report_ips(target: &str, data: &Value) -> BTreeSet<String> {
+    let mut ips = iocs::extract(data).ips;
+    if target.parse::<IpAddr>().is_ok() {
+        ips.insert(target.to_string());
+    }
+    ips
+}
+
+/// Adds `locations`, keyed by IP, to `data` as `geo`. Non-object results and
+/// empty maps are left alone.
+pub fn attach(mut data: Value, locations: Map<String, Value>) -> Value {
+    debug!(located = locations.len(), "geo enrichment finished");
+    if !locations.is_empty() && data.is_object() {
+        data["geo"] = Value::Object(locations);
+    }
+    data
+}
+
+/// Offline location and network lookups against local GeoLite2 City and ASN
+/// databases. Either database alone is enough; fields it lacks are omitted.
+pub struct GeoIp {
+    readers: Vec<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    /// Opens the databases given with `--geoip-db`, else those listed in
+    /// `GEOIP_DB`. Returns `None` when neither names a database.
+    pub fn open(paths: &[PathBuf]) -> Result<Option<Self>, OsintError> {
+        let mut paths = paths.to_vec();
+        if paths.is_empty() {
+            paths = env::var_os("GEOIP_DB").map(|value| env::split_paths(&value).filter(|path| !path.as_os_str().is_empty()).collect()).unwrap_or_default();
+        }
+        if paths.is_empty() {
+            debug!("no GeoIP database configured");
+            return Ok(None);
+        }
+        let readers = paths
+            .iter()
+            .map(|path| Reader::open_readfile(path).map_err(|err| OsintError::GeoIp(format!("{}: {}", path.display(), err))))
+            .collect::<Result<_, _>>()?;
+        Ok(Some(GeoIp { readers }))
+    }
+
+    /// Country, region, city, coordinates, ASN and organization of `ip`, or
+    /// `None` when no database knows it (private and reserved ranges, for one).
+    /// A City record decodes from an ASN database as an empty one and the
+    /// other way round, so each database only fills in the fields it has.
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        let mut geo = GeoLocation::default();
+        for reader in &self.readers {
+            let Ok(result) = reader.lookup(ip) else {
+                continue;
+            };
+            if let Ok(Some(city)) = result.decode::<geoip2::City>() {
+                geo.country = city.country.iso_code.map(str::to_string).or(geo.country.take());
+                geo.country_name = city.country.names.english.map(str::to_string).or(geo.country_name.take());
+                geo.region = city.subdivisions.first().and_then(|subdivision| subdivision.names.english).map(str::to_string).or(geo.region.take());
+                geo.city = city.city.names.english.map(str::to_string).or(geo.city.take());
+                geo.latitude = city.location.latitude.or(geo.latitude);
+                geo.longitude = city.location.longitude.or(geo.longitude);
+            }
+            if let Ok(Some(asn)) = result.decode::<geoip2::Asn>() {
+                geo.asn = asn.autonomous_system_number.or(geo.asn);
+                geo.organization = asn.autonomous_system_organization.map(str::to_string).or(geo.organization.take());
+            }
+        }
+        (!geo.is_empty()).then_some(geo)
+    }
+
+    /// Adds a `geo` object keyed by IP for the target and every IP found in
+    /// `data`.
+    pub fn enrich(&self, target: &str, data: Value) -> Value {
+        let locations = report_ips(target, &data).into_iter().filter_map(|ip| Some((ip.clone(), serde_json::to_value(self.lookup(ip.parse().ok()?)?).ok()?))).collect();
+        attach(data, locations)
+    }
+}
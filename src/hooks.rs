@@ -0,0 +1,90 @@
+use std::io;
+use std::process::Stdio;
+
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info, warn};
+
+/// A user command run after each source completes. It receives the source's
+/// JSON on stdin and may print JSON to stdout to enrich the result.
+pub struct Hook {
+    pub command: String,
+    pub timeout: Duration,
+}
+
+impl Hook {
+    /// Runs the hook and merges its output into `data` under `hook_output`.
+    /// Hook failures are logged and leave `data` untouched.
+    pub async fn apply(&self, target: &str, source: &str, data: Value) -> Value {
+        match self.run(target, source, &data).await {
+            Ok(Some(output)) => merge_output(data, output),
+            Ok(None) => data,
+            Err(err) => {
+                warn!(hook = %self.command, source, %err, "hook failed");
+                data
+            }
+        }
+    }
+
+    async fn run(&self, target: &str, source: &str, data: &Value) -> io::Result<Option<Value>> {
+        let mut child = shell(&self.command)
+            .env("OSINT_TARGET", target)
+            .env("OSINT_SOURCE", source)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let input = data.to_string();
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let finished = timeout(self.timeout, async move {
+            // A hook that ignores its input closes the pipe early; that is fine.
+            match stdin.write_all(input.as_bytes()).await {
+                Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+                _ => drop(stdin),
+            }
+            child.wait_with_output().await
+        })
+        .await;
+        let output = finished.map_err(|_| io::Error::new(io::ErrorKind::TimedOut, format!("timed out after {:?}", self.timeout)))??;
+
+        debug!(source, stderr = %String::from_utf8_lossy(&output.stderr).trim(), "hook stderr");
+        if !output.status.success() {
+            return Err(io::Error::other(format!("exited with {}", output.status)));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Ok(None);
+        }
+        match serde_json::from_str(stdout.trim()) {
+            Ok(value) => {
+                info!(source, "hook output merged");
+                Ok(Some(value))
+            }
+            Err(err) => {
+                warn!(source, %err, "hook printed non-JSON output, ignoring it");
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+    shell.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(command);
+    shell
+}
+
+/// Object results gain a `hook_output` key; other results are wrapped.
+fn merge_output(data: Value, output: Value) -> Value {
+    match data {
+        Value::Object(mut map) => {
+            map.insert("hook_output".to_string(), output);
+            Value::Object(map)
+        }
+        other => json!({ "data": other, "hook_output": output }),
+    }
+}
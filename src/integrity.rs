@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::OsintError;
+
+/// The outcome of checking a report against its sidecars. `signature` is
+/// None when it was not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verification {
+    pub checksum: bool,
+    pub signature: Option<bool>,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.checksum && self.signature != Some(false)
+    }
+}
+
+/// `report.json.sha256` next to `report.json`.
+pub fn checksum_path(report: &Path) -> PathBuf {
+    sidecar(report, "sha256")
+}
+
+/// `report.json.sig` next to `report.json`.
+pub fn signature_path(report: &Path) -> PathBuf {
+    sidecar(report, "sig")
+}
+
+fn sidecar(report: &Path, extension: &str) -> PathBuf {
+    let mut path = report.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Writes the checksum sidecar of the saved `report`, in `sha256sum` format
+/// so `sha256sum -c` can check it too, and with `signing_key` (an Ed25519
+/// PKCS#8 PEM file) the base64 signature of the same bytes.
+pub fn seal(report: &Path, signing_key: Option<&Path>) -> Result<(), OsintError> {
+    let bytes = fs::read(report)?;
+    let name = report.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    fs::write(checksum_path(report), format!("{}  {}\n", sha256_hex(&bytes), name))?;
+    if let Some(key_path) = signing_key {
+        let key = SigningKey::from_pkcs8_pem(&fs::read_to_string(key_path)?).map_err(|err| OsintError::Config(format!("{}: not an Ed25519 PKCS#8 private key ({})", key_path.display(), err)))?;
+        fs::write(signature_path(report), format!("{}\n", STANDARD.encode(key.sign(&bytes).to_bytes())))?;
+    }
+    info!(path = %report.display(), signed = signing_key.is_some(), "report sealed");
+    Ok(())
+}
+
+/// Checks `report` against its checksum sidecar and, given `public_key`
+/// (an Ed25519 SPKI PEM file), against its signature.
+pub fn verify(report: &Path, public_key: Option<&Path>) -> Result<Verification, OsintError> {
+    let bytes = fs::read(report)?;
+    let recorded = fs::read_to_string(checksum_path(report)).map_err(|_| OsintError::Integrity(format!("no checksum at {}; was the report saved with --sign?", checksum_path(report).display())))?;
+    let checksum = recorded.split_whitespace().next().is_some_and(|hex| hex.eq_ignore_ascii_case(&sha256_hex(&bytes)));
+    let Some(key_path) = public_key else {
+        return Ok(Verification { checksum, signature: None });
+    };
+    let key = VerifyingKey::from_public_key_pem(&fs::read_to_string(key_path)?).map_err(|err| OsintError::Config(format!("{}: not an Ed25519 public key ({})", key_path.display(), err)))?;
+    let encoded = fs::read_to_string(signature_path(report)).map_err(|_| OsintError::Integrity(format!("no signature at {}; was the report saved with --signing-key?", signature_path(report).display())))?;
+    let signature = STANDARD.decode(encoded.trim()).ok().and_then(|raw| Signature::from_slice(&raw).ok()).is_some_and(|signature| key.verify(&bytes, &signature).is_ok());
+    Ok(Verification { checksum, signature: Some(signature) })
+}
@@ -0,0 +1,55 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::target::{self, TargetKind};
+
+/// Keys whose hash-shaped values are hashes of a file. Hex strings under any
+/// other key, such as Gravatar's hash of an email or a leaked password hash,
+/// would otherwise pass for malware samples in MISP and STIX.
+const FILE_HASH_KEYS: &[&str] = &["md5", "sha1", "sha256", "md5_hash", "sha1_hash", "sha256_hash", "malware_samples"];
+/// Keys holding certificates, whose `sha256` is a fingerprint, not a file's.
+const CERTIFICATE_KEYS: &[&str] = &["chain", "last_https_certificate"];
+
+/// Indicators found anywhere in a report, deduplicated and sorted.
+#[derive(Serialize, Debug, Default)]
+pub struct Iocs {
+    pub domains: BTreeSet<String>,
+    pub ips: BTreeSet<String>,
+    pub urls: BTreeSet<String>,
+    pub hashes: BTreeSet<String>,
+    pub emails: BTreeSet<String>,
+}
+
+/// Walks every string in `data` and keeps those shaped like an indicator.
+/// Domains must sit under a known public suffix so that product names and
+/// other dotted strings are not picked up, and hashes must sit under one of
+/// `FILE_HASH_KEYS` outside a certificate.
+pub fn extract(data: &Value) -> Iocs {
+    let mut iocs = Iocs::default();
+    collect(data, None, false, &mut iocs);
+    iocs
+}
+
+/// `key` is that of the nearest enclosing object entry.
+fn collect(value: &Value, key: Option<&str>, in_certificate: bool, iocs: &mut Iocs) {
+    match value {
+        Value::String(text) => {
+            let text = text.trim();
+            match target::classify(text) {
+                TargetKind::Ip => iocs.ips.insert(text.to_string()),
+                TargetKind::Url => iocs.urls.insert(text.to_string()),
+                TargetKind::Hash if !in_certificate && key.is_some_and(|key| FILE_HASH_KEYS.contains(&key)) => iocs.hashes.insert(text.to_ascii_lowercase()),
+                TargetKind::Email if target::is_email(text) => iocs.emails.insert(text.to_ascii_lowercase()),
+                TargetKind::Domain if text.contains('.') && target::is_registrable_domain(text) => {
+                    iocs.domains.insert(text.trim_end_matches('.').to_ascii_lowercase())
+                }
+                _ => false,
+            };
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect(item, key, in_certificate, iocs)),
+        Value::Object(map) => map.iter().for_each(|(key, item)| collect(item, Some(key), in_certificate || CERTIFICATE_KEYS.contains(&key.as_str()), iocs)),
+        _ => {}
+    }
+}
@@ -0,0 +1,65 @@
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+const REDACTED: &str = "REDACTED";
+
+/// Query parameters whose values must never reach the logs.
+const SENSITIVE_PARAMS: &[&str] = &["key", "apikey", "api_key", "access_key", "token", "access_token", "secret", "password"];
+
+/// Installs the global tracing subscriber. `RUST_LOG` takes precedence over
+/// the `-v` count when set.
+pub fn init(verbosity: u8, json: bool) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_directive(verbosity)));
+    subscriber(filter, json, std::io::stderr).init();
+}
+
+/// The subscriber `init` installs, writing to `writer`, so tests can read
+/// what it logs.
+pub fn subscriber<W>(filter: EnvFilter, json: bool, writer: W) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer).with_target(false);
+    if json {
+        Box::new(builder.json().finish())
+    } else {
+        Box::new(builder.finish())
+    }
+}
+
+pub fn default_directive(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "osint_recon_tool=warn",
+        1 => "osint_recon_tool=info",
+        _ => "osint_recon_tool=debug",
+    }
+}
+
+/// Returns `raw` with credentials stripped so it is safe to log.
+pub fn redact_url(raw: &str) -> String {
+    let Ok(mut url) = Url::parse(raw) else {
+        // Without a parse we cannot tell secrets apart, so drop the whole query.
+        return raw.split('?').next().unwrap_or_default().to_string();
+    };
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if is_sensitive(&name) { REDACTED.to_string() } else { value.into_owned() };
+                (name.into_owned(), value)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+fn is_sensitive(param: &str) -> bool {
+    SENSITIVE_PARAMS.iter().any(|p| p.eq_ignore_ascii_case(param))
+}
@@ -1,120 +1,1724 @@
-use reqwest::{Client, StatusCode};
-use serde_json::Value;
-use clap::{Arg, Command};
-use tokio;
-use openai_rs::{Client as OpenAIClient, ChatMessage};
+mod ai;
+mod breaker;
+mod config;
+mod correlate;
+mod cost;
+mod crypto;
+mod findings;
+mod geoip;
+mod hooks;
+mod integrity;
+mod iocs;
+mod logging;
+mod misp;
+mod normalize;
+mod paginate;
+mod providers;
+mod ratelimit;
+mod redact;
+mod report;
+mod sarif;
+mod select;
+mod stix;
+mod store;
+mod summarize;
+mod target;
+mod taxii;
+mod triage;
+#[cfg(test)]
+mod tests;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, ClientBuilder, Request, Response, StatusCode};
+use serde_json::{json, Map, Value};
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser, ValueParser};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use dotenv::dotenv;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
 use std::env;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
 use thiserror::Error;
-use anyhow::{Result, Context};
-use tokio::time::{sleep, Duration};
-use std::fs::File;
-use std::io::Write;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
 
 #[derive(Error, Debug)]
 enum OsintError {
     #[error("HTTP request failed: {0}")]
     HttpRequest(#[from] reqwest::Error),
-    #[error("Invalid OSINT type")] 
+    #[error("API returned {status} for {url}")]
+    ApiStatus { status: StatusCode, url: String },
+    #[error("Max retries exceeded for {0}")]
+    RetriesExhausted(String),
+    #[error("Quota exhausted: {0}")]
+    QuotaExhausted(String),
+    #[error("Failed to parse JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Failed to write report: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Provider error: {0}")]
+    ProviderError(String),
+    #[error("Invalid OSINT type")]
     InvalidType,
+    #[error("Invalid target: {0}")]
+    InvalidTarget(String),
+    #[error("{target} is {got}, but this type expects {expected}{}", target::suggestion(.got))]
+    TargetMismatch { target: String, expected: target::Expected, got: target::TargetKind },
     #[error("Missing API Key: {0}")]
     MissingApiKey(String),
+    #[error("Credentials rejected: {0}")]
+    InvalidCredentials(String),
+    #[error("DNS lookup failed: {0}")]
+    Dns(#[from] hickory_resolver::error::ResolveError),
+    #[error("AI analysis failed: {0}")]
+    Analysis(String),
+    #[error("Connection failed: {0}")]
+    Connection(String),
+    #[error("GeoIP database error: {0}")]
+    GeoIp(String),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Report does not match its schema: {0}")]
+    InvalidReport(String),
+    #[error("Cannot verify the report: {0}")]
+    Integrity(String),
+    #[error("Report encryption error: {0}")]
+    Crypto(String),
+    #[error("Browser failed: {0}")]
+    Browser(String),
+    #[error("{host} keeps failing, skipping requests to it for {retry_after}s")]
+    CircuitOpen { host: String, retry_after: u64 },
+    #[error("Invalid config: {0}")]
+    Config(String),
+    #[error("Interrupted before the source finished")]
+    Interrupted,
+    #[error("No result within {0}s")]
+    TimedOut(u64),
+}
+
+impl OsintError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            OsintError::InvalidType | OsintError::InvalidTarget(_) | OsintError::TargetMismatch { .. } | OsintError::Config(_) => EXIT_INVALID_INPUT,
+            OsintError::MissingApiKey(_) | OsintError::InvalidCredentials(_) => EXIT_MISSING_KEY,
+            OsintError::RetriesExhausted(_) | OsintError::QuotaExhausted(_) => EXIT_RATE_LIMITED,
+            OsintError::Dns(_) | OsintError::Connection(_) | OsintError::CircuitOpen { .. } | OsintError::TimedOut(_) => EXIT_NETWORK,
+            OsintError::HttpRequest(err) if err.is_connect() || err.is_timeout() => EXIT_NETWORK,
+            OsintError::Io(_) | OsintError::Database(_) | OsintError::GeoIp(_) | OsintError::InvalidReport(_) | OsintError::Integrity(_) | OsintError::Crypto(_) | OsintError::Browser(_) => EXIT_LOCAL,
+            OsintError::Interrupted => EXIT_INTERRUPTED,
+            _ => EXIT_SOURCE_FAILED,
+        }
+    }
+
+    /// Stable name of the variant, the `error_kind` of an error report.
+    fn kind(&self) -> &'static str {
+        match self {
+            OsintError::HttpRequest(_) => "http_request",
+            OsintError::ApiStatus { .. } => "api_status",
+            OsintError::RetriesExhausted(_) => "retries_exhausted",
+            OsintError::QuotaExhausted(_) => "quota_exhausted",
+            OsintError::Parse(_) => "parse",
+            OsintError::Io(_) => "io",
+            OsintError::ProviderError(_) => "provider_error",
+            OsintError::InvalidType => "invalid_type",
+            OsintError::InvalidTarget(_) => "invalid_target",
+            OsintError::TargetMismatch { .. } => "target_mismatch",
+            OsintError::MissingApiKey(_) => "missing_api_key",
+            OsintError::InvalidCredentials(_) => "invalid_credentials",
+            OsintError::Dns(_) => "dns",
+            OsintError::Analysis(_) => "analysis",
+            OsintError::Connection(_) => "connection",
+            OsintError::GeoIp(_) => "geoip",
+            OsintError::Database(_) => "database",
+            OsintError::InvalidReport(_) => "invalid_report",
+            OsintError::Integrity(_) => "integrity",
+            OsintError::Crypto(_) => "crypto",
+            OsintError::Browser(_) => "browser",
+            OsintError::CircuitOpen { .. } => "circuit_open",
+            OsintError::Config(_) => "config",
+            OsintError::Interrupted => "interrupted",
+            OsintError::TimedOut(_) => "timed_out",
+        }
+    }
 }
 
+const EXIT_SUCCESS: u8 = 0;
+const EXIT_FINDINGS: u8 = 1;
+const EXIT_SOURCE_FAILED: u8 = 2;
+const EXIT_INVALID_INPUT: u8 = 3;
+const EXIT_MISSING_KEY: u8 = 4;
+const EXIT_RATE_LIMITED: u8 = 5;
+const EXIT_NETWORK: u8 = 6;
+const EXIT_LOCAL: u8 = 7;
+const EXIT_INTERRUPTED: u8 = 130;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  scan succeeded with no notable findings
+  1  scan succeeded with findings (only with --fail-on-findings)
+  2  one or more sources failed
+  3  invalid arguments, target or config file
+  4  missing or rejected API key
+  5  rate limited or out of quota after retries
+  6  network failure: DNS, connection refused or timed out
+  7  local failure: file, database or GeoIP database
+  130  interrupted by Ctrl-C (completed sources are still saved)";
+
+/// How long in-flight sources get to finish after Ctrl-C before they are dropped.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// Every recon type; `all` runs those that accept the target, in this order.
+const SOURCES: &[&str] = &["whois", "dns", "axfr", "shodan", "shodan-search", "shodan-dns", "censys", "zoomeye", "binaryedge", "onyphe", "reversedns", "dnsbl", "asn", "geoip", "ipinfo", "anonymity", "rdap", "abuseipdb", "greynoise", "emailverify", "hibp", "leakcheck", "intelx", "emailrep", "gravatar", "phone", "hunter", "company", "crtsh", "subbrute", "wayback", "robots", "dorks", "dork", "securitytrails", "emailauth", "http", "tlscert", "favicon", "screenshot", "urlscan", "phish", "virustotal", "threatfox", "otx", "github", "username"];
+
+/// Defaults of `--retries` and `--retry-delay`.
 const RETRY_ATTEMPTS: u8 = 3;
 const RETRY_DELAY: Duration = Duration::from_secs(5);
+const SHODAN_BASE_URL: &str = "https://api.shodan.io";
+const HIBP_BASE_URL: &str = "https://haveibeenpwned.com/api/v3";
+const OPENAI_MODEL: &str = "gpt-4o-mini";
+/// DNS lookups `--resolve-via dns` keeps in flight.
+const RESOLVE_CONCURRENCY: usize = 20;
+/// HIBP's lowest paid tier allows ten lookups a minute.
+const HIBP_REQUESTS_PER_MINUTE: u32 = 10;
+
+/// How often a request is attempted when the API answers 429 or 503, and how long
+/// to wait in between.
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    attempts: u8,
+    delay: Duration,
+}
+
+impl RetryPolicy {
+    const DEFAULT: RetryPolicy = RetryPolicy { attempts: RETRY_ATTEMPTS, delay: RETRY_DELAY };
+    /// For quota-based APIs, where a 429 will not clear by waiting.
+    const SINGLE_ATTEMPT: RetryPolicy = RetryPolicy { attempts: 1, delay: Duration::ZERO };
+
+    /// The policy set by `--retries` and `--retry-delay`, or the defaults.
+    fn standard() -> RetryPolicy {
+        RETRY_POLICY.get().copied().unwrap_or(RetryPolicy::DEFAULT)
+    }
+}
+
+static RETRY_POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Replaces the standard retry policy for every request sent afterwards.
+/// Called once from `--retries` and `--retry-delay`.
+fn use_retry_policy(policy: RetryPolicy) {
+    let _ = RETRY_POLICY.set(policy);
+}
+
+/// Sends `request`, retrying on 429 and 503 under the standard retry policy. Any other
+/// status is returned to the caller to interpret.
+async fn send_with_retries(client: &Client, request: Request) -> Result<Response, OsintError> {
+    send_with_policy(client, request, RetryPolicy::standard()).await
+}
 
-async fn fetch_with_retries(url: &str, user_agent: Option<&str>) -> Result<String, OsintError> {
-    let client = Client::new();
-    for attempt in 0..RETRY_ATTEMPTS {
-        let mut request = client.get(url);
-        if let Some(ua) = user_agent {
-            request = request.header("User-Agent", ua);
-        }
-        
-        match request.send().await {
-            Ok(response) if response.status().is_success() => return Ok(response.text().await?),
-            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
-                eprintln!("Rate limited! Retrying in {} seconds...", RETRY_DELAY.as_secs());
-                sleep(RETRY_DELAY).await;
+/// Like `send_with_retries` with an explicit policy. Once attempts run out
+/// the final 429 or 503 is handed back, so callers can read the API's explanation.
+async fn send_with_policy(client: &Client, request: Request, policy: RetryPolicy) -> Result<Response, OsintError> {
+    send_guarded(client, request, policy, CIRCUIT_BREAKER.get()).await
+}
+
+static CIRCUIT_BREAKER: OnceLock<breaker::CircuitBreaker> = OnceLock::new();
+
+/// Guards every request sent afterwards with a per-host circuit breaker.
+/// Called once from `--circuit-threshold` and `--circuit-cooldown`.
+fn use_circuit_breaker(threshold: u32, cooldown: Duration) {
+    let _ = CIRCUIT_BREAKER.set(breaker::CircuitBreaker::new(threshold, cooldown));
+}
+
+/// `send_with_policy` under `breaker`: each attempt is refused while the
+/// host's circuit is open, and connection errors, 429s and 5xx answers count
+/// as its failures.
+async fn send_guarded(client: &Client, request: Request, policy: RetryPolicy, breaker: Option<&breaker::CircuitBreaker>) -> Result<Response, OsintError> {
+    let safe_url = logging::redact_url(request.url().as_str());
+    let host = format!("{}:{}", request.url().host_str().unwrap_or_default(), request.url().port_or_known_default().unwrap_or_default());
+    let mut attempt = 1;
+    loop {
+        let attempt_request = request.try_clone().expect("requests built by this crate never stream their body");
+        if let Some(Err(wait)) = breaker.map(|breaker| breaker.allow(&host)) {
+            debug!(url = %safe_url, "circuit open, not sending");
+            return Err(OsintError::CircuitOpen { host, retry_after: wait.as_secs_f64().ceil() as u64 });
+        }
+        // The global budget comes first, so a host's own pacing cannot hold
+        // up requests to other hosts.
+        if let Some(limiter) = GLOBAL_RATE_LIMITER.get() {
+            limiter.acquire().await;
+        }
+        if let Some(limiter) = RATE_LIMITER.get() {
+            limiter.acquire(&host).await;
+        }
+        REQUEST_METRICS.record_attempt(attempt > 1);
+
+        info!(url = %safe_url, attempt, "requesting");
+        let started = Instant::now();
+        // reqwest embeds the full URL in its errors, which may carry an API key.
+        let response = match client.execute(attempt_request).await {
+            Ok(response) => response,
+            Err(err) => {
+                if let Some(breaker) = breaker {
+                    breaker.record_failure(&host);
+                }
+                return Err(err.without_url().into());
+            }
+        };
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            REQUEST_METRICS.record_rate_limited();
+        }
+        debug!(url = %safe_url, status = status.as_u16(), elapsed_ms = started.elapsed().as_millis() as u64, "response received");
+        if let Some(breaker) = breaker {
+            match status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                true => breaker.record_failure(&host),
+                false => breaker.record_success(&host),
             }
-            Ok(response) => return Err(OsintError::HttpRequest(reqwest::Error::new(response.status(), "API Error"))),
-            Err(err) => return Err(OsintError::HttpRequest(err)),
         }
+
+        if !matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) || attempt >= policy.attempts {
+            return Ok(response);
+        }
+        warn!(url = %safe_url, attempt, status = status.as_u16(), delay_secs = policy.delay.as_secs(), "rate limited or unavailable, retrying");
+        sleep(policy.delay).await;
+        attempt += 1;
     }
-    Err(OsintError::HttpRequest(reqwest::Error::new(StatusCode::BAD_REQUEST, "Max retries exceeded")))
 }
 
-async fn fetch_whois(domain: &str) -> Result<Value, OsintError> {
-    let url = format!("https://api.whois.vu/?q={}", domain);
-    let response = fetch_with_retries(&url, None).await?;
-    serde_json::from_str(&response).map_err(|_| OsintError::HttpRequest(reqwest::Error::new(StatusCode::BAD_REQUEST, "Failed to parse JSON")))
+/// Sent with every request unless `--user-agent` replaces it; some APIs
+/// reject reqwest's empty default.
+const DEFAULT_USER_AGENT: &str = "Rust-OSINT-Tool/1.0";
+
+static USER_AGENT: OnceLock<String> = OnceLock::new();
+
+/// Sets the User-Agent of every client built afterwards. Called once from
+/// `--user-agent`.
+fn use_user_agent(user_agent: String) {
+    let _ = USER_AGENT.set(user_agent);
 }
 
-async fn fetch_shodan(ip: &str) -> Result<Value, OsintError> {
-    let shodan_key = env::var("SHODAN_API_KEY").map_err(|_| OsintError::MissingApiKey("SHODAN_API_KEY".to_string()))?;
-    let url = format!("https://api.shodan.io/shodan/host/{}?key={}", ip, shodan_key);
-    let response = fetch_with_retries(&url, None).await?;
-    serde_json::from_str(&response).map_err(|_| OsintError::HttpRequest(reqwest::Error::new(StatusCode::BAD_REQUEST, "Failed to parse JSON")))
+static PROXY: OnceLock<String> = OnceLock::new();
+
+/// Routes every client built afterwards, and the screenshot browser, through
+/// `proxy`. Called once from `--proxy` or the config.
+fn use_proxy(proxy: &str) -> Result<(), OsintError> {
+    reqwest::Proxy::all(proxy).map_err(|err| OsintError::InvalidTarget(format!("proxy {}: {}", logging::redact_url(proxy), err)))?;
+    let _ = PROXY.set(proxy.to_string());
+    Ok(())
+}
+
+fn proxy() -> Option<&'static str> {
+    PROXY.get().map(String::as_str)
+}
+
+static RATE_LIMITER: OnceLock<ratelimit::HostRateLimiter> = OnceLock::new();
+
+/// Paces every request sent afterwards to `per_minute` per host. Called once
+/// from `--rate-limit` or the config.
+fn use_rate_limit(per_minute: u32) {
+    let _ = RATE_LIMITER.set(ratelimit::HostRateLimiter::per_minute(per_minute));
+}
+
+static GLOBAL_RATE_LIMITER: OnceLock<ratelimit::RateLimiter> = OnceLock::new();
+
+/// Paces every request sent afterwards, whatever its host, to `per_second`
+/// on top of `--rate-limit`. Called once from `--global-rps`.
+fn use_global_rate_limit(per_second: u32) {
+    let _ = GLOBAL_RATE_LIMITER.set(ratelimit::RateLimiter::per_second(per_second));
+}
+
+static REQUEST_METRICS: ratelimit::RequestMetrics = ratelimit::RequestMetrics::new();
+
+/// The key in environment variable `name`. A blank value, as `.env` ships
+/// them, is as missing as an unset one, so the source is skipped rather than
+/// sent a bogus key.
+fn api_key(name: &str) -> Result<String, OsintError> {
+    env::var(name).ok().filter(|key| !key.is_empty()).ok_or_else(|| OsintError::MissingApiKey(name.to_string()))
+}
+
+/// The starting point for every HTTP client, carrying the User-Agent and proxy.
+fn client_builder() -> ClientBuilder {
+    let builder = Client::builder().user_agent(USER_AGENT.get().map_or(DEFAULT_USER_AGENT, String::as_str));
+    match proxy() {
+        Some(proxy) => builder.proxy(reqwest::Proxy::all(proxy).expect("checked by use_proxy")),
+        None => builder,
+    }
+}
+
+/// GETs `url` with `headers` on top of the client defaults and returns the
+/// body of a successful response.
+async fn fetch_with_retries(url: &str, headers: HeaderMap) -> Result<String, OsintError> {
+    fetch_with_policy(url, headers, RetryPolicy::standard()).await
+}
+
+/// Like `fetch_with_retries` with an explicit retry policy.
+async fn fetch_with_policy(url: &str, headers: HeaderMap, policy: RetryPolicy) -> Result<String, OsintError> {
+    let client = client_builder().build()?;
+    let request = client.get(url).headers(headers).build().map_err(|err| err.without_url())?;
+    let response = send_with_policy(&client, request, policy).await?;
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(OsintError::RetriesExhausted(logging::redact_url(url)));
+    }
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: logging::redact_url(url) });
+    }
+    let body = response.text().await.map_err(|err| err.without_url())?;
+    debug!(url = %logging::redact_url(url), bytes = body.len(), "response body read");
+    Ok(body)
+}
+
+async fn fetch_shodan(target: &str) -> Result<Value, OsintError> {
+    let shodan_key = api_key("SHODAN_API_KEY")?;
+    let ip = match target.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => providers::dns::resolve_ip(&providers::dns::resolver(), target).await?,
+    };
+    let mut data = fetch_shodan_from(SHODAN_BASE_URL, &shodan_key, ip).await?;
+    data["cves"] = json!(providers::nvd::enrich(&providers::nvd::cve_ids(&data)).await);
+    Ok(data)
+}
+
+/// The Shodan host lookup against `base_url`, so tests can point it at a
+/// mock server.
+async fn fetch_shodan_from(base_url: &str, key: &str, ip: IpAddr) -> Result<Value, OsintError> {
+    let url = format!("{}/shodan/host/{}?key={}", base_url, ip, key);
+    let response = fetch_with_retries(&url, HeaderMap::new()).await?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+fn hibp_limiter() -> &'static ratelimit::RateLimiter {
+    static LIMITER: OnceLock<ratelimit::RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| ratelimit::RateLimiter::per_minute(HIBP_REQUESTS_PER_MINUTE))
 }
 
 async fn fetch_hibp(email: &str) -> Result<Value, OsintError> {
-    let url = format!("https://haveibeenpwned.com/api/v3/breachedaccount/{}", email);
-    let response = fetch_with_retries(&url, Some("Rust-OSINT-Tool/1.0")).await?;
-    serde_json::from_str(&response).map_err(|_| OsintError::HttpRequest(reqwest::Error::new(StatusCode::BAD_REQUEST, "Failed to parse JSON")))
+    // The breach and paste lookups go out together.
+    hibp_limiter().acquire_many(2).await;
+    let key = env::var("HIBP_API_KEY").ok().filter(|key| !key.is_empty());
+    static CATALOG: OnceLock<providers::hibp::BreachCatalog> = OnceLock::new();
+    let catalog = CATALOG.get_or_init(|| providers::hibp::BreachCatalog::new(HIBP_BASE_URL, providers::hibp::cache_dir()));
+    fetch_hibp_from(HIBP_BASE_URL, key.as_deref(), email, catalog).await
 }
 
-fn save_report(target: &str, data: &Value) -> Result<(), std::io::Error> {
-    let filename = format!("{}_osint_report.json", target);
-    let mut file = File::create(&filename)?;
-    file.write_all(data.to_string().as_bytes())?;
-    println!("Report saved to: {}", filename);
+/// The breaches and pastes HIBP lists for `email`, looked up concurrently
+/// against `base_url` without the rate limiter, with each breach's details
+/// filled in from `catalog`.
+async fn fetch_hibp_from(base_url: &str, key: Option<&str>, email: &str, catalog: &providers::hibp::BreachCatalog) -> Result<Value, OsintError> {
+    let mut headers = HeaderMap::new();
+    if let Some(key) = key {
+        headers.insert("hibp-api-key", key.parse().map_err(|_| OsintError::InvalidCredentials("HIBP_API_KEY".to_string()))?);
+    }
+    let (breaches_url, pastes_url) = (format!("{}/breachedaccount/{}", base_url, email), format!("{}/pasteaccount/{}", base_url, email));
+    let (breaches, pastes) = tokio::try_join!(hibp_list(&breaches_url, headers.clone()), hibp_list(&pastes_url, headers))?;
+    Ok(json!({ "breaches": catalog.expand(breaches).await, "pastes": pastes }))
+}
+
+async fn hibp_list(url: &str, headers: HeaderMap) -> Result<Value, OsintError> {
+    match fetch_with_retries(url, headers).await {
+        Ok(response) => Ok(serde_json::from_str(&response)?),
+        // HIBP answers 404 for accounts that appear in no breach or paste.
+        Err(OsintError::ApiStatus { status: StatusCode::NOT_FOUND, .. }) => Ok(Value::Array(Vec::new())),
+        Err(err) => Err(err),
+    }
+}
+
+/// `2 breach(es) (1 exposing credentials or payment data), 1 paste(s),
+/// latest paste 2024-01-02T00:00:00Z` for a HIBP result.
+fn hibp_summary(data: &Value) -> String {
+    let count = |field: &str| data[field].as_array().map_or(0, Vec::len);
+    let mut summary = format!("{} breach(es)", count("breaches"));
+    let serious = data["breaches"].as_array().into_iter().flatten().filter(|breach| providers::hibp::exposes_secrets(breach)).count();
+    if serious > 0 {
+        summary.push_str(&format!(" ({} exposing credentials or payment data)", serious));
+    }
+    summary.push_str(&format!(", {} paste(s)", count("pastes")));
+    if let Some(latest) = data["pastes"].as_array().into_iter().flatten().filter_map(|paste| paste["Date"].as_str()).max() {
+        summary.push_str(&format!(", latest paste {}", latest));
+    }
+    summary
+}
+
+/// Alternative names the type argument accepts for a source.
+const SOURCE_ALIASES: &[(&str, &str)] = &[("rdns", "reversedns"), ("webprobe", "http")];
+
+/// Accepted values of the type argument; aliases resolve to the source name.
+fn source_values() -> ValueParser {
+    let values = SOURCES.iter().map(|&source| {
+        let aliases = SOURCE_ALIASES.iter().filter(|(_, name)| *name == source).map(|(alias, _)| *alias);
+        PossibleValue::new(source).aliases(aliases)
+    });
+    let parser = PossibleValuesParser::new(values.chain([PossibleValue::new("all")]));
+    ValueParser::new(parser.map(|value| SOURCE_ALIASES.iter().find(|(alias, _)| *alias == value).map_or(value, |(_, name)| name.to_string())))
+}
+
+/// Shape of the report file written after each scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Json,
+    Stix,
+    Sarif,
+}
+
+impl ReportFormat {
+    const NAMES: &'static [&'static str] = &["json", "stix", "sarif"];
+
+    fn from_name(name: &str) -> ReportFormat {
+        match name {
+            "stix" => ReportFormat::Stix,
+            "sarif" => ReportFormat::Sarif,
+            _ => ReportFormat::Json,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Stix => "stix.json",
+            ReportFormat::Sarif => "sarif",
+        }
+    }
+}
+
+/// Characters that cannot appear in file names on common filesystems.
+const UNSAFE_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// `<target>_osint_report.<extension>` in the output directory, with
+/// characters that would break the path (as in CIDR ranges or URLs) replaced by `_`.
+fn report_filename(target: &str, extension: &str, compress: bool) -> PathBuf {
+    let safe_target: String = target.chars().map(|c| if UNSAFE_FILENAME_CHARS.contains(&c) || c.is_control() { '_' } else { c }).collect();
+    let suffix = if compress { ".gz" } else { "" };
+    output_path(&format!("{}_osint_report.{}{}", safe_target, extension, suffix))
+}
+
+static OUTPUT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Writes report files and screenshots into `dir` from now on, creating it
+/// if needed. Called once from `--output-dir` or the config.
+fn use_output_dir(dir: &Path) -> Result<(), OsintError> {
+    fs::create_dir_all(dir)?;
+    let _ = OUTPUT_DIR.set(dir.to_path_buf());
     Ok(())
 }
 
-async fn analyze_with_chatgpt(api_key: &str, data: &Value) -> Result<String, OsintError> {
-    let client = OpenAIClient::new(api_key);
-    let messages = vec![
-        ChatMessage::system("You are a cybersecurity expert."),
-        ChatMessage::user(&format!("Analyze this OSINT data: {}", data)),
-    ];
-    let response = client.chat(messages).await.unwrap();
-    Ok(response)
+/// Where a file named `name` that the scan produces is written.
+fn output_path(name: &str) -> PathBuf {
+    OUTPUT_DIR.get().map_or_else(|| PathBuf::from(name), |dir| dir.join(name))
 }
 
-#[tokio::main]
-async fn main() -> Result<(), OsintError> {
-    dotenv().ok(); // Load environment variables from .env file
-    let matches = Command::new("OSINT Recon Tool")
+/// Writes the report to `path`, or to stdout when `path` is `-`, gzipping it
+/// when `compress` is set. With a `passphrase` the file is encrypted and
+/// written to `{path}.enc` instead, the plaintext never touching the disk.
+/// Returns where it went.
+fn save_report(path: &Path, data: &Value, compress: bool, passphrase: Option<&str>, console: Console) -> Result<PathBuf, OsintError> {
+    let body = data.to_string();
+    let raw_len = body.len();
+    let to_stdout = path == Path::new("-");
+    let bytes = if compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes())?;
+        encoder.finish()?
+    } else if to_stdout {
+        (body + "\n").into_bytes()
+    } else {
+        body.into_bytes()
+    };
+
+    if compress && console != Console::Quiet {
+        eprintln!("Compressed {}: {} -> {} bytes ({:.1}x)", path.display(), raw_len, bytes.len(), raw_len as f64 / bytes.len().max(1) as f64);
+    }
+    let (path, bytes) = match passphrase {
+        Some(passphrase) if !to_stdout => (crypto::encrypted_path(path), crypto::encrypt(&bytes, passphrase)?),
+        _ => (path.to_path_buf(), bytes),
+    };
+    if to_stdout {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&bytes)?;
+        stdout.flush()?;
+    } else {
+        File::create(&path)?.write_all(&bytes)?;
+    }
+    info!(path = %path.display(), encrypted = passphrase.is_some() && !to_stdout, "report saved");
+    Ok(path)
+}
+
+/// Where human-readable output goes: stdout, stderr when the report itself
+/// is written to stdout, or nowhere under `--quiet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Console {
+    Stdout,
+    Stderr,
+    Quiet,
+}
+
+impl Console {
+    fn say(self, text: &str) {
+        match self {
+            Console::Stdout => println!("{}", text),
+            Console::Stderr => eprintln!("{}", text),
+            Console::Quiet => {}
+        }
+    }
+
+    /// Output asked for with a flag, such as `--misp-dry-run`, which
+    /// `--quiet` only moves to stderr.
+    fn show(self, text: &str) {
+        match self {
+            Console::Stdout => println!("{}", text),
+            Console::Stderr | Console::Quiet => eprintln!("{}", text),
+        }
+    }
+}
+
+/// Reads a saved report, decrypting it when the name ends in `.enc` and
+/// decompressing it when the name then ends in `.gz`.
+fn load_report(path: &Path) -> Result<Value, OsintError> {
+    let (path, bytes) = match path.extension().is_some_and(|extension| extension == "enc") {
+        true => (path.with_extension(""), crypto::decrypt(&fs::read(path)?, &crypto::passphrase(false)?)?),
+        false => (path.to_path_buf(), fs::read(path)?),
+    };
+    let body = if path.extension().is_some_and(|extension| extension == "gz") {
+        let mut body = String::new();
+        GzDecoder::new(bytes.as_slice()).read_to_string(&mut body)?;
+        body
+    } else {
+        String::from_utf8(bytes).map_err(|err| OsintError::InvalidReport(format!("{}: not UTF-8 ({})", path.display(), err)))?
+    };
+    Ok(serde_json::from_str(&body)?)
+}
+
+const ANALYSIS_SYSTEM_PROMPT: &str = "You are a cybersecurity expert.";
+const ANALYSIS_PROMPT_PREFIX: &str = "Analyze this OSINT data: ";
+
+/// What to do when the prompt's estimated cost exceeds `--max-cost`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverBudget {
+    Truncate,
+    Refuse,
+}
+
+/// Sends the report for analysis. Reports too large for the model's context
+/// are condensed first, and the call's cost is estimated, truncating or
+/// refusing when it would exceed `max_cost` USD.
+async fn analyze_with_ai(endpoint: &ai::Endpoint, model: &str, data: &Value, max_cost: Option<f64>, over_budget: OverBudget) -> Result<(String, cost::Usage), OsintError> {
+    let estimator = cost::Estimator::for_model(model)?;
+    let fixed_tokens = estimator.prompt_tokens(&[ANALYSIS_SYSTEM_PROMPT, ANALYSIS_PROMPT_PREFIX]);
+    let context_budget = cost::context_window(model) - cost::MAX_COMPLETION_TOKENS - fixed_tokens;
+    let (mut payload, condensed_notes) = summarize::DataSummarizer::default().fit(data, |payload| estimator.prompt_tokens(&[payload]) <= context_budget);
+    if !condensed_notes.is_empty() {
+        warn!(notes = condensed_notes.len(), "report exceeds the model's context window, condensing it for analysis");
+        payload = format!("(Condensed to fit the context window: {}.) {}", condensed_notes.join("; "), payload);
+    }
+    let prompt_tokens = fixed_tokens + estimator.prompt_tokens(&[&payload]);
+    let estimate = estimator.worst_case_cost(prompt_tokens);
+    info!(prompt_tokens, estimated_usd = estimate, "analysis cost estimated");
+
+    if let Some(max_cost) = max_cost.filter(|&max_cost| estimate > max_cost) {
+        let refusal = format!("{} prompt tokens would cost up to ${:.4}, over --max-cost ${:.4}", prompt_tokens, estimate, max_cost);
+        match (over_budget, estimator.truncate_to_budget(&payload, fixed_tokens, max_cost)) {
+            (OverBudget::Truncate, Some(truncated)) => {
+                warn!(from_bytes = payload.len(), to_bytes = truncated.len(), "{}; truncating the data sent for analysis", refusal);
+                payload = truncated;
+            }
+            (OverBudget::Truncate, None) => return Err(OsintError::Analysis(format!("{}, and even an empty report does not fit", refusal))),
+            (OverBudget::Refuse, _) => return Err(OsintError::Analysis(format!("{}; raise it or pass --max-cost-action truncate", refusal))),
+        }
+    }
+
+    let (mut analysis, usage) = complete(endpoint, model, &format!("{}{}", ANALYSIS_PROMPT_PREFIX, payload)).await?;
+    if !condensed_notes.is_empty() {
+        analysis = format!("Note: the report was too large for the model and was condensed before analysis ({}).\n\n{}", condensed_notes.join("; "), analysis);
+    }
+    Ok((analysis, usage))
+}
+
+/// One chat completion of `prompt` under the analysis system prompt.
+async fn complete(endpoint: &ai::Endpoint, model: &str, prompt: &str) -> Result<(String, cost::Usage), OsintError> {
+    let body = json!({
+        "model": model,
+        "max_tokens": cost::MAX_COMPLETION_TOKENS,
+        "messages": [
+            { "role": "system", "content": ANALYSIS_SYSTEM_PROMPT },
+            { "role": "user", "content": prompt },
+        ],
+    });
+    let started = Instant::now();
+    info!(url = %endpoint.url(), flavor = ?endpoint.flavor(), model, "requesting analysis");
+    let response = endpoint.post(&client_builder().build()?).json(&body).send().await?;
+    let status = response.status();
+    debug!(status = status.as_u16(), elapsed_ms = started.elapsed().as_millis() as u64, "analysis response received");
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: endpoint.url().to_string() });
+    }
+    let reply: Value = response.json().await?;
+    let text = reply["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| OsintError::Analysis("response contained no message content".to_string()))?;
+    Ok((text, cost::Usage::from_response(&reply)))
+}
+
+fn cli() -> Command {
+    Command::new("OSINT Recon Tool")
         .version("1.0")
         .author("Vector")
         .about("Performs OSINT reconnaissance using Rust and AI analysis")
-        .arg(Arg::new("target").help("Target domain/IP/email").required(true))
-        .arg(Arg::new("type").help("Type: whois/shodan/hibp").required(true))
-        .get_matches();
-    
+        .after_help(EXIT_CODES_HELP)
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("query")
+                .about("List past scans of a target stored with --db, or print one")
+                .arg(Arg::new("target").help("Target whose scans to list"))
+                .arg(Arg::new("id").long("id").value_parser(clap::value_parser!(i64)).help("Print the report of the scan with this id"))
+                .arg(Arg::new("file").long("file").value_parser(clap::value_parser!(PathBuf)).help("Print a saved report file instead, decompressing .gz files"))
+                .group(ArgGroup::new("selection").args(["target", "id", "file"]).required(true)),
+        )
+        .subcommand(Command::new("schema").about("Print the JSON Schema of the report file"))
+        .subcommand(Command::new("profiles").about("List the profiles of the config file and what each overrides"))
+        .subcommand(
+            Command::new("correlate")
+                .about("Ask the AI for the patterns across several saved reports in one analysis")
+                .arg(Arg::new("reports").help("Report files of related targets (.json or .json.gz)").required(true).num_args(2..).value_parser(clap::value_parser!(PathBuf)))
+                .arg(Arg::new("output").long("output").value_parser(clap::value_parser!(PathBuf)).help("Where to write the Markdown analysis [default: correlation_analysis.md]")),
+        )
+        .subcommand(
+            Command::new("decrypt")
+                .about("Print a report saved with --encrypt, or write it decrypted with --output")
+                .arg(Arg::new("report").help("Encrypted report (<report>.enc)").required(true).value_parser(clap::value_parser!(PathBuf)))
+                .arg(Arg::new("output").long("output").value_parser(clap::value_parser!(PathBuf)).help("Write the decrypted file here instead of printing the report")),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Check a report saved with --sign against its .sha256 checksum and, with --public-key, its .sig signature")
+                .arg(Arg::new("report").help("Report file to check").required(true).value_parser(clap::value_parser!(PathBuf)))
+                .arg(Arg::new("public-key").long("public-key").value_parser(clap::value_parser!(PathBuf)).help("Ed25519 public key (SPKI PEM) to check the signature with")),
+        )
+        .arg(Arg::new("target").help("Target domain/IP/email/URL/file hash/AS number/phone number, or - to read targets from stdin, one per line").required(true))
+        .arg(Arg::new("concurrency").long("concurrency").value_parser(clap::value_parser!(usize)).default_value("4").help("Targets from stdin scanned at once"))
+        .arg(Arg::new("type").help("Type of reconnaissance to run; `all` runs every source that accepts the target").required(true).value_parser(source_values()))
+        .arg(Arg::new("whois-provider").long("whois-provider").visible_alias("whois-source").value_parser(providers::whois::SOURCES.to_vec()).default_value("auto").help("WHOIS service for the whois type; auto falls back from whois.vu to WhoisXML (with WHOISXML_API_KEY) to RDAP to the registry's WHOIS server on port 43"))
+        .arg(Arg::new("dkim-selector").long("dkim-selector").visible_alias("dkim-selectors").action(ArgAction::Append).value_delimiter(',').help("DKIM selectors to check with emailauth, besides common defaults (repeatable or comma-separated)"))
+        .arg(Arg::new("max-redirects").long("max-redirects").value_parser(clap::value_parser!(usize)).default_value("10").help("Maximum redirects the http type follows"))
+        .arg(Arg::new("sites").long("sites").value_parser(clap::value_parser!(PathBuf)).help("Site list for the username type: a .toml file of [[site]] tables adds to the built-in list, a .json list replaces it"))
+        .arg(Arg::new("username").long("username").help("Handle the username type checks instead of the target or the names derived from an email target"))
+        .arg(Arg::new("dork-templates").long("dork-templates").value_name("PATH").value_parser(clap::value_parser!(PathBuf)).help("More templates for the dorks type: [category] lines, then one dork per line with {domain}"))
+        .arg(Arg::new("dorks").long("dorks").value_name("FILE").value_parser(clap::value_parser!(PathBuf)).help("Dork templates for the dork type instead of its own and the config's: one per line with {{target}}"))
+        .arg(Arg::new("dork-links").long("dork-links").action(ArgAction::SetTrue).help("Add Google, Bing and DuckDuckGo search links to each dork of the dorks type"))
+        .arg(Arg::new("wordlist").long("wordlist").value_parser(clap::value_parser!(PathBuf)).help("Words to try as subdomains with the subbrute type, one per line (replaces the built-in list)"))
+        .arg(Arg::new("dns-concurrency").long("dns-concurrency").value_parser(clap::value_parser!(usize)).default_value("20").help("DNS lookups the subbrute type and crtsh --resolve keep in flight"))
+        .arg(Arg::new("urlscan-visibility").long("urlscan-visibility").value_parser(providers::urlscan::VISIBILITIES.to_vec()).default_value("unlisted").help("Visibility of urlscan submissions"))
+        .arg(Arg::new("download-screenshot").long("download-screenshot").action(ArgAction::SetTrue).help("Save the urlscan screenshot next to the report"))
+        .arg(Arg::new("smtp-probe").long("smtp-probe").action(ArgAction::SetTrue).help("Let the emailverify type ask the mail server about the address with RCPT TO (no mail is sent)"))
+        .arg(Arg::new("max-pages").long("max-pages").value_parser(clap::value_parser!(u32).range(1..)).default_value("1").help("Result pages the shodan-search (100 a page), zoomeye (20 a page) and binaryedge subdomain types read; each costs query credits"))
+        .arg(Arg::new("max-results").long("max-results").value_parser(clap::value_parser!(usize)).help("Most results the paginated shodan-search, zoomeye, binaryedge and hunter types return, stopping paging once they have them, that onyphe keeps (default 1000), that intelx keeps per bucket group (default 100) and that dork keeps per dork (default 10)"))
+        .arg(Arg::new("resolve").long("resolve").action(ArgAction::SetTrue).help("Resolve the crtsh subdomains, --dns-concurrency at a time, keeping those that resolve other than through wildcard DNS"))
+        .arg(Arg::new("resolve-via").long("resolve-via").value_parser(["dns", "shodan"]).help("In `all` mode, map the discovered subdomains to IPs with DNS or one batched Shodan lookup"))
+        .arg(Arg::new("pivot-shodan").long("pivot-shodan").action(ArgAction::SetTrue).help("Search Shodan for the hosts sharing the favicon type's hashes (needs SHODAN_API_KEY)"))
+        .arg(Arg::new("screenshot").long("screenshot").action(ArgAction::SetTrue).help("Also capture the landing page with headless Chrome as <target>_screenshot.png (http, urlscan, all)"))
+        .arg(Arg::new("screenshot-insecure").long("screenshot-insecure").action(ArgAction::SetTrue).help("Capture pages whose TLS certificate Chrome rejects"))
+        .arg(Arg::new("include-secrets").long("include-secrets").action(ArgAction::SetTrue).help("Keep the leaked passwords and hashes the leakcheck type finds in the report, in plain text"))
+        .arg(Arg::new("pivot-hibp").long("pivot-hibp").action(ArgAction::SetTrue).help("Check every email the hunter type discovers against HIBP"))
+        .arg(Arg::new("pivot-username").long("pivot-username").action(ArgAction::SetTrue).help("Check every social handle the company type finds with the username type's sites"))
+        .arg(Arg::new("wayback-limit").long("wayback-limit").value_parser(clap::value_parser!(usize)).default_value("10000").help("Maximum archived URLs the wayback type fetches"))
+        .arg(Arg::new("sitemap-limit").long("sitemap-limit").value_parser(clap::value_parser!(usize)).default_value("5000").help("Maximum sitemap URLs the robots type collects"))
+        .arg(Arg::new("github-max-results").long("github-max-results").value_parser(clap::value_parser!(usize)).default_value("100").help("Maximum code search hits the github type fetches"))
+        .arg(Arg::new("github-secrets").long("github-secrets").action(ArgAction::SetTrue).help("Also search GitHub for files naming the target next to secret-looking terms (one more search each)"))
+        .arg(Arg::new("port").long("port").value_parser(clap::value_parser!(u16)).default_value("443").help("Port the tlscert type connects to"))
+        .arg(Arg::new("asn-prefixes").long("asn-prefixes").action(ArgAction::SetTrue).help("Also list the other prefixes announced by the asn type's ASNs"))
+        .arg(Arg::new("circuit-threshold").long("circuit-threshold").value_parser(clap::value_parser!(u32)).default_value("5").help("Consecutive failures after which requests to a host fail fast (0 disables)"))
+        .arg(Arg::new("circuit-cooldown").long("circuit-cooldown").value_name("SECS").value_parser(clap::value_parser!(u64)).default_value("60").help("Seconds a failing host is skipped before a trial request is let through"))
+        .arg(Arg::new("retries").long("retries").value_parser(clap::value_parser!(u8).range(1..)).default_value("3").help("Attempts per request when an API answers 429 or 503, at least 1"))
+        .arg(Arg::new("retry-delay").long("retry-delay").visible_alias("retry-base-delay").value_name("SECS").value_parser(clap::value_parser!(u64)).default_value("5").help("Seconds waited between those attempts"))
+        .arg(Arg::new("rate-limit").long("rate-limit").value_name("PER_MINUTE").value_parser(clap::value_parser!(u32).range(1..)).help("Most requests per minute sent to any one host"))
+        .arg(Arg::new("global-rps").long("global-rps").value_name("PER_SECOND").value_parser(clap::value_parser!(u32).range(1..)).help("Most requests per second sent in total, across every host and retry; the request totals are printed at the end"))
+        .arg(Arg::new("proxy").long("proxy").value_name("URL").help("HTTP(S) proxy for every request, e.g. http://127.0.0.1:8080"))
+        .arg(Arg::new("user-agent").long("user-agent").help("User-Agent sent with every request (default: Rust-OSINT-Tool/1.0)"))
+        .arg(Arg::new("nameserver").long("nameserver").visible_alias("resolver").value_parser(clap::value_parser!(IpAddr)).help("DNS server for DNS-backed sources instead of the system resolver"))
+        .arg(Arg::new("source-timeout").long("source-timeout").value_name("SECS").value_parser(clap::value_parser!(u64)).default_value("120").help("Seconds each source gets in `all` mode before it is marked timeout"))
+        .arg(Arg::new("deadline").long("deadline").value_name("SECS").value_parser(clap::value_parser!(u64)).help("Seconds after which `all` abandons the sources still running and reports the rest"))
+        .arg(Arg::new("geoip-db").long("geoip-db").value_name("PATH").action(ArgAction::Append).value_parser(clap::value_parser!(PathBuf)).help("GeoLite2 City or ASN .mmdb file used to locate the IPs in the report (repeatable; default: GEOIP_DB)"))
+        .arg(Arg::new("enrich-geo").long("enrich-geo").action(ArgAction::SetTrue).help("Locate the IPs in the report even without a GeoLite2 database, using ipapi.co"))
+        .arg(Arg::new("hook").long("hook").value_name("COMMAND").help("Shell command run after each source with its JSON on stdin; JSON it prints is merged under hook_output"))
+        .arg(Arg::new("hook-timeout").long("hook-timeout").value_parser(clap::value_parser!(u64)).default_value("30").help("Seconds before a hook is killed"))
+        .arg(Arg::new("format").long("format").value_parser(ReportFormat::NAMES.to_vec()).default_value("json").help("Report file format: raw JSON, a STIX 2.1 bundle, or a SARIF 2.1.0 log of the findings"))
+        .arg(Arg::new("fields").long("fields").value_name("PATHS").action(ArgAction::Append).value_delimiter(',').value_parser(select::parse_path).help("Keep only these comma-separated dot paths of the JSON report, e.g. data.ports,risk_score or data.data[].port"))
+        .arg(Arg::new("redact").long("redact").action(ArgAction::SetTrue).help("Mask email local parts, IPs and sensitive fields in the saved report with salted hash tokens"))
+        .arg(Arg::new("redact-key").long("redact-key").value_name("FIELD").action(ArgAction::Append).requires("redact").help("Extra field name whose value --redact masks (repeatable)"))
+        .arg(Arg::new("redact-salt").long("redact-salt").requires("redact").help("Salt for --redact tokens; reuse it to correlate across reports (default: OSINT_REDACT_SALT, else random)"))
+        .arg(Arg::new("keep-raw").long("keep-raw").action(ArgAction::SetTrue).requires("redact").help("Also save the unredacted report as <target>_osint_report.raw.json"))
+        .arg(Arg::new("output").short('o').long("output").value_name("PATH").value_parser(clap::value_parser!(PathBuf)).help("Write the report here instead of <target>_osint_report.json; `-` writes it to stdout"))
+        .arg(Arg::new("output-dir").long("output-dir").value_name("DIR").value_parser(clap::value_parser!(PathBuf)).help("Directory for report files and screenshots, created if missing"))
+        .arg(Arg::new("quiet").short('q').long("quiet").action(ArgAction::SetTrue).help("Print nothing but the report JSON, on stdout unless --output names a file; logs stay on stderr"))
+        .arg(Arg::new("encrypt").long("encrypt").action(ArgAction::SetTrue).help("Encrypt saved reports with a passphrase (from OSINT_ENCRYPTION_KEY or asked for) into <report>.enc"))
+        .arg(Arg::new("sign").long("sign").action(ArgAction::SetTrue).help("Write a <report>.sha256 checksum next to each saved report"))
+        .arg(Arg::new("signing-key").long("signing-key").value_parser(clap::value_parser!(PathBuf)).help("Also sign saved reports with this Ed25519 private key (PKCS#8 PEM) into <report>.sig; implies --sign"))
+        .arg(Arg::new("compress").long("compress").action(ArgAction::SetTrue).help("Gzip the saved report files (<target>_osint_report.json.gz)"))
+        .arg(Arg::new("config").long("config").global(true).value_name("PATH").value_parser(clap::value_parser!(PathBuf)).help("Config file with [defaults] and [profiles.<name>] (default: OSINT_CONFIG, else ./osint.toml)"))
+        .arg(Arg::new("profile").long("profile").global(true).value_name("NAME").help("Config profile whose keys, sources, output directory, rate limit and proxy to use"))
+        .arg(Arg::new("db").long("db").global(true).value_name("PATH").value_parser(clap::value_parser!(PathBuf)).help("SQLite database recording each scan's report and risk score"))
+        .arg(Arg::new("skip-if-scanned-within").long("skip-if-scanned-within").value_name("DURATION").value_parser(parse_duration).requires("db").help("Reuse a --db scan of the same target and type newer than this (e.g. 30m, 12h, 7d) instead of scanning again"))
+        .arg(Arg::new("misp-url").long("misp-url").value_parser(clap::value_parser!(url::Url)).help("MISP instance to push the scan's indicators and findings to as an event"))
+        .arg(Arg::new("misp-key").long("misp-key").requires("misp-url").help("MISP automation key sent in the Authorization header"))
+        .arg(Arg::new("taxii-url").long("taxii-url").value_name("API_ROOT").value_parser(clap::value_parser!(url::Url)).requires("taxii-collection").help("TAXII 2.1 API root to push the scan's STIX bundle to, e.g. https://taxii.example.org/api1/"))
+        .arg(Arg::new("taxii-collection").long("taxii-collection").value_name("ID").requires("taxii-url").help("Collection the STIX objects are added to"))
+        .arg(Arg::new("taxii-auth").long("taxii-auth").requires("taxii-url").help("user:password for basic authentication, or a bearer token (default: TAXII_AUTH)"))
+        .arg(Arg::new("misp-dry-run").long("misp-dry-run").action(ArgAction::SetTrue).help("Print the MISP event JSON instead of sending it"))
+        .arg(Arg::new("analyze").long("analyze").action(ArgAction::SetTrue).overrides_with("no-ai").help("Send the report for AI analysis; needs the key named by --ai-api-key-env"))
+        .arg(Arg::new("no-ai").long("no-ai").action(ArgAction::SetTrue).overrides_with("analyze").help("Skip the AI analysis, also when an earlier --analyze (say, in an alias) asked for it"))
+        .arg(Arg::new("ai-base-url").long("ai-base-url").value_name("URL").default_value(ai::OPENAI_BASE_URL).help("OpenAI-compatible API for the analysis, e.g. https://api.groq.com/openai/v1 or an Azure deployment URL with ?api-version="))
+        .arg(Arg::new("ai-api-key-env").long("ai-api-key-env").value_name("VAR").default_value(ai::DEFAULT_API_KEY_ENV).help("Environment variable holding the analysis API key"))
+        .arg(Arg::new("ai-model").long("ai-model").default_value(OPENAI_MODEL).help("Model asked for the analysis; with Azure, the deployment's model (used for cost estimates)"))
+        .arg(Arg::new("max-cost").long("max-cost").value_name("USD").value_parser(clap::value_parser!(f64)).help("Ceiling on the estimated cost of the AI analysis call"))
+        .arg(Arg::new("max-cost-action").long("max-cost-action").value_parser(["truncate", "refuse"]).default_value("truncate").help("Truncate the data or skip analysis when over --max-cost"))
+        .arg(Arg::new("fail-on-findings").long("fail-on-findings").action(ArgAction::SetTrue).help("Exit with code 1 when the scan reports notable findings"))
+        .arg(Arg::new("verbose").short('v').long("verbose").action(ArgAction::Count).help("Increase log verbosity (-v info, -vv debug); RUST_LOG overrides"))
+        .arg(Arg::new("error-format").long("error-format").value_parser(["text", "json"]).default_value("text").help("On failure, also emit a JSON error report to the --output path (else stderr)"))
+        .arg(Arg::new("log-json").long("log-json").action(ArgAction::SetTrue).help("Emit logs as structured JSON"))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv().ok(); // Load environment variables from .env file
+    let matches = match cli().try_get_matches() {
+        Ok(matches) => matches,
+        Err(err) => {
+            let _ = err.print();
+            // --help and --version also arrive here, on stdout.
+            return if err.use_stderr() { ExitCode::from(EXIT_INVALID_INPUT) } else { ExitCode::SUCCESS };
+        }
+    };
+
+    logging::init(matches.get_count("verbose"), matches.get_flag("log-json"));
+
+    let cancel = CancellationToken::new();
+    let on_interrupt = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!(grace_secs = SHUTDOWN_GRACE.as_secs(), "interrupted, cancelling in-flight sources");
+            on_interrupt.cancel();
+        }
+    });
+
+    let outcome = match (configure(&matches), matches.subcommand()) {
+        (Err(err), _) => Err(err),
+        (Ok(_), Some(("query", query_matches))) => query(query_matches),
+        (Ok(_), Some(("schema", _))) => {
+            println!("{}", report::SCHEMA.trim_end());
+            Ok(EXIT_SUCCESS)
+        }
+        (Ok((config, _)), Some(("profiles", _))) => list_profiles(&config),
+        (Ok(_), Some(("correlate", correlate_matches))) => correlate_reports(&matches, correlate_matches).await,
+        (Ok(_), Some(("verify", verify_matches))) => verify_report(verify_matches),
+        (Ok(_), Some(("decrypt", decrypt_matches))) => decrypt_report(decrypt_matches),
+        (Ok((_, settings)), _) => run(&matches, &settings, &cancel).await,
+    };
+    let totals = REQUEST_METRICS.totals();
+    if totals.requests > 0 {
+        info!(requests = totals.requests, retries = totals.retries, rate_limited = totals.rate_limited, "request totals");
+        if matches.contains_id("global-rps") && !matches.get_flag("quiet") {
+            eprintln!("Requests: {} sent, {} of them retries, {} answered 429", totals.requests, totals.retries, totals.rate_limited);
+        }
+    }
+    match outcome {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            error!(%err, "scan failed");
+            if matches.get_one::<String>("error-format").is_some_and(|format| format == "json") {
+                emit_error_report(&matches, matches.get_one::<String>("target").map(String::as_str), &err);
+            }
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+/// Loads the config file and sets the process-wide options. A value comes from
+/// its flag when given, else from the `--profile`, else from `[defaults]`.
+fn configure(matches: &ArgMatches) -> Result<(config::Config, config::Settings), OsintError> {
+    let config = config::Config::find(matches.get_one::<PathBuf>("config").map(PathBuf::as_path), SOURCES)?;
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+    let settings = config.settings(profile)?;
+    config.export_keys(profile)?;
+    if let Some(profile) = profile {
+        info!(profile, settings = %settings.describe(), "profile selected");
+    }
+
+    if let Some(user_agent) = matches.get_one::<String>("user-agent") {
+        use_user_agent(user_agent.clone());
+    }
+    if let (Some(threshold), Some(cooldown)) = (matches.get_one::<u32>("circuit-threshold"), matches.get_one::<u64>("circuit-cooldown")) {
+        use_circuit_breaker(*threshold, Duration::from_secs(*cooldown));
+    }
+    if let (Some(&attempts), Some(&delay)) = (matches.get_one::<u8>("retries"), matches.get_one::<u64>("retry-delay")) {
+        use_retry_policy(RetryPolicy { attempts, delay: Duration::from_secs(delay) });
+    }
+    if let Some(nameserver) = matches.get_one::<IpAddr>("nameserver") {
+        providers::dns::use_nameserver(*nameserver);
+    }
+    if let Some(proxy) = matches.get_one::<String>("proxy").or(settings.proxy.as_ref()) {
+        use_proxy(proxy)?;
+    }
+    if let Some(per_minute) = matches.get_one::<u32>("rate-limit").copied().or(settings.rate_limit) {
+        use_rate_limit(per_minute);
+    }
+    if let Some(&per_second) = matches.get_one::<u32>("global-rps") {
+        use_global_rate_limit(per_second);
+    }
+    if let Some(dir) = matches.get_one::<PathBuf>("output-dir").or(settings.output_dir.as_ref()) {
+        use_output_dir(dir)?;
+    }
+    providers::dnsbl::use_extra_zones(settings.dnsbl.clone());
+    providers::serpapi::use_extra_templates(settings.dorks.clone());
+    Ok((config, settings))
+}
+
+/// Handles the `profiles` subcommand.
+fn list_profiles(config: &config::Config) -> Result<u8, OsintError> {
+    let Some(path) = &config.path else {
+        println!("No config file (--config, OSINT_CONFIG or ./{})", config::DEFAULT_PATH);
+        return Ok(EXIT_SUCCESS);
+    };
+    println!("Profiles in {}:", path.display());
+    if config.profiles.is_empty() {
+        println!("  (none)");
+    }
+    for (name, profile) in &config.profiles {
+        println!("  {}: {}", name, profile.describe());
+    }
+    println!("Defaults: {}", config.defaults.describe());
+    Ok(EXIT_SUCCESS)
+}
+
+/// Handles the `correlate` subcommand; the AI options come from `matches`.
+async fn correlate_reports(matches: &ArgMatches, correlate_matches: &ArgMatches) -> Result<u8, OsintError> {
+    let reports = correlate_matches.get_many::<PathBuf>("reports").unwrap_or_default().map(|path| load_report(path)).collect::<Result<Vec<_>, _>>()?;
+    let key_env = matches.get_one::<String>("ai-api-key-env").unwrap();
+    let ai_key = api_key(key_env)?;
+    let ai_endpoint = ai::Endpoint::new(matches.get_one::<String>("ai-base-url").unwrap(), ai_key)?;
+    let ai_model = matches.get_one::<String>("ai-model").unwrap();
+    let correlation = correlate::correlate(&ai_endpoint, ai_model, &reports, matches.get_one::<f64>("max-cost").copied()).await?;
+    let path = correlate_matches.get_one::<PathBuf>("output").cloned().unwrap_or_else(|| output_path("correlation_analysis.md"));
+    fs::write(&path, correlate::markdown(&reports, &correlation))?;
+    let spent = cost::Pricing::for_model(ai_model).cost(correlation.usage.prompt_tokens, correlation.usage.completion_tokens);
+    println!("Correlation Analysis: \n{}", correlation.text);
+    println!("AI usage: {} call(s), {} prompt + {} completion tokens (${:.4})", correlation.calls, correlation.usage.prompt_tokens, correlation.usage.completion_tokens, spent);
+    println!("Saved to {}", path.display());
+    Ok(EXIT_SUCCESS)
+}
+
+/// Handles the `decrypt` subcommand.
+fn decrypt_report(decrypt_matches: &ArgMatches) -> Result<u8, OsintError> {
+    let path = decrypt_matches.get_one::<PathBuf>("report").unwrap();
+    match decrypt_matches.get_one::<PathBuf>("output") {
+        Some(output) => {
+            fs::write(output, crypto::decrypt(&fs::read(path)?, &crypto::passphrase(false)?)?)?;
+            eprintln!("Decrypted {} to {}", path.display(), output.display());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&load_report(path)?)?),
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+/// Handles the `verify` subcommand: exit 0 when the report is intact, 1 when
+/// it no longer matches its checksum or signature.
+fn verify_report(verify_matches: &ArgMatches) -> Result<u8, OsintError> {
+    let path = verify_matches.get_one::<PathBuf>("report").unwrap();
+    let verification = integrity::verify(path, verify_matches.get_one::<PathBuf>("public-key").map(PathBuf::as_path))?;
+    let verdict = |ok: bool| if ok { "OK" } else { "FAILED" };
+    println!("{}: checksum {}", path.display(), verdict(verification.checksum));
+    match verification.signature {
+        Some(signature) => println!("{}: signature {}", path.display(), verdict(signature)),
+        None if integrity::signature_path(path).exists() => println!("{}: signature not checked (pass --public-key)", path.display()),
+        None => {}
+    }
+    Ok(if verification.passed() { EXIT_SUCCESS } else { EXIT_FINDINGS })
+}
+
+async fn run(matches: &ArgMatches, settings: &config::Settings, cancel: &CancellationToken) -> Result<u8, OsintError> {
     let target = matches.get_one::<String>("target").unwrap();
+    let output_on_stdout = matches.get_one::<PathBuf>("output").map_or(matches.get_flag("quiet"), |path| path == Path::new("-"));
+    if signing(matches) && output_on_stdout {
+        return Err(OsintError::InvalidTarget("--sign seals saved report files; it cannot be combined with a report on stdout".to_string()));
+    }
+    if matches.get_flag("encrypt") {
+        if output_on_stdout {
+            return Err(OsintError::InvalidTarget("--encrypt protects saved report files; it cannot be combined with a report on stdout".to_string()));
+        }
+        crypto::use_passphrase(crypto::passphrase(true)?);
+    }
+    if target != "-" {
+        return scan(target, matches, settings, cancel).await.map(|scanned| scanned.code);
+    }
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Err(OsintError::InvalidTarget("target - reads targets from stdin, which is a terminal; pipe a list in, as in `cat domains.txt | osint-recon-tool - whois`".to_string()));
+    }
+    let targets = read_targets(stdin.lock())?;
+    run_batch(&targets, matches, settings, cancel).await
+}
+
+/// Whether saved reports get integrity sidecars.
+fn signing(matches: &ArgMatches) -> bool {
+    matches.get_flag("sign") || matches.contains_id("signing-key")
+}
+
+/// A `--skip-if-scanned-within` duration in seconds: a number with an `s`,
+/// `m`, `h`, `d` or `w` unit, seconds when it has none.
+fn parse_duration(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let (number, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let number: u64 = number.parse().map_err(|_| format!("{:?} is not a duration such as 30m, 12h or 7d", text))?;
+    let unit = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit {:?}; use s, m, h, d or w", unit)),
+    };
+    number.checked_mul(unit).ok_or_else(|| format!("{} is too long", text))
+}
+
+/// Targets from a list, one per line, skipping blanks, `#` comments and repeats.
+fn read_targets(reader: impl BufRead) -> Result<Vec<String>, OsintError> {
+    let mut targets: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let target = line.split('#').next().unwrap_or_default().trim();
+        if !target.is_empty() && !targets.iter().any(|seen| seen == target) {
+            targets.push(target.to_string());
+        }
+    }
+    if targets.is_empty() {
+        return Err(OsintError::InvalidTarget("no targets on stdin".to_string()));
+    }
+    Ok(targets)
+}
+
+/// Scans every target, `--concurrency` at a time, each with its own report.
+/// A failed target is logged and the rest go on; the exit code is the
+/// highest of the targets'.
+async fn run_batch(targets: &[String], matches: &ArgMatches, settings: &config::Settings, cancel: &CancellationToken) -> Result<u8, OsintError> {
+    if matches.get_one::<PathBuf>("output").is_some_and(|path| path != Path::new("-")) {
+        return Err(OsintError::InvalidTarget("--output names a single file; with targets from stdin use --output - (one report per line) or --output-dir".to_string()));
+    }
+    let concurrency = (*matches.get_one::<usize>("concurrency").unwrap()).max(1);
+    info!(targets = targets.len(), concurrency, "scanning targets from stdin");
+    let results: Vec<(u8, bool, triage::Row)> = stream::iter(targets)
+        .map(|target| async move {
+            if cancel.is_cancelled() {
+                return (EXIT_INTERRUPTED, false, triage::Row::failed(target, OsintError::Interrupted.kind()));
+            }
+            match scan(target, matches, settings, cancel).await {
+                Ok(scanned) => (scanned.code, scanned.reused, scanned.row),
+                Err(err) => {
+                    error!(%target, %err, "scan failed");
+                    if matches.get_one::<String>("error-format").is_some_and(|format| format == "json") {
+                        emit_error_report(matches, Some(target), &err);
+                    }
+                    (err.exit_code(), false, triage::Row::failed(target, err.kind()))
+                }
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+    let failed = results.iter().filter(|(code, _, _)| *code > EXIT_FINDINGS).count();
+    let skipped = results.iter().filter(|(_, reused, _)| *reused).count();
+    info!(targets = targets.len(), failed, skipped, "batch done");
+    if let Some(within) = matches.get_raw("skip-if-scanned-within").and_then(|mut values| values.next()) {
+        eprintln!("{} targets scanned, {} skipped as scanned within the last {}", targets.len() - skipped, skipped, within.to_string_lossy());
+    }
+    let code = results.iter().map(|(code, _, _)| *code).max().unwrap_or(EXIT_SUCCESS);
+    let mut rows: Vec<triage::Row> = results.into_iter().map(|(_, _, row)| row).collect();
+    triage::sort(&mut rows);
+    let (_, console) = report_output(matches);
+    let on_terminal = match console {
+        Console::Stdout => std::io::stdout().is_terminal(),
+        _ => std::io::stderr().is_terminal(),
+    };
+    console.say(&if on_terminal { triage::table(&rows) } else { triage::tsv(&rows) });
+    Ok(code)
+}
+
+/// Scans one target: runs its sources, analyzes and saves the report.
+async fn scan(target: &str, matches: &ArgMatches, settings: &config::Settings, cancel: &CancellationToken) -> Result<Scanned, OsintError> {
     let recon_type = matches.get_one::<String>("type").unwrap();
-    let openai_api_key = env::var("OPENAI_API_KEY").map_err(|_| OsintError::MissingApiKey("OPENAI_API_KEY".to_string()))?;
-    
-    let osint_data = match recon_type.as_str() {
-        "whois" => fetch_whois(target).await,
+    let normalized = target::normalize_target(target, recon_type)?;
+    if normalized != target {
+        info!(raw = %target, target = %normalized, "target normalized");
+    }
+    let target = normalized.as_str();
+    target::validate(recon_type, target)?;
+    if matches.contains_id("fields") && matches.get_one::<String>("format").unwrap() != "json" {
+        return Err(OsintError::InvalidTarget(format!("--fields only applies to JSON reports, not --format {}", matches.get_one::<String>("format").unwrap())));
+    }
+    // Open the database up front so a bad path fails before any network work.
+    let store = matches.get_one::<PathBuf>("db").map(|path| store::Store::open(path)).transpose()?;
+    if let (Some(store), Some(&within)) = (&store, matches.get_one::<u64>("skip-if-scanned-within")) {
+        if let Some((scan, data)) = store.latest(target, recon_type, within)? {
+            return reuse_scan(target, matches, scan, data).await;
+        }
+    }
+    let analyzer = analyzer(matches)?;
+    let geoip_paths: Vec<PathBuf> = matches.get_many::<PathBuf>("geoip-db").unwrap_or_default().cloned().collect();
+    let geoip = geoip::install(geoip::GeoIp::open(&geoip_paths)?);
+    let enabled = settings.sources.as_deref();
+    if matches.get_flag("screenshot") || recon_type == "screenshot" || (recon_type == "all" && enabled.is_some_and(|enabled| enabled.iter().any(|source| source == "screenshot"))) {
+        providers::screenshot::chrome_executable(env::var_os("CHROME_PATH").map(PathBuf::from))?;
+    }
+
+    let data = match recon_type.as_str() {
+        "all" => run_all(target, matches, enabled, cancel).await?,
+        source => run_source(source, target, matches, cancel).await?,
+    };
+    let mut data = match geoip {
+        Some(geoip) => geoip.enrich(target, data),
+        None if matches.get_flag("enrich-geo") => providers::geoip::enrich_online(target, data).await,
+        None => data,
+    };
+    // With a token, IPs the DNS pivots turned up also get ipinfo.io attribution.
+    if matches!(recon_type.as_str(), "dns" | "crtsh" | "all") {
+        data = providers::ipinfo::enrich(target, data).await;
+    }
+    normalize::normalize(&mut data);
+
+    let (output, console) = report_output(matches);
+    // With --fields, only the projected report is shown, once it is built.
+    if output.as_deref() != Some(Path::new("-")) && !matches.contains_id("fields") {
+        console.say(&format!("Raw OSINT Data: \n{}", data));
+    }
+    if let Some(summary) = data["summary"].as_str() {
+        console.say(&format!("Summary: {}", summary));
+    }
+    if let Some(cves) = (recon_type == "shodan").then(|| providers::nvd::summary(&data["cves"])).flatten() {
+        console.say(&format!("CVEs: {}", cves));
+    }
+    if recon_type == "hibp" {
+        console.say(&format!("HIBP: {}", hibp_summary(&data)));
+    }
+    if let Some(classification) = (recon_type == "anonymity").then(|| data["classification"].as_str()).flatten() {
+        console.say(&format!("Network: {}", classification));
+    }
+    if recon_type == "dnsbl" {
+        console.say(&format!("Blocklists:\n{}", providers::dnsbl::table(&data)));
+    }
+    if let Some(resolved) = (recon_type == "crtsh").then(|| data["resolved"]["hosts"].as_array()).flatten() {
+        console.say(&format!("Resolved: {} of {} subdomains", resolved.len(), data["resolved"]["tried"]));
+    }
+    if recon_type == "dorks" {
+        console.say(&format!("Dorks:\n{}", providers::dorks::listing(&data)));
+    }
+    if recon_type == "dork" {
+        console.say(&format!("Dork results:\n{}", providers::serpapi::listing(&data)));
+    }
+    if recon_type == "axfr" {
+        console.say(&format!("Zone transfer: {}", providers::axfr::summary(&data)));
+    }
+    let findings = findings::collect(recon_type, &data);
+
+    let mut analysis = None;
+    match analyzer {
+        None => {}
+        Some(_) if cancel.is_cancelled() => warn!("skipping analysis of the partial report"),
+        Some((ai_endpoint, ai_model)) => {
+            let over_budget = if matches.get_one::<String>("max-cost-action").unwrap() == "refuse" { OverBudget::Refuse } else { OverBudget::Truncate };
+            tokio::select! {
+                result = analyze_with_ai(&ai_endpoint, ai_model, &data, matches.get_one::<f64>("max-cost").copied(), over_budget) => match result {
+                    Ok((text, usage)) => {
+                        console.say(&format!("AI Analysis: \n{}", text));
+                        let spent = cost::Pricing::for_model(ai_model).cost(usage.prompt_tokens, usage.completion_tokens);
+                        console.say(&format!("AI usage: {} prompt + {} completion tokens (${:.4})", usage.prompt_tokens, usage.completion_tokens, spent));
+                        analysis = Some(text);
+                    }
+                    Err(err) => error!(%err, "AI analysis failed"),
+                },
+                _ = cancel.cancelled() => warn!("analysis interrupted"),
+            }
+        }
+    }
+
+    write_reports(target, recon_type, matches, &data, analysis.as_deref(), &findings, None)?;
+    if let Some(store) = &store {
+        let id = store.record(target, recon_type, &data, findings::risk_score(&findings))?;
+        info!(id, "scan recorded");
+    }
+    if cancel.is_cancelled() {
+        return Ok(Scanned { code: EXIT_INTERRUPTED, reused: false, row: triage::Row::scanned(target, recon_type, &data, &findings, "interrupted") });
+    }
+
+    for finding in &findings {
+        warn!(severity = ?finding.severity, "{}", finding.message);
+    }
+    export_to_misp(target, recon_type, &data, &findings, matches, console).await?;
+    export_to_taxii(target, &data, &findings, matches, console).await?;
+    let code = exit_code(recon_type, &data, &findings, matches);
+    Ok(Scanned { code, reused: false, row: triage::Row::scanned(target, recon_type, &data, &findings, status(code, &findings)) })
+}
+
+/// The endpoint and model `--analyze` sends the report to, with its key
+/// checked before any recon runs. Without `--analyze` there is no analysis
+/// and no key is needed.
+fn analyzer(matches: &ArgMatches) -> Result<Option<(ai::Endpoint, &str)>, OsintError> {
+    if !matches.get_flag("analyze") {
+        debug!("AI analysis not requested");
+        return Ok(None);
+    }
+    let key_env = matches.get_one::<String>("ai-api-key-env").unwrap();
+    let ai_key = api_key(key_env)?;
+    let ai_endpoint = ai::Endpoint::new(matches.get_one::<String>("ai-base-url").unwrap(), ai_key)?;
+    Ok(Some((ai_endpoint, matches.get_one::<String>("ai-model").unwrap())))
+}
+
+/// How one target's scan went: its exit code, whether a recent stored
+/// result was reused instead of scanning again, and its line in the batch
+/// summary.
+struct Scanned {
+    code: u8,
+    reused: bool,
+    row: triage::Row,
+}
+
+/// The batch summary's status for a scan that ran to the end.
+fn status(code: u8, findings: &[findings::Finding]) -> &'static str {
+    match code {
+        EXIT_SOURCE_FAILED => "partial",
+        _ if !findings.is_empty() => "findings",
+        _ => "ok",
+    }
+}
+
+/// Reports a scan stored in the `--db` database in place of running it
+/// again under `--skip-if-scanned-within`. It is not analyzed or recorded
+/// again; the report keeps the stored scan's timestamp.
+async fn reuse_scan(target: &str, matches: &ArgMatches, scan: store::ScanSummary, data: Value) -> Result<Scanned, OsintError> {
+    let recon_type = matches.get_one::<String>("type").unwrap();
+    let (_, console) = report_output(matches);
+    info!(%target, source = %recon_type, id = scan.id, timestamp = %scan.timestamp, "scanned recently, reusing the stored result");
+    console.say(&format!("Skipped: {} was scanned at {} (scan {}), reusing that result", target, scan.timestamp, scan.id));
+    let findings = findings::collect(recon_type, &data);
+    write_reports(target, recon_type, matches, &data, None, &findings, Some(&scan.timestamp))?;
+    export_to_misp(target, recon_type, &data, &findings, matches, console).await?;
+    export_to_taxii(target, &data, &findings, matches, console).await?;
+    let code = exit_code(recon_type, &data, &findings, matches);
+    Ok(Scanned { code, reused: true, row: triage::Row::scanned(target, recon_type, &data, &findings, "reused") })
+}
+
+/// Where the report goes and where console output goes. `--quiet` sends
+/// the report to stdout unless `--output` names a file.
+fn report_output(matches: &ArgMatches) -> (Option<PathBuf>, Console) {
+    let quiet = matches.get_flag("quiet");
+    let output = matches.get_one::<PathBuf>("output").cloned().or_else(|| quiet.then(|| PathBuf::from("-")));
+    let console = match (quiet, output.as_deref() == Some(Path::new("-"))) {
+        (true, _) => Console::Quiet,
+        (false, true) => Console::Stderr,
+        (false, false) => Console::Stdout,
+    };
+    (output, console)
+}
+
+/// Builds the report in the `--format` asked for and saves it, sealed with
+/// `--sign`, plus the unredacted copy with `--keep-raw`. `scanned_at`
+/// replaces the envelope's timestamp for a reused scan.
+fn write_reports(target: &str, recon_type: &str, matches: &ArgMatches, data: &Value, analysis: Option<&str>, findings: &[findings::Finding], scanned_at: Option<&str>) -> Result<(), OsintError> {
+    let format = ReportFormat::from_name(matches.get_one::<String>("format").unwrap());
+    let fields: Vec<select::FieldPath> = matches.get_many::<select::FieldPath>("fields").unwrap_or_default().cloned().collect();
+    let (output, console) = report_output(matches);
+    let report_on_stdout = output.as_deref() == Some(Path::new("-"));
+    let envelope = |data: &Value| {
+        let mut envelope = report::envelope(target, recon_type, data, analysis, findings);
+        if let Some(timestamp) = scanned_at {
+            envelope["timestamp"] = json!(timestamp);
+        }
+        envelope
+    };
+    let shared = if matches.get_flag("redact") { redactor(matches).redact(data) } else { data.clone() };
+    let report = match format {
+        ReportFormat::Json => {
+            let envelope = envelope(&shared);
+            report::validate(&envelope)?;
+            match fields.is_empty() {
+                true => envelope,
+                false => select::project(&envelope, &fields),
+            }
+        }
+        ReportFormat::Stix => stix::bundle(target, &iocs::extract(&shared), findings)?,
+        ReportFormat::Sarif => sarif::log(target, recon_type, findings),
+    };
+    let compress = matches.get_flag("compress");
+    let report_path = output.unwrap_or_else(|| report_filename(target, format.extension(), compress));
+    if !report_on_stdout && !fields.is_empty() {
+        console.say(&format!("Selected Fields: \n{}", report));
+    }
+    let saved = save_report(&report_path, &report, compress, crypto::passphrase_in_use(), console)?;
+    let signing_key = matches.get_one::<PathBuf>("signing-key").map(PathBuf::as_path);
+    if signing(matches) {
+        integrity::seal(&saved, signing_key)?;
+    }
+    if matches.get_flag("keep-raw") {
+        let raw_path = save_report(&report_filename(target, "raw.json", compress), &envelope(data), compress, crypto::passphrase_in_use(), console)?;
+        if signing(matches) {
+            integrity::seal(&raw_path, signing_key)?;
+        }
+    }
+    Ok(())
+}
+
+/// A finished scan's exit code: failed sources in `all` mode, then
+/// findings under `--fail-on-findings`.
+fn exit_code(recon_type: &str, data: &Value, findings: &[findings::Finding], matches: &ArgMatches) -> u8 {
+    if recon_type == "all" && has_failed_sources(data) {
+        return EXIT_SOURCE_FAILED;
+    }
+    if matches.get_flag("fail-on-findings") && !findings.is_empty() {
+        return EXIT_FINDINGS;
+    }
+    EXIT_SUCCESS
+}
+
+/// Writes the `--error-format json` report to the `--output` path, or to
+/// stderr when there is none. Failing to write it is only logged: the exit
+/// code still tells the caller what went wrong.
+fn emit_error_report(matches: &ArgMatches, target: Option<&str>, err: &OsintError) {
+    let source = matches.get_one::<String>("type").map(String::as_str);
+    let report = report::error_report(target, source, err.kind(), &err.to_string(), err.exit_code());
+    let written = match matches.get_one::<PathBuf>("output") {
+        Some(path) => save_report(path, &report, false, None, Console::Stderr).map(drop),
+        None => writeln!(std::io::stderr(), "{}", report).map_err(OsintError::from),
+    };
+    if let Err(write_err) = written {
+        error!(%write_err, "could not write the error report");
+    }
+}
+
+/// The `--redact` masker. Without a configured salt, tokens are only
+/// consistent within this run.
+fn redactor(matches: &ArgMatches) -> redact::Redactor {
+    let salt = matches.get_one::<String>("redact-salt").cloned().or_else(|| env::var("OSINT_REDACT_SALT").ok()).unwrap_or_else(|| {
+        info!("no redaction salt configured, tokens will not match across runs");
+        uuid::Uuid::new_v4().to_string()
+    });
+    let extra_keys: Vec<String> = matches.get_many::<String>("redact-key").unwrap_or_default().cloned().collect();
+    redact::Redactor::new(salt, &extra_keys)
+}
+
+/// Builds a MISP event from the report when `--misp-url` or `--misp-dry-run`
+/// is given, and prints or pushes it.
+async fn export_to_misp(target: &str, recon_type: &str, data: &Value, findings: &[findings::Finding], matches: &ArgMatches, console: Console) -> Result<(), OsintError> {
+    let misp_url = matches.get_one::<url::Url>("misp-url");
+    let dry_run = matches.get_flag("misp-dry-run");
+    if misp_url.is_none() && !dry_run {
+        return Ok(());
+    }
+    let event = misp::event(target, recon_type, &iocs::extract(data), findings);
+    match misp_url {
+        Some(base) if !dry_run => {
+            let key = matches.get_one::<String>("misp-key").ok_or_else(|| OsintError::MissingApiKey("--misp-key".to_string()))?;
+            misp::push(base, key, &event).await
+        }
+        _ => {
+            console.show(&format!("MISP Event: \n{}", serde_json::to_string_pretty(&event)?));
+            Ok(())
+        }
+    }
+}
+
+/// Pushes the report's STIX bundle to the `--taxii-url` collection when one
+/// is given.
+async fn export_to_taxii(target: &str, data: &Value, findings: &[findings::Finding], matches: &ArgMatches, console: Console) -> Result<(), OsintError> {
+    let (Some(api_root), Some(collection)) = (matches.get_one::<url::Url>("taxii-url"), matches.get_one::<String>("taxii-collection")) else {
+        return Ok(());
+    };
+    let auth = matches.get_one::<String>("taxii-auth").cloned().or_else(|| env::var("TAXII_AUTH").ok().filter(|auth| !auth.is_empty()));
+    let bundle = stix::bundle(target, &iocs::extract(data), findings)?;
+    let ingested = taxii::push(api_root, collection, auth.as_deref(), &bundle).await?;
+    console.say(&format!("TAXII: {} of {} objects ingested into collection {}{}", ingested.succeeded, ingested.total, collection, if ingested.failed > 0 { format!(", {} rejected", ingested.failed) } else { String::new() }));
+    Ok(())
+}
+
+/// Handles the `query` subcommand: a saved report file, or scans in the `--db` database.
+fn query(matches: &ArgMatches) -> Result<u8, OsintError> {
+    if let Some(file) = matches.get_one::<PathBuf>("file") {
+        println!("{}", serde_json::to_string_pretty(&load_report(file)?)?);
+        return Ok(EXIT_SUCCESS);
+    }
+    let path = matches.get_one::<PathBuf>("db").ok_or_else(|| OsintError::InvalidTarget("query needs --db <path>".to_string()))?;
+    let store = store::Store::open(path)?;
+    let output = match (matches.get_one::<i64>("id"), matches.get_one::<String>("target")) {
+        (Some(&id), _) => store.scan(id)?.ok_or_else(|| OsintError::InvalidTarget(format!("no scan with id {}", id)))?,
+        (None, Some(target)) => serde_json::to_value(store.scans_for(target)?)?,
+        (None, None) => unreachable!("clap requires a target or --id"),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(EXIT_SUCCESS)
+}
+
+/// Runs one source, giving up with `Interrupted` once `cancel` fires and the
+/// shutdown grace period has passed.
+async fn run_source(source: &str, target: &str, matches: &ArgMatches, cancel: &CancellationToken) -> Result<Value, OsintError> {
+    tokio::select! {
+        result = source_result(source, target, matches) => result,
+        _ = async {
+            cancel.cancelled().await;
+            sleep(SHUTDOWN_GRACE).await;
+        } => {
+            warn!(source, "source cancelled");
+            Err(OsintError::Interrupted)
+        }
+    }
+}
+
+async fn source_result(source: &str, target: &str, matches: &ArgMatches) -> Result<Value, OsintError> {
+    let started = Instant::now();
+    let result = match source {
+        "whois" => providers::whois::fetch_whois(target, matches.get_one::<String>("whois-provider").unwrap()).await,
+        "dns" => providers::dnsrecords::fetch_dns(target).await,
+        "axfr" => providers::axfr::fetch_axfr(target).await,
         "shodan" => fetch_shodan(target).await,
+        "shodan-dns" => providers::shodan::fetch_shodan_dns(target).await,
+        "shodan-search" => providers::shodan::fetch_shodan_search(target, *matches.get_one::<u32>("max-pages").unwrap(), matches.get_one::<usize>("max-results").copied()).await,
         "hibp" => fetch_hibp(target).await,
+        "emailrep" => providers::emailrep::fetch_emailrep(target).await,
+        "gravatar" => providers::gravatar::fetch_gravatar(target).await,
+        "phone" => providers::phone::fetch_phone(target).await,
+        "emailverify" => providers::emailverify::fetch_emailverify(target, matches.get_flag("smtp-probe")).await,
+        "hunter" => match providers::hunter::fetch_hunter(target, matches.get_one::<usize>("max-results").copied()).await {
+            Ok(data) if matches.get_flag("pivot-hibp") => Ok(pivot_hibp(data).await),
+            result => result,
+        },
+        "company" => match providers::company::fetch_company(target).await {
+            Ok(data) if matches.get_flag("pivot-username") => pivot_username(data, matches.get_one::<PathBuf>("sites").map(PathBuf::as_path)).await,
+            result => result,
+        },
+        "emailauth" => {
+            let selectors: Vec<String> = matches.get_many::<String>("dkim-selector").unwrap_or_default().cloned().collect();
+            providers::emailauth::fetch_emailauth(target, &selectors).await
+        }
+        "http" => with_screenshot(providers::http::fetch_http(target, *matches.get_one::<usize>("max-redirects").unwrap()).await, target, matches).await,
+        "tlscert" => providers::tlscert::fetch_tlscert(target, *matches.get_one::<u16>("port").unwrap()).await,
+        "favicon" => providers::favicon::fetch_favicon(target, matches.get_flag("pivot-shodan")).await,
+        "screenshot" => providers::screenshot::fetch_screenshot(target, matches.get_flag("screenshot-insecure")).await,
+        "urlscan" => {
+            let visibility = matches.get_one::<String>("urlscan-visibility").unwrap();
+            with_screenshot(providers::urlscan::fetch_urlscan(target, visibility, matches.get_flag("download-screenshot")).await, target, matches).await
+        }
+        "virustotal" => providers::virustotal::fetch_virustotal(target).await,
+        "otx" => providers::otx::fetch_otx(target).await,
+        "github" => providers::github::fetch_github(target, *matches.get_one::<usize>("github-max-results").unwrap(), matches.get_flag("github-secrets")).await,
+        "username" => match providers::username::load_sites(matches.get_one::<PathBuf>("sites").map(PathBuf::as_path)) {
+            Ok(sites) => providers::username::fetch_username(matches.get_one::<String>("username").map_or(target, String::as_str), sites).await,
+            Err(err) => Err(err),
+        },
+        "censys" => providers::censys::fetch_censys(target).await,
+        "binaryedge" => providers::binaryedge::fetch_binaryedge(target, *matches.get_one::<u32>("max-pages").unwrap(), matches.get_one::<usize>("max-results").copied()).await,
+        "onyphe" => providers::onyphe::fetch_onyphe(target, matches.get_one::<usize>("max-results").copied()).await,
+        "leakcheck" => providers::leakcheck::fetch_leakcheck(target, matches.get_flag("include-secrets")).await,
+        "intelx" => providers::intelx::fetch_intelx(target, matches.get_one::<usize>("max-results").copied()).await,
+        "zoomeye" => providers::zoomeye::fetch_zoomeye(target, *matches.get_one::<u32>("max-pages").unwrap(), matches.get_one::<usize>("max-results").copied()).await,
+        "reversedns" => providers::reversedns::fetch_reversedns(target).await,
+        "rdap" => providers::rdap::fetch_rdap(target).await,
+        "geoip" => providers::geoip::fetch_geoip(target).await,
+        "ipinfo" => providers::ipinfo::fetch_ipinfo(target).await,
+        "asn" => providers::asn::fetch_asn(target, matches.get_flag("asn-prefixes")).await,
+        "crtsh" => match providers::crtsh::fetch_crtsh(target).await {
+            Ok(mut data) if matches.get_flag("resolve") => {
+                let names = data["subdomains"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect();
+                data["resolved"] = providers::subbrute::resolve_names(names, *matches.get_one::<usize>("dns-concurrency").unwrap()).await?;
+                Ok(data)
+            }
+            result => result,
+        },
+        "subbrute" => match providers::subbrute::load_wordlist(matches.get_one::<PathBuf>("wordlist").map(PathBuf::as_path)) {
+            Ok(words) => providers::subbrute::fetch_subbrute(target, words, *matches.get_one::<usize>("dns-concurrency").unwrap()).await,
+            Err(err) => Err(err),
+        },
+        "dorks" => providers::dorks::load_templates(matches.get_one::<PathBuf>("dork-templates").map(PathBuf::as_path)).map(|templates| providers::dorks::generate(target, &templates, matches.get_flag("dork-links"))),
+        "robots" => providers::robots::fetch_robots(target, *matches.get_one::<usize>("sitemap-limit").unwrap()).await,
+        "wayback" => providers::wayback::fetch_wayback(target, *matches.get_one::<usize>("wayback-limit").unwrap()).await,
+        "securitytrails" => providers::securitytrails::fetch_securitytrails(target).await,
+        "abuseipdb" => providers::abuseipdb::fetch_abuseipdb(target).await,
+        "greynoise" => providers::greynoise::fetch_greynoise(target).await,
+        "anonymity" => providers::anonymity::fetch_anonymity(target).await,
+        "dnsbl" => providers::dnsbl::fetch_dnsbl(target).await,
+        "dork" => providers::serpapi::fetch_dork(target, matches.get_one::<PathBuf>("dorks").map(PathBuf::as_path), matches.get_one::<usize>("max-results").copied()).await,
+        "phish" => providers::phish::fetch_phish(target).await,
+        "threatfox" => providers::threatfox::fetch_threatfox(target).await,
         _ => Err(OsintError::InvalidType),
     };
-    
-    match osint_data {
-        Ok(data) => {
-            println!("Raw OSINT Data: \n{}", data);
-            save_report(target, &data)?;
-            match analyze_with_chatgpt(&openai_api_key, &data).await {
-                Ok(analysis) => println!("ChatGPT Analysis: \n{}", analysis),
-                Err(err) => eprintln!("Error analyzing data with ChatGPT: {}", err),
-            }
+    info!(source, elapsed_ms = started.elapsed().as_millis() as u64, ok = result.is_ok(), "source finished");
+    match (result, matches.get_one::<String>("hook")) {
+        (Ok(data), Some(command)) => {
+            let hook = hooks::Hook { command: command.clone(), timeout: Duration::from_secs(*matches.get_one::<u64>("hook-timeout").unwrap()) };
+            Ok(hook.apply(target, source, data).await)
+        }
+        (result, _) => result,
+    }
+}
+
+/// Adds a `screenshot` of the target to an http or urlscan result under
+/// `--screenshot`. In `all` mode the screenshot source runs on its own
+/// instead. A failed capture is recorded without failing the source.
+async fn with_screenshot(result: Result<Value, OsintError>, target: &str, matches: &ArgMatches) -> Result<Value, OsintError> {
+    let mut data = result?;
+    if !matches.get_flag("screenshot") || matches.get_one::<String>("type").is_some_and(|recon_type| recon_type == "all") {
+        return Ok(data);
+    }
+    data["screenshot"] = match providers::screenshot::fetch_screenshot(target, matches.get_flag("screenshot-insecure")).await {
+        Ok(screenshot) => screenshot,
+        Err(err) => {
+            warn!(%err, "screenshot failed");
+            json!({ "error": err.to_string() })
+        }
+    };
+    Ok(data)
+}
+
+/// Checks every email Hunter discovered against HIBP, paced by the HIBP
+/// rate limiter, and records the outcomes under `hibp`.
+async fn pivot_hibp(data: Value) -> Value {
+    pivot_hibp_with(data, |email| async move { fetch_hibp(&email).await }).await
+}
+
+/// `pivot_hibp` looking each email up with `check`, so tests can stand in
+/// a mock HIBP.
+async fn pivot_hibp_with<F, Fut>(mut data: Value, check: F) -> Value
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<Value, OsintError>>,
+{
+    let emails: Vec<String> = data["emails"].as_array().into_iter().flatten().filter_map(|entry| entry["email"].as_str()).map(str::to_string).collect();
+    info!(count = emails.len(), "pivoting discovered emails into hibp");
+    let results = join_all(emails.iter().cloned().map(check)).await;
+    let breaches: Map<String, Value> = emails
+        .into_iter()
+        .zip(results)
+        .map(|(email, result)| (email, result.unwrap_or_else(|err| json!({ "error": err.to_string() }))))
+        .collect();
+    data["hibp"] = Value::Object(breaches);
+    data
+}
+
+/// Checks every social handle the company source found with the username
+/// checker's sites, and records the outcomes under `usernames`.
+async fn pivot_username(mut data: Value, sites: Option<&Path>) -> Result<Value, OsintError> {
+    let sites = providers::username::load_sites(sites)?;
+    let handles: Vec<String> = data["handles"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect();
+    info!(count = handles.len(), "pivoting company handles into username");
+    let results = join_all(handles.iter().map(|handle| providers::username::fetch_username(handle, sites.clone()))).await;
+    let usernames: Map<String, Value> = handles
+        .into_iter()
+        .zip(results)
+        .map(|(handle, result)| (handle, result.unwrap_or_else(|err| json!({ "error": err.to_string() }))))
+        .collect();
+    data["usernames"] = Value::Object(usernames);
+    Ok(data)
+}
+
+/// Runs every source that accepts the target concurrently, or only the
+/// `enabled` ones of the profile, and merges their results. Sources without
+/// a configured key are reported as skipped, those still running at
+/// `--source-timeout` or `--deadline` as timeout, and after Ctrl-C the
+/// unfinished ones as cancelled; either of the last two lists them under
+/// `incomplete` and marks the report partial.
+async fn run_all(target: &str, matches: &ArgMatches, enabled: Option<&[String]>, cancel: &CancellationToken) -> Result<Value, OsintError> {
+    // Screenshots need a local Chrome, so `all` only takes them on request;
+    // dorks are for manual follow-up and every dork searched costs a SerpAPI
+    // search, so only a profile's sources add them.
+    let wanted = |source: &str| match enabled {
+        Some(enabled) => enabled.iter().any(|name| name == source) || (source == "screenshot" && matches.get_flag("screenshot")),
+        None => match source {
+            "screenshot" => matches.get_flag("screenshot"),
+            "dorks" | "dork" => false,
+            _ => true,
         },
-        Err(err) => eprintln!("Error fetching OSINT data: {}", err),
+    };
+    // Each source gets the target in its own form, e.g. a URL's host for whois.
+    let sources: Vec<(&str, String)> = SOURCES
+        .iter()
+        .copied()
+        .filter(|source| wanted(source))
+        .filter_map(|source| target::normalize_target(target, source).ok().filter(|normalized| target::applies(source, normalized)).map(|normalized| (source, normalized)))
+        .collect();
+    if sources.is_empty() {
+        let which = if enabled.is_some() { "enabled source" } else { "source" };
+        return Err(OsintError::InvalidTarget(format!("no {} accepts {}", which, target)));
     }
-    Ok(())
+
+    // Every source starts at once, so the deadline is just a tighter timeout.
+    let source_timeout = *matches.get_one::<u64>("source-timeout").unwrap();
+    let limit = matches.get_one::<u64>("deadline").map_or(source_timeout, |deadline| source_timeout.min(*deadline));
+    let results = join_all(sources.iter().map(|(source, target)| async move {
+        match timeout(Duration::from_secs(limit), run_source(source, target, matches, cancel)).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!(source, limit_secs = limit, "source timed out");
+                Err(OsintError::TimedOut(limit))
+            }
+        }
+    }))
+    .await;
+    let mut merged = Map::new();
+    for ((source, _), result) in sources.into_iter().zip(results) {
+        let entry = match result {
+            Ok(data) => json!({ "status": "ok", "data": data }),
+            Err(OsintError::MissingApiKey(key)) => json!({ "status": "skipped", "reason": format!("{} is not set", key) }),
+            Err(OsintError::Interrupted) => json!({ "status": "cancelled" }),
+            Err(err @ OsintError::TimedOut(_)) => json!({ "status": "timeout", "reason": err.to_string() }),
+            Err(err) => json!({ "status": "error", "error": err.to_string() }),
+        };
+        merged.insert(source.to_string(), entry);
+    }
+    enrich_shodan_with_ptr(&mut merged);
+    let summary = summarize(&merged);
+    let mut report = json!({ "target": target, "summary": summary, "sources": merged });
+    let subdomains = merge_subdomains(&merged);
+    if let (Some(via), false) = (matches.get_one::<String>("resolve-via"), subdomains.is_empty()) {
+        match resolve_subdomains(subdomains.keys().cloned().collect(), via).await {
+            Ok(resolved) => report["resolved"] = json!(resolved),
+            Err(err) => warn!(%err, via, "could not resolve the subdomains"),
+        }
+    }
+    if !subdomains.is_empty() {
+        report["subdomains"] = json!(subdomains);
+    }
+    let incomplete: Vec<&String> = merged.iter().filter(|(_, entry)| matches!(entry["status"].as_str(), Some("timeout" | "cancelled"))).map(|(source, _)| source).collect();
+    if cancel.is_cancelled() || !incomplete.is_empty() {
+        report["partial"] = json!(true);
+        report["incomplete"] = json!(incomplete);
+    }
+    Ok(report)
+}
+
+/// Subdomains found by crt.sh and by brute force, each with the sources that
+/// found it.
+fn merge_subdomains(sources: &Map<String, Value>) -> BTreeMap<String, Vec<&'static str>> {
+    let mut subdomains: BTreeMap<String, Vec<&'static str>> = BTreeMap::new();
+    let found = [
+        ("crtsh", sources.get("crtsh").map(|entry| entry["data"]["subdomains"].clone())),
+        ("tlscert", sources.get("tlscert").map(|entry| entry["data"]["subdomains"].clone())),
+        ("binaryedge", sources.get("binaryedge").map(|entry| entry["data"]["subdomains"].clone())),
+        ("subbrute", sources.get("subbrute").map(|entry| entry["data"]["hosts"].as_array().into_iter().flatten().map(|host| host["name"].clone()).collect())),
+    ];
+    for (source, names) in found {
+        for name in names.as_ref().and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            subdomains.entry(name.to_string()).or_default().push(source);
+        }
+    }
+    subdomains
+}
+
+/// `--resolve-via`: the addresses of `names` as hostname/IP pairs, sorted by
+/// hostname. Shodan answers for all names in a few batched requests.
+async fn resolve_subdomains(names: Vec<String>, via: &str) -> Result<Vec<providers::shodan::DnsEntity>, OsintError> {
+    let mut entities = match via {
+        "shodan" => {
+            let key = api_key("SHODAN_API_KEY")?;
+            providers::shodan::resolve_from(SHODAN_BASE_URL, &key, &names).await?.entities
+        }
+        _ => providers::dns::resolve_all(&providers::dns::resolver(), names, RESOLVE_CONCURRENCY)
+            .await
+            .into_iter()
+            .flat_map(|(hostname, ips)| ips.into_iter().map(move |ip| providers::shodan::DnsEntity { hostname: hostname.clone(), ip: ip.to_string(), source: "dns" }))
+            .collect(),
+    };
+    entities.sort_by(|a, b| (&a.hostname, &a.ip).cmp(&(&b.hostname, &b.ip)));
+    info!(via, resolved = entities.len(), "subdomains resolved");
+    Ok(entities)
+}
+
+/// Copies the PTR hostnames reversedns found into Shodan's result, which
+/// only lists the hostnames Shodan itself has seen.
+fn enrich_shodan_with_ptr(sources: &mut Map<String, Value>) {
+    let Some(names) = sources.get("reversedns").filter(|entry| entry["status"] == "ok").map(|entry| entry["data"]["names"].clone()) else {
+        return;
+    };
+    let ptr_names: Vec<Value> = names.as_array().into_iter().flatten().map(|name| name["hostname"].clone()).collect();
+    if let Some(shodan) = sources.get_mut("shodan").filter(|entry| entry["status"] == "ok" && entry["data"].is_object()) {
+        shodan["data"]["ptr_names"] = Value::from(ptr_names);
+    }
+}
+
+/// One line of per-status counts, plus GreyNoise's verdict, the network
+/// classification, blocklistings and the accounts Gravatar and EmailRep
+/// found when those ran.
+fn summarize(sources: &Map<String, Value>) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for entry in sources.values() {
+        let status = entry["status"].as_str().unwrap_or("error");
+        match counts.iter_mut().find(|(seen, _)| *seen == status) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((status, 1)),
+        }
+    }
+    let mut summary = counts.iter().map(|(status, count)| format!("{} {}", count, status)).collect::<Vec<_>>().join(", ");
+    if let Some(company) = sources.get("company").and_then(|entry| entry["data"]["name"].as_str()) {
+        summary.push_str(&format!("; company: {}", company));
+    }
+    if let Some(classification) = sources.get("greynoise").and_then(|entry| entry["data"]["classification"].as_str()) {
+        summary.push_str(&format!("; GreyNoise: {}", classification));
+    }
+    if let Some(classification) = sources.get("anonymity").and_then(|entry| entry["data"]["classification"].as_str()) {
+        summary.push_str(&format!("; network: {}", classification));
+    }
+    let blocklisted: Vec<&str> = sources.get("dnsbl").and_then(|entry| entry["data"]["listed_on"].as_array()).into_iter().flatten().filter_map(Value::as_str).collect();
+    if !blocklisted.is_empty() {
+        summary.push_str(&format!("; blocklisted on: {}", blocklisted.join(", ")));
+    }
+    let mut families: Vec<&str> = sources.get("threatfox").and_then(|entry| entry["data"]["matches"].as_array()).into_iter().flatten().filter_map(|entry| entry["family"].as_str()).collect();
+    families.sort_unstable();
+    families.dedup();
+    if !families.is_empty() {
+        summary.push_str(&format!("; known-malicious: {}", families.join(", ")));
+    }
+    let threatlists: Vec<&str> = sources.get("onyphe").and_then(|entry| entry["data"]["threatlists"].as_array()).into_iter().flatten().filter_map(Value::as_str).collect();
+    if !threatlists.is_empty() {
+        summary.push_str(&format!("; Onyphe threat lists: {}", threatlists.join(", ")));
+    }
+    if let Some(exposed) = sources.get("leakcheck").and_then(|entry| entry["data"]["password_exposed"].as_u64()).filter(|&exposed| exposed > 0) {
+        summary.push_str(&format!("; passwords leaked in {} source(s)", exposed));
+    }
+    let not_licensed: Vec<&str> = sources.get("intelx").and_then(|entry| entry["data"]["not_licensed"].as_array()).into_iter().flatten().filter_map(Value::as_str).collect();
+    if let Some(intelx) = sources.get("intelx").and_then(|entry| entry["data"]["total"].as_u64()).filter(|&total| total > 0) {
+        summary.push_str(&format!("; IntelX records: {}", intelx));
+    }
+    if !not_licensed.is_empty() {
+        summary.push_str(&format!("; IntelX not licensed for: {}", not_licensed.join(", ")));
+    }
+    if sources.get("phish").is_some_and(|entry| entry["data"]["listed"] == true) {
+        summary.push_str("; known phish");
+    }
+    if sources.get("axfr").is_some_and(|entry| entry["data"]["transfer_allowed"] == true) {
+        summary.push_str("; zone transfer allowed");
+    }
+    let missing = sources.get("http").map(|entry| findings::missing_headers(&entry["data"])).unwrap_or_default();
+    if !missing.is_empty() {
+        summary.push_str(&format!("; missing security headers: {}", missing.join(", ")));
+    }
+    if let Some(cves) = sources.get("shodan").and_then(|entry| providers::nvd::summary(&entry["data"]["cves"])) {
+        summary.push_str(&format!("; CVEs: {}", cves));
+    }
+    if let Some(entry) = sources.get("hibp").filter(|entry| entry["status"] == "ok") {
+        summary.push_str(&format!("; HIBP: {}", hibp_summary(&entry["data"])));
+    }
+    let linked: Vec<&str> = sources.get("gravatar").and_then(|entry| entry["data"]["accounts"].as_array()).into_iter().flatten().filter_map(|account| account["service"].as_str()).collect();
+    if !linked.is_empty() {
+        summary.push_str(&format!("; Gravatar accounts: {}", linked.join(", ")));
+    }
+    let profiles: Vec<&str> = sources.get("emailrep").and_then(|entry| entry["data"]["profiles"].as_array()).into_iter().flatten().filter_map(Value::as_str).collect();
+    if !profiles.is_empty() {
+        summary.push_str(&format!("; profiles: {}", profiles.join(", ")));
+    }
+    summary
 }
 
+fn has_failed_sources(data: &Value) -> bool {
+    data["sources"].as_object().is_some_and(|sources| sources.values().any(|entry| matches!(entry["status"].as_str(), Some("error" | "timeout"))))
+}
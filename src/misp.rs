@@ -0,0 +1,89 @@
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::info;
+use url::Url;
+
+use crate::findings::{Finding, Severity};
+use crate::iocs::Iocs;
+use crate::OsintError;
+
+/// Builds a MISP event carrying the report's indicators as attributes and
+/// its findings as text attributes, tagged with the target and source.
+pub fn event(target: &str, source: &str, iocs: &Iocs, findings: &[Finding]) -> Value {
+    let mut attributes = Vec::new();
+    let mut push = |kind: &str, category: &str, value: &str, comment: String| {
+        attributes.push(json!({ "type": kind, "category": category, "value": value, "to_ids": false, "comment": comment }));
+    };
+    for domain in &iocs.domains {
+        push("domain", "Network activity", domain, String::new());
+    }
+    for ip in &iocs.ips {
+        push("ip-dst", "Network activity", ip, String::new());
+    }
+    for url in &iocs.urls {
+        push("url", "Network activity", url, String::new());
+    }
+    for hash in &iocs.hashes {
+        push(hash_type(hash), "Payload delivery", hash, String::new());
+    }
+    for email in &iocs.emails {
+        push("email-src", "Payload delivery", email, String::new());
+    }
+    for finding in findings {
+        push("text", "Other", &finding.message, format!("severity: {:?}", finding.severity).to_lowercase());
+    }
+
+    json!({
+        "Event": {
+            "info": format!("OSINT recon of {} ({})", target, source),
+            "threat_level_id": threat_level(findings),
+            // Organisation only and not yet analysed; the analyst decides wider sharing.
+            "distribution": "0",
+            "analysis": "0",
+            "Tag": [
+                { "name": format!("osint-recon:target=\"{}\"", target) },
+                { "name": format!("osint-recon:source=\"{}\"", source) },
+            ],
+            "Attribute": attributes,
+        }
+    })
+}
+
+/// POSTs `event` to the MISP instance at `base`.
+pub async fn push(base: &Url, key: &str, event: &Value) -> Result<(), OsintError> {
+    let url = format!("{}/events", base.as_str().trim_end_matches('/'));
+    let client = crate::client_builder().build()?;
+    let request = client
+        .post(&url)
+        .header("Authorization", key)
+        .header("Accept", "application/json")
+        .json(event)
+        .build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials("--misp-key".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+    let created: Value = response.json().await?;
+    info!(id = %created["Event"]["id"], "MISP event created");
+    Ok(())
+}
+
+fn hash_type(hash: &str) -> &'static str {
+    match hash.len() {
+        32 => "md5",
+        40 => "sha1",
+        _ => "sha256",
+    }
+}
+
+/// MISP threat levels run from 1 (high) to 4 (undefined).
+fn threat_level(findings: &[Finding]) -> &'static str {
+    match findings.iter().map(|finding| finding.severity).max() {
+        Some(Severity::High) => "1",
+        Some(Severity::Medium) => "2",
+        Some(Severity::Low) => "3",
+        None => "4",
+    }
+}
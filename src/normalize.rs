@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+/// Fields holding host names, whose values (or, for maps such as the merged
+/// `subdomains`, keys) are lowercased and stripped of trailing dots.
+const HOST_FIELDS: &[&str] = &["subdomains", "nameservers", "hostnames", "cnames", "sans", "ptr_names", "domains"];
+/// String lists whose order carries meaning and is kept as produced.
+const ORDERED_FIELDS: &[&str] = &["notes"];
+
+/// Puts a report into a canonical form so reruns only differ where the
+/// results do: lists of plain values are sorted and deduplicated, and host
+/// names canonicalized. Lists of objects keep their order, which is often
+/// meaningful (a redirect chain, a certificate chain leaf first).
+pub fn normalize(value: &mut Value) {
+    normalize_field(value, None);
+}
+
+fn normalize_field(value: &mut Value, field: Option<&str>) {
+    let hosts = field.is_some_and(|field| HOST_FIELDS.contains(&field));
+    match value {
+        Value::Array(items) => {
+            items.iter_mut().for_each(|item| normalize_field(item, None));
+            if hosts {
+                items.iter_mut().for_each(canonicalize_host);
+            }
+            if items.iter().all(is_scalar) && !field.is_some_and(|field| ORDERED_FIELDS.contains(&field)) {
+                items.sort_by(compare_scalars);
+                items.dedup();
+            }
+        }
+        Value::Object(map) => {
+            let mut normalized = Map::new();
+            for (key, mut item) in std::mem::take(map) {
+                normalize_field(&mut item, Some(&key));
+                let key = if hosts { canonical_host(&key) } else { key };
+                // Two spellings of one host: merge what each was listed with.
+                match normalized.get_mut(&key) {
+                    Some(Value::Array(existing)) if item.is_array() => {
+                        existing.extend(item.as_array_mut().map(std::mem::take).unwrap_or_default());
+                        existing.sort_by(compare_scalars);
+                        existing.dedup();
+                    }
+                    Some(_) => {}
+                    None => {
+                        normalized.insert(key, item);
+                    }
+                }
+            }
+            *map = normalized;
+        }
+        _ => {}
+    }
+}
+
+fn canonical_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn canonicalize_host(value: &mut Value) {
+    if let Value::String(host) = value {
+        *host = canonical_host(host);
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+/// Orders null, then booleans, numbers (by value) and strings.
+fn compare_scalars(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            _ => 3,
+        }
+    }
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a.as_f64().unwrap_or_default().total_cmp(&b.as_f64().unwrap_or_default()),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
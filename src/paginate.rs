@@ -0,0 +1,51 @@
+use std::future::Future;
+
+use tracing::debug;
+
+use crate::OsintError;
+
+/// Everything a paginated lookup returned.
+pub struct Paginated<T, P> {
+    pub items: Vec<T>,
+    pub pages: u32,
+    /// The first page as fetched, for totals and other metadata.
+    pub first: P,
+    /// Whether `max_results` cut the results short.
+    pub capped: bool,
+}
+
+/// Fetches pages from `start` on and collects their items. `fetch` gets a
+/// page by its cursor (a page number, offset or token); `next_page` splits a
+/// page into its items and the cursor of the following page, given the page's
+/// cursor and how many items came before it. Paging stops when there is no
+/// next cursor, a page is empty, or `max_results` items are in.
+pub async fn paginate<T, P, C, F, Fut, N>(start: C, max_results: Option<usize>, mut fetch: F, mut next_page: N) -> Result<Paginated<T, P>, OsintError>
+where
+    C: Clone,
+    F: FnMut(C) -> Fut,
+    Fut: Future<Output = Result<P, OsintError>>,
+    N: FnMut(&P, &C, usize) -> (Vec<T>, Option<C>),
+{
+    let max_results = max_results.unwrap_or(usize::MAX);
+    let mut items = Vec::new();
+    let mut pages = 1;
+    let first = fetch(start.clone()).await?;
+    let (batch, mut cursor) = next_page(&first, &start, 0);
+    let mut last_empty = batch.is_empty();
+    items.extend(batch);
+    while let Some(current) = cursor.clone() {
+        if last_empty || items.len() >= max_results {
+            break;
+        }
+        let page = fetch(current.clone()).await?;
+        pages += 1;
+        let (batch, following) = next_page(&page, &current, items.len());
+        debug!(pages, items = items.len() + batch.len(), "page read");
+        last_empty = batch.is_empty();
+        items.extend(batch);
+        cursor = following;
+    }
+    let capped = items.len() > max_results || (items.len() == max_results && cursor.is_some() && !last_empty);
+    items.truncate(max_results);
+    Ok(Paginated { items, pages, first, capped })
+}
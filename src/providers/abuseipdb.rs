@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::findings::Severity;
+use crate::{OsintError, RetryPolicy};
+
+const ABUSEIPDB_CHECK_URL: &str = "https://api.abuseipdb.com/api/v2/check";
+const MAX_AGE_DAYS: &str = "90";
+
+/// AbuseIPDB's report category IDs, indexed from 1.
+const CATEGORIES: &[&str] = &[
+    "DNS Compromise",
+    "DNS Poisoning",
+    "Fraud Orders",
+    "DDoS Attack",
+    "FTP Brute-Force",
+    "Ping of Death",
+    "Phishing",
+    "Fraud VoIP",
+    "Open Proxy",
+    "Web Spam",
+    "Email Spam",
+    "Blog Spam",
+    "VPN IP",
+    "Port Scan",
+    "Hacking",
+    "SQL Injection",
+    "Spoofing",
+    "Brute-Force",
+    "Bad Web Bot",
+    "Exploited Host",
+    "Web App Attack",
+    "SSH",
+    "IoT Targeted",
+];
+
+pub async fn fetch_abuseipdb(ip: &str) -> Result<Value, OsintError> {
+    let key = crate::api_key("ABUSEIPDB_API_KEY")?;
+    check_from(ABUSEIPDB_CHECK_URL, &key, ip).await
+}
+
+/// The `check` call against `check_url`, so tests can point it at a mock
+/// server. The address goes in the query string, where an IPv6 address's
+/// colons are percent-encoded.
+pub async fn check_from(check_url: &str, key: &str, ip: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let request = client
+        .get(check_url)
+        .query(&[("ipAddress", ip), ("maxAgeInDays", MAX_AGE_DAYS), ("verbose", "")])
+        .header("Key", key)
+        .header("Accept", "application/json")
+        .build()?;
+    // The free tier's limit is daily, so a 429 will not clear by waiting.
+    let response = crate::send_with_policy(&client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials("ABUSEIPDB_API_KEY".to_string())),
+        StatusCode::TOO_MANY_REQUESTS => {
+            let body: Value = response.json().await.unwrap_or_default();
+            let detail = body["errors"][0]["detail"].as_str().unwrap_or("AbuseIPDB daily request limit reached");
+            return Err(OsintError::QuotaExhausted(detail.to_string()));
+        }
+        status => return Err(OsintError::ApiStatus { status, url: check_url.to_string() }),
+    }
+
+    let raw: Value = response.json().await?;
+    let data = &raw["data"];
+    let categories: BTreeSet<&str> = data["reports"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|report| report["categories"].as_array().into_iter().flatten())
+        .filter_map(Value::as_u64)
+        .filter_map(|id| CATEGORIES.get((id as usize).checked_sub(1)?).copied())
+        .collect();
+    let score = data["abuseConfidenceScore"].as_u64().unwrap_or(0);
+
+    Ok(json!({
+        "ip": data["ipAddress"],
+        "abuse_confidence_score": score,
+        "severity": severity(score),
+        "total_reports": data["totalReports"],
+        "distinct_reporters": data["numDistinctUsers"],
+        "last_reported_at": data["lastReportedAt"],
+        "categories": categories,
+        "isp": data["isp"],
+        "usage_type": data["usageType"],
+        "domain": data["domain"],
+        "country_code": data["countryCode"],
+        "is_whitelisted": data["isWhitelisted"],
+    }))
+}
+
+/// Maps a 0–100 confidence score onto the report's severity scale.
+pub fn severity(score: u64) -> Option<Severity> {
+    match score {
+        0 => None,
+        1..=24 => Some(Severity::Low),
+        25..=74 => Some(Severity::Medium),
+        _ => Some(Severity::High),
+    }
+}
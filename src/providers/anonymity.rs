@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::env;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use super::asn;
+use super::feed::{self, Origin};
+use crate::OsintError;
+
+const TOR_EXIT_LIST_URL: &str = "https://check.torproject.org/torbulkexitlist";
+const CACHE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+const CACHE_FILE: &str = "torbulkexitlist";
+
+/// Well-known ASNs of commercial VPN providers.
+const VPN_ASNS: &[(u64, &str)] = &[
+    (9009, "M247"),
+    (39351, "31173 Services (Mullvad)"),
+    (60068, "Datacamp (CDN77)"),
+    (212238, "Datacamp"),
+    (136787, "Tefincom (NordVPN)"),
+    (209854, "Cyberzone (Surfshark)"),
+    (51852, "Private Layer"),
+    (147049, "PacketHub"),
+];
+
+/// Well-known ASNs of cloud and hosting providers.
+const DATACENTER_ASNS: &[(u64, &str)] = &[
+    (16509, "Amazon"),
+    (14618, "Amazon"),
+    (15169, "Google"),
+    (396982, "Google Cloud"),
+    (8075, "Microsoft"),
+    (14061, "DigitalOcean"),
+    (24940, "Hetzner"),
+    (16276, "OVH"),
+    (63949, "Akamai (Linode)"),
+    (20473, "Vultr (Choopa)"),
+    (13335, "Cloudflare"),
+    (45102, "Alibaba"),
+    (31898, "Oracle Cloud"),
+    (12876, "Scaleway"),
+];
+
+/// What kind of network an IP sits in, most telling first.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Classification {
+    TorExit,
+    LikelyVpn,
+    Datacenter,
+    ResidentialUnknown,
+}
+
+pub async fn fetch_anonymity(ip: &str) -> Result<Value, OsintError> {
+    let ip: IpAddr = ip.parse().map_err(|_| OsintError::InvalidTarget(format!("{} is not an IP address", ip)))?;
+    let asn = match asn::is_routable(ip) {
+        true => asn::origin_asn(ip).await.unwrap_or_else(|err| {
+            warn!(%ip, %err, "ASN lookup failed, classifying by the Tor exit list alone");
+            None
+        }),
+        false => None,
+    };
+    check_from(TOR_EXIT_LIST_URL, &cache_dir(), ip, asn).await
+}
+
+/// The check against the exit list at `url`, cached in `cache_dir`, so tests
+/// can use a mock server and a scratch directory. `asn` is the IP's origin
+/// AS when it is known.
+pub async fn check_from(url: &str, cache_dir: &Path, ip: IpAddr, asn: Option<u64>) -> Result<Value, OsintError> {
+    let (exits, origin) = exit_list_from(url, cache_dir).await?;
+    let classification = classify(ip, &exits, asn);
+    info!(%ip, ?classification, "anonymity check done");
+    Ok(json!({
+        "ip": ip.to_string(),
+        "classification": classification,
+        "tor_exit": exits.contains(&ip),
+        "asn": asn,
+        "network": asn.and_then(known_network),
+        "exit_list": { "entries": exits.len(), "stale": origin == Origin::Stale },
+    }))
+}
+
+pub fn classify(ip: IpAddr, exits: &HashSet<IpAddr>, asn: Option<u64>) -> Classification {
+    if exits.contains(&ip) {
+        return Classification::TorExit;
+    }
+    match asn {
+        Some(asn) if VPN_ASNS.iter().any(|(known, _)| *known == asn) => Classification::LikelyVpn,
+        Some(asn) if DATACENTER_ASNS.iter().any(|(known, _)| *known == asn) => Classification::Datacenter,
+        _ => Classification::ResidentialUnknown,
+    }
+}
+
+fn known_network(asn: u64) -> Option<&'static str> {
+    VPN_ASNS.iter().chain(DATACENTER_ASNS).find(|(known, _)| *known == asn).map(|(_, name)| *name)
+}
+
+/// The addresses of a bulk exit list, one per line. Blank lines, comments
+/// and anything that is not an address are skipped.
+pub fn parse_exit_list(text: &str) -> HashSet<IpAddr> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).filter_map(|line| line.parse().ok()).collect()
+}
+
+/// The exit list, from the cache while it is under an hour old, else
+/// refreshed. A failed refresh falls back to the cached copy.
+pub async fn exit_list_from(url: &str, cache_dir: &Path) -> Result<(HashSet<IpAddr>, Origin), OsintError> {
+    let (text, origin) = feed::fetch(url, cache_dir, CACHE_FILE, CACHE_MAX_AGE).await?;
+    Ok((parse_exit_list(&text), origin))
+}
+
+/// Where the exit list is cached between runs.
+pub fn cache_dir() -> PathBuf {
+    env::temp_dir().join("osint-recon-tool-tor")
+}
@@ -0,0 +1,174 @@
+use std::net::IpAddr;
+
+use hickory_resolver::TokioAsyncResolver;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::dns;
+use crate::OsintError;
+
+const BGPVIEW_BASE_URL: &str = "https://api.bgpview.io";
+
+#[derive(Serialize, Debug)]
+pub struct Announcement {
+    pub prefix: String,
+    pub asn: Option<u64>,
+    pub as_name: Option<String>,
+    pub description: Option<String>,
+    pub country: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SiblingPrefixes {
+    pub asn: u64,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IpAsn {
+    pub ip: IpAddr,
+    pub announced: bool,
+    /// "not announced" for private, reserved and unrouted addresses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub announcements: Vec<Announcement>,
+    pub rir: Option<String>,
+    pub allocation_date: Option<String>,
+    /// Other prefixes announced by the same ASNs, with `--asn-prefixes`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sibling_prefixes: Vec<SiblingPrefixes>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AsnReport {
+    pub target: String,
+    /// One entry per address; a domain is resolved to all of its addresses.
+    pub lookups: Vec<IpAsn>,
+}
+
+pub async fn fetch_asn(target: &str, with_prefixes: bool) -> Result<Value, OsintError> {
+    lookup_from(BGPVIEW_BASE_URL, &dns::resolver(), target, with_prefixes).await
+}
+
+/// The lookups against bgpview at `base_url`, resolving domains through
+/// `resolver`, so tests can point both at mock servers.
+pub async fn lookup_from(base_url: &str, resolver: &TokioAsyncResolver, target: &str, with_prefixes: bool) -> Result<Value, OsintError> {
+    let ips: Vec<IpAddr> = match target.parse() {
+        Ok(ip) => vec![ip],
+        Err(_) => resolver.lookup_ip(target).await?.iter().collect(),
+    };
+    if ips.is_empty() {
+        return Err(OsintError::InvalidTarget(format!("{} does not resolve to an address", target)));
+    }
+
+    let client = crate::client_builder().build()?;
+    let mut lookups = Vec::with_capacity(ips.len());
+    for ip in ips {
+        lookups.push(lookup(&client, base_url, ip, with_prefixes).await?);
+    }
+    Ok(serde_json::to_value(AsnReport { target: target.to_string(), lookups })?)
+}
+
+async fn lookup(client: &Client, base_url: &str, ip: IpAddr, with_prefixes: bool) -> Result<IpAsn, OsintError> {
+    if !is_routable(ip) {
+        return Ok(parse_ip(ip, &Value::Null));
+    }
+    let data = get(client, format!("{}/ip/{}", base_url, ip)).await?;
+    let mut result = parse_ip(ip, &data);
+    if with_prefixes {
+        let mut asns: Vec<u64> = result.announcements.iter().filter_map(|announcement| announcement.asn).collect();
+        asns.sort_unstable();
+        asns.dedup();
+        for asn in asns {
+            let prefixes = get(client, format!("{}/asn/{}/prefixes", base_url, asn)).await?;
+            result.sibling_prefixes.push(parse_prefixes(asn, &prefixes));
+        }
+    }
+    Ok(result)
+}
+
+/// Reads the `data` of a bgpview `/ip/{ip}` response. An IP without prefixes
+/// is reported as not announced.
+pub fn parse_ip(ip: IpAddr, data: &Value) -> IpAsn {
+    let text = |value: &Value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+    let announcements: Vec<Announcement> = data["prefixes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|prefix| {
+            Some(Announcement {
+                prefix: prefix["prefix"].as_str()?.to_string(),
+                asn: prefix["asn"]["asn"].as_u64(),
+                as_name: text(&prefix["asn"]["name"]),
+                description: text(&prefix["asn"]["description"]).or_else(|| text(&prefix["description"])),
+                country: text(&prefix["asn"]["country_code"]).or_else(|| text(&prefix["country_code"])),
+            })
+        })
+        .collect();
+    let announced = !announcements.is_empty();
+    IpAsn {
+        ip,
+        announced,
+        note: (!announced).then(|| "not announced".to_string()),
+        announcements,
+        rir: text(&data["rir_allocation"]["rir_name"]),
+        allocation_date: text(&data["rir_allocation"]["date_allocated"]),
+        sibling_prefixes: Vec::new(),
+    }
+}
+
+/// Reads the `data` of a bgpview `/asn/{asn}/prefixes` response.
+pub fn parse_prefixes(asn: u64, data: &Value) -> SiblingPrefixes {
+    let prefixes = |family: &str| data[family].as_array().into_iter().flatten().filter_map(|entry| entry["prefix"].as_str()).map(str::to_string).collect();
+    SiblingPrefixes { asn, ipv4: prefixes("ipv4_prefixes"), ipv6: prefixes("ipv6_prefixes") }
+}
+
+/// The AS that announces `ip`'s most specific prefix, if any.
+pub async fn origin_asn(ip: IpAddr) -> Result<Option<u64>, OsintError> {
+    let client = crate::client_builder().build()?;
+    let data = get(&client, format!("{}/ip/{}", BGPVIEW_BASE_URL, ip)).await?;
+    Ok(origin(&parse_ip(ip, &data)))
+}
+
+/// The origin AS of a lookup: the announcement with the longest prefix.
+pub fn origin(lookup: &IpAsn) -> Option<u64> {
+    let length = |announcement: &&Announcement| announcement.prefix.rsplit_once('/').and_then(|(_, length)| length.parse::<u8>().ok()).unwrap_or_default();
+    lookup.announcements.iter().filter(|announcement| announcement.asn.is_some()).max_by_key(length).and_then(|announcement| announcement.asn)
+}
+
+/// Whether `ip` could be announced on the public internet at all; other
+/// addresses are answered locally without a request.
+pub fn is_routable(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [first, second, ..] = v4.octets();
+            // 100.64.0.0/10 is carrier-grade NAT space.
+            let shared = first == 100 && (second & 0xc0) == 64;
+            !(v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_documentation() || v4.is_unspecified() || v4.is_broadcast() || v4.is_multicast() || shared)
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            // Only global unicast (2000::/3) is routed; 2001:db8::/32 is documentation.
+            (first & 0xe000) == 0x2000 && !(first == 0x2001 && v6.segments()[1] == 0x0db8)
+        }
+    }
+}
+
+async fn get(client: &Client, url: String) -> Result<Value, OsintError> {
+    let response = crate::send_with_retries(client, client.get(&url).build()?).await?;
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(OsintError::RetriesExhausted(url));
+    }
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url });
+    }
+    let body: Value = response.json().await?;
+    if body["status"] != "ok" {
+        let message = body["status_message"].as_str().unwrap_or("unexpected response");
+        return Err(OsintError::ProviderError(format!("bgpview: {}", message)));
+    }
+    Ok(body["data"].clone())
+}
@@ -0,0 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use hickory_resolver::proto::op::{Message, MessageType, OpCode, Query, ResponseCode};
+use hickory_resolver::proto::rr::{Record, RecordType};
+use hickory_resolver::Name;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tracing::{info, warn};
+
+use super::dns;
+use crate::OsintError;
+
+/// How long one nameserver gets to complete the whole transfer.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(15);
+/// Stops a hostile or enormous zone from exhausting memory.
+const MAX_RECORDS: usize = 100_000;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Transferred,
+    Refused,
+    Timeout,
+    Error,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NameserverAttempt {
+    pub nameserver: String,
+    pub address: Option<IpAddr>,
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub record_count: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AxfrReport {
+    pub domain: String,
+    pub attempts: Vec<NameserverAttempt>,
+    /// Whether any nameserver handed over the zone.
+    pub transfer_allowed: bool,
+    /// Records from every successful transfer, deduplicated, by type.
+    pub records: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub async fn fetch_axfr(domain: &str) -> Result<Value, OsintError> {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let zone = Name::from_str(&format!("{}.", domain)).map_err(|err| OsintError::InvalidTarget(format!("{}: {}", domain, err)))?;
+    let resolver = dns::resolver();
+    let nameservers: Vec<String> = resolver.ns_lookup(zone.clone()).await?.iter().map(|ns| ns.0.to_utf8().trim_end_matches('.').to_string()).collect();
+
+    // Every address of every nameserver is asked: the instances behind one
+    // name are often configured apart, and one of them may let the zone go.
+    let per_nameserver = futures::future::join_all(nameservers.into_iter().map(|nameserver| {
+        let resolver = &resolver;
+        let zone = &zone;
+        async move {
+            let addresses: Vec<IpAddr> = match resolver.lookup_ip(nameserver.as_str()).await {
+                Ok(lookup) => lookup.iter().collect(),
+                Err(err) => return vec![(NameserverAttempt { nameserver, address: None, outcome: Outcome::Error, detail: Some(err.to_string()), record_count: 0 }, Vec::new())],
+            };
+            if addresses.is_empty() {
+                return vec![(NameserverAttempt { nameserver, address: None, outcome: Outcome::Error, detail: Some("no address".to_string()), record_count: 0 }, Vec::new())];
+            }
+            futures::future::join_all(addresses.into_iter().map(|address| attempt(&nameserver, SocketAddr::new(address, 53), zone, TRANSFER_TIMEOUT))).await
+        }
+    }))
+    .await;
+    let (report_attempts, transfers): (Vec<NameserverAttempt>, Vec<Vec<Record>>) = per_nameserver.into_iter().flatten().unzip();
+    let records = group_records(transfers.iter().flatten());
+    let transfer_allowed = report_attempts.iter().any(|attempt| attempt.outcome == Outcome::Transferred);
+    Ok(serde_json::to_value(AxfrReport { domain, attempts: report_attempts, transfer_allowed, records })?)
+}
+
+/// Transferred records by type, as `name ttl data` lines, so the copies of
+/// a zone several nameservers handed over appear once.
+pub fn group_records<'a>(transferred: impl IntoIterator<Item = &'a Record>) -> BTreeMap<String, BTreeSet<String>> {
+    let mut records: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for record in transferred {
+        let Some(data) = record.data() else {
+            continue;
+        };
+        records.entry(record.record_type().to_string()).or_default().insert(format!("{} {} {}", record.name().to_utf8().trim_end_matches('.'), record.ttl(), data));
+    }
+    records
+}
+
+/// `ALLOWED by ns1.example.com (192.0.2.53, 120 records)` when a transfer
+/// went through, else how the attempts ended, as in `refused 2, timeout 1`.
+pub fn summary(data: &Value) -> String {
+    let attempts: Vec<&Value> = data["attempts"].as_array().into_iter().flatten().collect();
+    let allowed: Vec<String> = attempts
+        .iter()
+        .filter(|attempt| attempt["outcome"] == "transferred")
+        .map(|attempt| format!("{} ({}, {} records)", attempt["nameserver"].as_str().unwrap_or("?"), attempt["address"].as_str().unwrap_or("?"), attempt["record_count"]))
+        .collect();
+    if !allowed.is_empty() {
+        return format!("ALLOWED by {}", allowed.join(", "));
+    }
+    let mut outcomes: BTreeMap<&str, usize> = BTreeMap::new();
+    for attempt in &attempts {
+        *outcomes.entry(attempt["outcome"].as_str().unwrap_or("error")).or_default() += 1;
+    }
+    match outcomes.is_empty() {
+        true => "no nameservers to ask".to_string(),
+        false => outcomes.iter().map(|(outcome, count)| format!("{} {}", outcome, count)).collect::<Vec<_>>().join(", "),
+    }
+}
+
+/// One transfer attempt against `server`, which answers for `nameserver`,
+/// within `limit`. Refusals, timeouts and errors are outcomes, not
+/// failures of the check.
+pub async fn attempt(nameserver: &str, server: SocketAddr, zone: &Name, limit: Duration) -> (NameserverAttempt, Vec<Record>) {
+    let (outcome, detail, records) = match timeout(limit, transfer(server, zone)).await {
+        Ok(Ok(records)) => (Outcome::Transferred, None, records),
+        Ok(Err(Failure::Refused(reason))) => (Outcome::Refused, Some(reason), Vec::new()),
+        Ok(Err(Failure::Error(reason))) => (Outcome::Error, Some(reason), Vec::new()),
+        Err(_) => (Outcome::Timeout, None, Vec::new()),
+    };
+    match outcome {
+        Outcome::Transferred => warn!(%nameserver, %server, records = records.len(), "zone transfer allowed"),
+        _ => info!(%nameserver, %server, ?outcome, "zone transfer not allowed"),
+    }
+    (NameserverAttempt { nameserver: nameserver.to_string(), address: Some(server.ip()), outcome, detail, record_count: records.len() }, records)
+}
+
+/// Why a server did not hand over the zone: it said no, or the exchange
+/// broke down.
+enum Failure {
+    Refused(String),
+    Error(String),
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(err: std::io::Error) -> Self {
+        Failure::Error(err.to_string())
+    }
+}
+
+/// Requests the zone over TCP and reads messages until the closing SOA.
+async fn transfer(server: SocketAddr, zone: &Name) -> Result<Vec<Record>, Failure> {
+    let mut request = Message::new();
+    request.set_id(rand_id()).set_message_type(MessageType::Query).set_op_code(OpCode::Query).set_recursion_desired(false);
+    request.add_query(Query::query(zone.clone(), RecordType::AXFR));
+    let bytes = request.to_vec().map_err(|err| Failure::Error(err.to_string()))?;
+
+    let mut stream = TcpStream::connect(server).await?;
+    stream.write_all(&(bytes.len() as u16).to_be_bytes()).await?;
+    stream.write_all(&bytes).await?;
+
+    let mut records = Vec::new();
+    let mut soa_seen = 0;
+    while soa_seen < 2 {
+        let length = match stream.read_u16().await {
+            Ok(length) => length as usize,
+            Err(_) if records.is_empty() => return Err(Failure::Refused("connection closed without an answer".to_string())),
+            Err(_) => return Err(Failure::Error("connection closed mid-transfer".to_string())),
+        };
+        let mut buffer = vec![0; length];
+        stream.read_exact(&mut buffer).await?;
+        let response = Message::from_vec(&buffer).map_err(|err| Failure::Error(err.to_string()))?;
+        if response.response_code() != ResponseCode::NoError {
+            return Err(Failure::Refused(response.response_code().to_string()));
+        }
+        if response.answers().is_empty() {
+            return Err(Failure::Refused("empty answer".to_string()));
+        }
+        for record in response.answers() {
+            if record.record_type() == RecordType::SOA {
+                soa_seen += 1;
+            }
+            records.push(record.clone());
+        }
+        if records.len() > MAX_RECORDS {
+            return Err(Failure::Error(format!("zone exceeds {} records", MAX_RECORDS)));
+        }
+    }
+    // The closing SOA repeats the opening one.
+    records.pop();
+    Ok(records)
+}
+
+fn rand_id() -> u16 {
+    let bytes = uuid::Uuid::new_v4().into_bytes();
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, SecondsFormat};
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+use crate::paginate::paginate;
+use crate::target::{self, TargetKind};
+use crate::{OsintError, RetryPolicy};
+
+const BINARYEDGE_BASE_URL: &str = "https://api.binaryedge.io";
+/// Banner characters kept per service; full banners can run to kilobytes.
+const BANNER_EXCERPT_CHARS: usize = 512;
+
+/// What BinaryEdge has seen on an IP, or the subdomains it knows of a
+/// domain, reading up to `max_pages` pages of them. Each request costs a
+/// credit, and the free tier has few.
+pub async fn fetch_binaryedge(target: &str, max_pages: u32, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("BINARYEDGE_API_KEY")?;
+    match target::classify(target) {
+        TargetKind::Ip => host_from(BINARYEDGE_BASE_URL, &key, target).await,
+        _ => subdomains_from(BINARYEDGE_BASE_URL, &key, target, max_pages, max_results).await,
+    }
+}
+
+/// `/v2/query/ip/{ip}` against `base_url`, so tests can point it at a mock
+/// server. The per-port events are flattened into the port and service
+/// structure of a Shodan host record.
+pub async fn host_from(base_url: &str, key: &str, ip: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let body = get(&client, key, format!("{}/v2/query/ip/{}", base_url, ip)).await?;
+    let services = flatten_events(&body);
+    let ports: BTreeSet<u64> = services.iter().filter_map(|service| service["port"].as_u64()).collect();
+    info!(ip, events = body["total"].as_u64().unwrap_or_default(), services = services.len(), "binaryedge host lookup done");
+    Ok(json!({
+        "ip_str": ip,
+        "ports": ports,
+        "hostnames": [],
+        "data": services,
+        "total": body["total"],
+    }))
+}
+
+/// `/v2/query/domains/subdomain/{domain}` against `base_url`, page by page.
+pub async fn subdomains_from(base_url: &str, key: &str, domain: &str, max_pages: u32, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let fetch = |page: u32| get(&client, key, format!("{}/v2/query/domains/subdomain/{}?page={}", base_url, domain, page));
+    let results = paginate(1, max_results, fetch, |body: &Value, &page: &u32, before| {
+        let names: Vec<String> = body["events"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect();
+        let total = body["total"].as_u64().unwrap_or_default();
+        debug!(page, total, fetched = before + names.len(), "binaryedge subdomain page read");
+        let more = !names.is_empty() && ((before + names.len()) as u64) < total && page < max_pages;
+        (names, more.then_some(page + 1))
+    })
+    .await?;
+    let subdomains: BTreeSet<String> = results.items.into_iter().collect();
+    info!(domain, subdomains = subdomains.len(), "binaryedge subdomain lookup done");
+    Ok(json!({
+        "domain": domain,
+        "subdomains": subdomains,
+        "total": results.first["total"],
+        "pages_fetched": results.pages,
+        "capped": results.capped,
+    }))
+}
+
+async fn get(client: &Client, key: &str, url: String) -> Result<Value, OsintError> {
+    let request = client.get(&url).header("X-Key", key).build()?;
+    // The free tier's credits are monthly, so a 429 will not clear by waiting.
+    let response = crate::send_with_policy(client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json().await?);
+    }
+    // BinaryEdge answers 404 for an IP or domain it has nothing on.
+    if status == StatusCode::NOT_FOUND {
+        return Ok(json!({ "total": 0, "events": [] }));
+    }
+    let body: Value = response.json().await.unwrap_or_default();
+    let message = body["message"].as_str().or(body["title"].as_str()).unwrap_or_default();
+    Err(match status {
+        StatusCode::TOO_MANY_REQUESTS => OsintError::QuotaExhausted(format!("BinaryEdge credits exhausted for this period{}", detail(message))),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN if message.to_ascii_lowercase().contains("credit") => OsintError::QuotaExhausted(format!("BinaryEdge credits exhausted for this period{}", detail(message))),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => OsintError::InvalidCredentials("BINARYEDGE_API_KEY".to_string()),
+        status => OsintError::ApiStatus { status, url },
+    })
+}
+
+fn detail(message: &str) -> String {
+    match message.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", message),
+    }
+}
+
+/// BinaryEdge groups a host's results into one event per port, each with a
+/// result per scanning module. Every port becomes one service: its name,
+/// product and version from the first module that identified it, the banner
+/// of the first that grabbed one, and the names of all its modules.
+pub fn flatten_events(body: &Value) -> Vec<Value> {
+    body["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|event| {
+            let results: Vec<&Value> = event["results"].as_array().into_iter().flatten().collect();
+            let first = |pick: &dyn Fn(&Value) -> Option<String>| results.iter().find_map(|result| pick(result));
+            let text = |value: &Value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+            let service = |result: &Value| result["result"]["data"]["service"].clone();
+            let modules: BTreeSet<String> = results.iter().filter_map(|result| text(&result["origin"]["type"])).collect();
+            let banner = first(&|result| text(&service(result)["banner"]).or_else(|| text(&result["result"]["data"]["banner"])));
+            let last_seen = results.iter().filter_map(|result| result["origin"]["ts"].as_i64()).max();
+            json!({
+                "ip_str": first(&|result| text(&result["target"]["ip"])),
+                "port": event["port"].as_u64().or_else(|| results.iter().find_map(|result| result["target"]["port"].as_u64())),
+                "transport": first(&|result| text(&result["target"]["protocol"])).unwrap_or_else(|| "tcp".to_string()),
+                "service": first(&|result| text(&service(result)["name"])),
+                "product": first(&|result| text(&service(result)["product"])),
+                "version": first(&|result| text(&service(result)["version"])),
+                "data": banner.map(|banner| banner.chars().take(BANNER_EXCERPT_CHARS).collect::<String>()),
+                "modules": modules,
+                // Module timestamps are in milliseconds.
+                "timestamp": last_seen.and_then(DateTime::from_timestamp_millis).map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            })
+        })
+        .collect()
+}
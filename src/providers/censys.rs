@@ -0,0 +1,68 @@
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::OsintError;
+
+const CENSYS_HOSTS_URL: &str = "https://search.censys.io/api/v2/hosts";
+const CREDENTIAL_VARS: &str = "CENSYS_API_ID/CENSYS_API_SECRET";
+
+pub async fn fetch_censys(ip: &str) -> Result<Value, OsintError> {
+    let id = crate::api_key("CENSYS_API_ID")?;
+    let secret = crate::api_key("CENSYS_API_SECRET")?;
+    fetch_censys_from(CENSYS_HOSTS_URL, &id, &secret, ip).await
+}
+
+/// The host lookup against `hosts_url`, so tests can point it at a mock
+/// server. IPv6 addresses go into the path as they are: colons are allowed
+/// in a path segment.
+pub async fn fetch_censys_from(hosts_url: &str, id: &str, secret: &str, ip: &str) -> Result<Value, OsintError> {
+    let url = format!("{}/{}", hosts_url, ip);
+    let client = crate::client_builder().build()?;
+    let request = client.get(&url).basic_auth(id, Some(secret)).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        // Censys answers 422 for hosts it has never observed.
+        StatusCode::UNPROCESSABLE_ENTITY => {
+            info!(%ip, "host not found in Censys");
+            return Ok(json!({ "ip": ip, "found": false, "services": [] }));
+        }
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials(CREDENTIAL_VARS.to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+
+    let raw: Value = response.json().await?;
+    let host = &raw["result"];
+    let services: Vec<Value> = host["services"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|service| {
+            json!({
+                "port": service["port"],
+                "transport": service["transport_protocol"],
+                "service_name": service["service_name"],
+                "certificate_names": service["tls"]["certificates"]["leaf_data"]["names"],
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "ip": ip,
+        "found": true,
+        "services": services,
+        "autonomous_system": {
+            "asn": host["autonomous_system"]["asn"],
+            "name": host["autonomous_system"]["name"],
+            "bgp_prefix": host["autonomous_system"]["bgp_prefix"],
+            "country_code": host["autonomous_system"]["country_code"],
+        },
+        "location": {
+            "country": host["location"]["country"],
+            "city": host["location"]["city"],
+            "coordinates": host["location"]["coordinates"],
+        },
+        "raw": raw,
+    }))
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::time::Duration;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+use url::Url;
+
+use super::http;
+use crate::OsintError;
+
+const CLEARBIT_URL: &str = "https://company.clearbit.com/v2/companies/find";
+const HOMEPAGE_TIMEOUT: Duration = Duration::from_secs(20);
+/// Hosts whose links on a homepage name the company's accounts, and the
+/// platform each is reported under.
+const SOCIAL_HOSTS: &[(&str, &str)] = &[
+    ("twitter.com", "twitter"),
+    ("x.com", "twitter"),
+    ("linkedin.com", "linkedin"),
+    ("facebook.com", "facebook"),
+    ("github.com", "github"),
+    ("instagram.com", "instagram"),
+    ("youtube.com", "youtube"),
+];
+/// First path segments of share and intent links, which name no account.
+const NOT_HANDLES: &[&str] = &["share", "sharer", "sharer.php", "intent", "home", "i", "hashtag", "search"];
+
+/// The organization behind a domain, in one shape whoever supplied it.
+#[derive(Serialize, Debug, Default)]
+pub struct Company {
+    pub domain: String,
+    /// `clearbit` or `homepage`; absent when nothing was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub found: bool,
+    pub name: Option<String>,
+    pub legal_name: Option<String>,
+    pub description: Option<String>,
+    pub employees_range: Option<String>,
+    pub location: Option<String>,
+    pub industry_tags: Vec<String>,
+    /// Account handle by platform, as in `twitter: acme` or `linkedin:
+    /// company/acme`.
+    pub social: BTreeMap<String, String>,
+    /// Usernames among the handles, for `--pivot-username`.
+    pub handles: Vec<String>,
+}
+
+/// Clearbit's Company API when `CLEARBIT_API_KEY` is set, otherwise, or
+/// when Clearbit has nothing, the name and description the homepage gives
+/// in its meta tags.
+pub async fn fetch_company(domain: &str) -> Result<Value, OsintError> {
+    let key = env::var("CLEARBIT_API_KEY").ok().filter(|key| !key.is_empty());
+    let clearbit = key.as_deref().map(|key| (CLEARBIT_URL, key));
+    Ok(serde_json::to_value(lookup_from(clearbit, &http::target_url(domain)?, domain).await?)?)
+}
+
+/// The lookup against Clearbit at `clearbit`'s URL with its key, and then
+/// `homepage`, so tests can point both at a mock server.
+pub async fn lookup_from(clearbit: Option<(&str, &str)>, homepage: &Url, domain: &str) -> Result<Company, OsintError> {
+    if let Some((url, key)) = clearbit {
+        if let Some(company) = find_from(url, key, domain).await? {
+            info!(domain, "Clearbit knows the company");
+            return Ok(company);
+        }
+        info!(domain, "Clearbit has no company for the domain, reading the homepage");
+    }
+    let company = match scrape(homepage, domain).await {
+        Ok(company) => company,
+        // Clearbit's empty answer stands when the site is down.
+        Err(err) if clearbit.is_some() => {
+            warn!(domain, %err, "homepage unreachable");
+            None
+        }
+        Err(err) => return Err(err),
+    };
+    Ok(company.unwrap_or_else(|| {
+        info!(domain, "no company data found");
+        Company { domain: domain.to_string(), ..Company::default() }
+    }))
+}
+
+/// Clearbit's record for `domain`, or `None` when it has none. Clearbit
+/// answers 202 while it is still looking up a domain it has not seen.
+async fn find_from(url: &str, key: &str, domain: &str) -> Result<Option<Company>, OsintError> {
+    let client = crate::client_builder().build()?;
+    let response = crate::send_with_retries(&client, client.get(url).query(&[("domain", domain)]).bearer_auth(key).build()?).await?;
+    match response.status() {
+        StatusCode::OK => Ok(Some(parse_clearbit(domain, &response.json().await?))),
+        StatusCode::ACCEPTED | StatusCode::NOT_FOUND => Ok(None),
+        StatusCode::UNAUTHORIZED => Err(OsintError::InvalidCredentials("CLEARBIT_API_KEY".to_string())),
+        StatusCode::PAYMENT_REQUIRED => Err(OsintError::QuotaExhausted("Clearbit lookups exhausted".to_string())),
+        status => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+}
+
+pub fn parse_clearbit(domain: &str, body: &Value) -> Company {
+    let text = |value: &Value| value.as_str().map(str::trim).filter(|text| !text.is_empty()).map(str::to_string);
+    let mut industry_tags: Vec<String> = [&body["category"]["sector"], &body["category"]["industryGroup"], &body["category"]["industry"], &body["category"]["subIndustry"]].into_iter().filter_map(text).collect();
+    industry_tags.extend(body["tags"].as_array().into_iter().flatten().filter_map(text));
+    dedup(&mut industry_tags);
+    let social: BTreeMap<String, String> = ["twitter", "linkedin", "facebook", "crunchbase"].into_iter().filter_map(|platform| Some((platform.to_string(), text(&body[platform]["handle"])?))).collect();
+    let location = text(&body["location"]).or_else(|| {
+        let parts: Vec<String> = [&body["geo"]["city"], &body["geo"]["state"], &body["geo"]["country"]].into_iter().filter_map(text).collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    });
+    Company {
+        domain: domain.to_string(),
+        source: Some("clearbit".to_string()),
+        found: true,
+        name: text(&body["name"]),
+        legal_name: text(&body["legalName"]),
+        description: text(&body["description"]),
+        employees_range: text(&body["metrics"]["employeesRange"]),
+        location,
+        industry_tags,
+        handles: handles(&social),
+        social,
+    }
+}
+
+/// The homepage's meta tags and social links, or `None` when it gives
+/// neither a name nor a description.
+async fn scrape(homepage: &Url, domain: &str) -> Result<Option<Company>, OsintError> {
+    let client = crate::client_builder().timeout(HOMEPAGE_TIMEOUT).build()?;
+    let response = crate::send_with_retries(&client, client.get(homepage.clone()).build()?).await?;
+    if !response.status().is_success() {
+        info!(%homepage, status = response.status().as_u16(), "homepage has no content");
+        return Ok(None);
+    }
+    let content_type = response.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let (bytes, _) = http::read_capped(response, http::MAX_BODY_BYTES).await?;
+    Ok(parse_homepage(domain, &http::decode_body(&bytes, content_type.as_deref())))
+}
+
+pub fn parse_homepage(domain: &str, body: &str) -> Option<Company> {
+    let meta = |key: &str| http::meta_content(body, key).filter(|value| !value.is_empty());
+    let name = meta("og:site_name").or_else(|| meta("application-name")).or_else(|| http::page_title(body));
+    let description = meta("og:description").or_else(|| meta("description"));
+    if name.is_none() && description.is_none() {
+        return None;
+    }
+    let social = social_links(body);
+    Some(Company { domain: domain.to_string(), source: Some("homepage".to_string()), found: true, name, description, handles: handles(&social), social, ..Company::default() })
+}
+
+/// The first account linked for each platform in `href`s of `body`.
+fn social_links(body: &str) -> BTreeMap<String, String> {
+    let mut social = BTreeMap::new();
+    for (start, _) in body.match_indices("href=") {
+        let rest = &body[start + "href=".len()..];
+        let Some(quote) = rest.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+            continue;
+        };
+        let Some(link) = rest[1..].split(quote).next().and_then(|link| Url::parse(link).ok()) else {
+            continue;
+        };
+        let host = link.host_str().unwrap_or_default().trim_start_matches("www.").to_ascii_lowercase();
+        let Some((_, platform)) = SOCIAL_HOSTS.iter().find(|(known, _)| *known == host) else {
+            continue;
+        };
+        let segments: Vec<&str> = link.path_segments().into_iter().flatten().filter(|segment| !segment.is_empty()).collect();
+        let handle = match (*platform, segments.as_slice()) {
+            (_, [first, ..]) if NOT_HANDLES.contains(&first.to_ascii_lowercase().as_str()) => continue,
+            ("linkedin" | "youtube", [kind, name, ..]) if !kind.starts_with('@') => format!("{}/{}", kind, name),
+            (_, [first, ..]) => first.to_string(),
+            _ => continue,
+        };
+        social.entry(platform.to_string()).or_insert(handle);
+    }
+    social
+}
+
+/// The handles that could be usernames elsewhere: the last part of each,
+/// without `@`, when it is made of username characters.
+fn handles(social: &BTreeMap<String, String>) -> Vec<String> {
+    let mut handles: Vec<String> = social
+        .values()
+        .filter_map(|handle| handle.rsplit('/').next())
+        .map(|handle| handle.trim_start_matches('@').to_ascii_lowercase())
+        .filter(|handle| !handle.is_empty() && handle.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c)))
+        .collect();
+    dedup(&mut handles);
+    handles
+}
+
+/// Drops repeats, keeping the first of each in place.
+fn dedup(values: &mut Vec<String>) {
+    let mut seen = Vec::new();
+    values.retain(|value| {
+        let fresh = !seen.contains(value);
+        seen.push(value.clone());
+        fresh
+    });
+}
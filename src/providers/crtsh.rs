@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::OsintError;
+
+const CRTSH_URL: &str = "https://crt.sh/";
+/// crt.sh routinely takes tens of seconds on popular domains.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Deserialize, Debug)]
+struct CertificateEntry {
+    #[serde(default)]
+    issuer_name: Option<String>,
+    #[serde(default)]
+    name_value: String,
+    #[serde(default)]
+    not_before: Option<String>,
+    #[serde(default)]
+    not_after: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Certificate {
+    pub issuer: Option<String>,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CrtshReport {
+    pub domain: String,
+    pub subdomains: Vec<String>,
+    pub certificates: Vec<Certificate>,
+}
+
+pub async fn fetch_crtsh(domain: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().timeout(REQUEST_TIMEOUT).build()?;
+    let request = client.get(CRTSH_URL).query(&[("q", format!("%.{}", domain).as_str()), ("output", "json")]).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: CRTSH_URL.to_string() });
+    }
+
+    let body = response.text().await.map_err(|err| err.without_url())?;
+    let report = parse_crtsh(domain, &body)?;
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Turns a crt.sh JSON body into deduplicated in-scope subdomains. crt.sh
+/// serves HTML error pages under load, which surface as a provider error.
+pub fn parse_crtsh(domain: &str, body: &str) -> Result<CrtshReport, OsintError> {
+    let entries: Vec<CertificateEntry> = serde_json::from_str(body).map_err(|err| {
+        warn!(%err, bytes = body.len(), "crt.sh returned a non-JSON body");
+        OsintError::ProviderError(format!("crt.sh returned an unreadable response: {}", err))
+    })?;
+
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let suffix = format!(".{}", domain);
+    let mut subdomains = BTreeSet::new();
+    let mut certificates = BTreeSet::new();
+    for entry in entries {
+        // One certificate can list several names, separated by newlines.
+        for name in entry.name_value.lines() {
+            let name = name.trim().trim_start_matches("*.").trim_end_matches('.').to_ascii_lowercase();
+            if name == domain || name.ends_with(&suffix) {
+                subdomains.insert(name);
+            }
+        }
+        certificates.insert(Certificate { issuer: entry.issuer_name, not_before: entry.not_before, not_after: entry.not_after });
+    }
+
+    Ok(CrtshReport { domain, subdomains: subdomains.into_iter().collect(), certificates: certificates.into_iter().collect() })
+}
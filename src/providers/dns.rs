@@ -0,0 +1,82 @@
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::proto::op::ResponseCode;
+use hickory_resolver::TokioAsyncResolver;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use futures::stream::{self, StreamExt};
+use tracing::{debug, info, warn};
+
+use crate::OsintError;
+
+static NAMESERVER: OnceLock<IpAddr> = OnceLock::new();
+
+/// Points every resolver built afterwards at `nameserver` instead of the
+/// system configuration. Called once from `--nameserver`.
+pub fn use_nameserver(nameserver: IpAddr) {
+    let _ = NAMESERVER.set(nameserver);
+}
+
+/// Builds the resolver shared by every DNS-backed provider, falling back to
+/// public defaults when the system configuration cannot be read.
+pub fn resolver() -> TokioAsyncResolver {
+    if let Some(nameserver) = NAMESERVER.get() {
+        let servers = NameServerConfigGroup::from_ips_clear(&[*nameserver], 53, true);
+        return TokioAsyncResolver::tokio(ResolverConfig::from_parts(None, Vec::new(), servers), ResolverOpts::default());
+    }
+    TokioAsyncResolver::tokio_from_system_conf().unwrap_or_else(|err| {
+        warn!(%err, "system resolver configuration unavailable, using defaults");
+        TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+    })
+}
+
+/// Looks up the TXT records at `name`, joining each record's character-strings
+/// into one value. A name without TXT records yields an empty list.
+pub async fn txt_records(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<String>, ResolveError> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => Ok(lookup
+            .iter()
+            .map(|txt| txt.txt_data().iter().map(|part| String::from_utf8_lossy(part)).collect())
+            .collect()),
+        Err(err) if is_no_records(&err) => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Whether `err` says the name has no such records. The resolver reports
+/// SERVFAIL and REFUSED answers as "no records" too, but those are failures.
+pub fn is_no_records(err: &ResolveError) -> bool {
+    matches!(err.kind(), ResolveErrorKind::NoRecordsFound { response_code, .. } if matches!(*response_code, ResponseCode::NoError | ResponseCode::NXDomain))
+}
+
+/// Resolves `host` to its first address, for sources that only take IPs.
+pub async fn resolve_ip(resolver: &TokioAsyncResolver, host: &str) -> Result<IpAddr, OsintError> {
+    let ip = resolver
+        .lookup_ip(host)
+        .await?
+        .iter()
+        .next()
+        .ok_or_else(|| OsintError::InvalidTarget(format!("{} does not resolve to an address", host)))?;
+    info!(%host, %ip, "resolved hostname");
+    Ok(ip)
+}
+
+/// Resolves every name in `names`, `concurrency` at a time, returning the
+/// addresses of those that resolve.
+pub async fn resolve_all(resolver: &TokioAsyncResolver, names: Vec<String>, concurrency: usize) -> Vec<(String, Vec<IpAddr>)> {
+    stream::iter(names)
+        .map(|name| async move {
+            match resolver.lookup_ip(name.as_str()).await {
+                Ok(lookup) => (name, lookup.iter().collect()),
+                Err(err) => {
+                    debug!(%name, %err, "name did not resolve");
+                    (name, Vec::new())
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .filter(|(_, ips): &(String, Vec<IpAddr>)| futures::future::ready(!ips.is_empty()))
+        .collect()
+        .await
+}
@@ -0,0 +1,171 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use futures::future::join_all;
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info};
+
+use super::dns;
+use crate::OsintError;
+
+/// Some lists answer slowly; past this a list counts as not answering.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The lists checked by default, as (name, zone).
+pub const DEFAULT_LISTS: &[(&str, &str)] = &[("Spamhaus ZEN", "zen.spamhaus.org"), ("SpamCop", "bl.spamcop.net"), ("Barracuda", "b.barracudacentral.org")];
+
+static EXTRA_ZONES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Adds the zones of the config file's `dnsbl` setting to the default lists.
+pub fn use_extra_zones(zones: Vec<String>) {
+    let _ = EXTRA_ZONES.set(zones);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blocklist {
+    pub name: String,
+    pub zone: String,
+}
+
+/// The default lists and the configured extra zones, which are named after
+/// their zone.
+pub fn blocklists() -> Vec<Blocklist> {
+    let mut lists: Vec<Blocklist> = DEFAULT_LISTS.iter().map(|(name, zone)| Blocklist { name: name.to_string(), zone: zone.to_string() }).collect();
+    for zone in EXTRA_ZONES.get().into_iter().flatten() {
+        if !lists.iter().any(|list| list.zone.eq_ignore_ascii_case(zone)) {
+            lists.push(Blocklist { name: zone.clone(), zone: zone.clone() });
+        }
+    }
+    lists
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Listed,
+    Clean,
+    /// The list did not answer in time, which says nothing either way.
+    NoAnswer,
+    /// The list answered, but with an error code or a failure.
+    Error,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ListCheck {
+    pub list: String,
+    pub zone: String,
+    pub status: Status,
+    /// The A records returned, as in `127.0.0.2`.
+    pub codes: Vec<String>,
+    /// What the codes mean on this list.
+    pub reasons: Vec<String>,
+}
+
+pub async fn fetch_dnsbl(ip: &str) -> Result<Value, OsintError> {
+    let ip: IpAddr = ip.parse().map_err(|_| OsintError::InvalidTarget(format!("{} is not an IP address", ip)))?;
+    check_with(&dns::resolver(), ip, &blocklists(), LOOKUP_TIMEOUT).await
+}
+
+/// Asks every list in `lists` about `ip` at once through `resolver`, each
+/// given `timeout` to answer, so tests can use a local server.
+pub async fn check_with(resolver: &TokioAsyncResolver, ip: IpAddr, lists: &[Blocklist], timeout: Duration) -> Result<Value, OsintError> {
+    let checks: Vec<ListCheck> = join_all(lists.iter().map(|list| check_list(resolver, ip, list, timeout))).await;
+    let listed: Vec<&str> = checks.iter().filter(|check| check.status == Status::Listed).map(|check| check.list.as_str()).collect();
+    info!(%ip, lists = checks.len(), listed = listed.len(), "blocklist lookups done");
+    Ok(serde_json::json!({ "ip": ip.to_string(), "listed_on": listed, "checks": checks }))
+}
+
+async fn check_list(resolver: &TokioAsyncResolver, ip: IpAddr, list: &Blocklist, timeout: Duration) -> ListCheck {
+    let name = query_name(ip, &list.zone);
+    let answer = tokio::time::timeout(timeout, resolver.ipv4_lookup(name.as_str())).await;
+    debug!(%name, ?answer, "blocklist queried");
+    let (status, codes) = match answer {
+        Err(_) => (Status::NoAnswer, Vec::new()),
+        Ok(Ok(lookup)) => {
+            let codes: Vec<Ipv4Addr> = lookup.iter().map(|record| record.0).collect();
+            (verdict(&codes), codes)
+        }
+        Ok(Err(err)) if dns::is_no_records(&err) => (Status::Clean, Vec::new()),
+        Ok(Err(err)) if matches!(err.kind(), ResolveErrorKind::Timeout) => (Status::NoAnswer, Vec::new()),
+        Ok(Err(err)) => {
+            return ListCheck { list: list.name.clone(), zone: list.zone.clone(), status: Status::Error, codes: Vec::new(), reasons: vec![err.to_string()] };
+        }
+    };
+    ListCheck { list: list.name.clone(), zone: list.zone.clone(), status, reasons: codes.iter().map(|code| reason(&list.zone, *code)).collect(), codes: codes.iter().map(Ipv4Addr::to_string).collect() }
+}
+
+/// An answer in 127.255.255.0/24 is a list's error code, not a listing.
+fn verdict(codes: &[Ipv4Addr]) -> Status {
+    match codes.iter().any(|code| !is_error_code(*code)) {
+        true => Status::Listed,
+        false if codes.is_empty() => Status::Clean,
+        false => Status::Error,
+    }
+}
+
+fn is_error_code(code: Ipv4Addr) -> bool {
+    let [first, second, third, _] = code.octets();
+    (first, second, third) == (127, 255, 255)
+}
+
+/// `{reversed ip}.{zone}`: the octets of an IPv4 address reversed, or the 32
+/// nibbles of an IPv6 one, as in `2.0.0.127.zen.spamhaus.org`.
+pub fn query_name(ip: IpAddr, zone: &str) -> String {
+    let labels: Vec<String> = match ip {
+        IpAddr::V4(v4) => v4.octets().iter().rev().map(u8::to_string).collect(),
+        IpAddr::V6(v6) => v6.octets().iter().rev().flat_map(|byte| [byte & 0x0f, byte >> 4]).map(|nibble| format!("{:x}", nibble)).collect(),
+    };
+    format!("{}.{}.", labels.join("."), zone.trim_end_matches('.'))
+}
+
+/// What a return code means on the list at `zone`.
+pub fn reason(zone: &str, code: Ipv4Addr) -> String {
+    let last = code.octets()[3];
+    let meaning = match (zone.to_ascii_lowercase().as_str(), is_error_code(code), last) {
+        (_, true, 252) => "error: typing error in the query",
+        (_, true, 254) => "error: queried through a public resolver, which the list refuses",
+        (_, true, 255) => "error: too many queries from this resolver",
+        (_, true, _) => "error code",
+        ("zen.spamhaus.org", _, 2) => "SBL: Spamhaus spam source",
+        ("zen.spamhaus.org", _, 3) => "SBL CSS: snowshoe spam",
+        ("zen.spamhaus.org", _, 4..=7) => "XBL: exploited or infected host",
+        ("zen.spamhaus.org", _, 9) => "SBL DROP: hijacked or criminal network",
+        ("zen.spamhaus.org", _, 10) => "PBL: dynamic or end-user range (ISP maintained)",
+        ("zen.spamhaus.org", _, 11) => "PBL: end-user range (Spamhaus maintained)",
+        ("bl.spamcop.net", _, 2) => "reported as a spam source by SpamCop users",
+        ("b.barracudacentral.org", _, 2) => "poor sender reputation at Barracuda",
+        _ => "listed",
+    };
+    format!("{} ({})", meaning, code)
+}
+
+/// Whether a check only lists the IP as an end-user range, which is policy
+/// rather than bad behaviour.
+pub fn policy_only(check: &Value) -> bool {
+    check["reasons"].as_array().into_iter().flatten().all(|reason| reason.as_str().is_some_and(|reason| reason.starts_with("PBL") || reason.starts_with("error")))
+}
+
+/// The checks of a report as a table for the console.
+pub fn table(data: &Value) -> String {
+    let checks: Vec<&Value> = data["checks"].as_array().into_iter().flatten().collect();
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let rows: Vec<[String; 3]> = checks
+        .iter()
+        .map(|check| {
+            let result = match check["reasons"].as_array().filter(|reasons| !reasons.is_empty()) {
+                Some(reasons) => format!("{}: {}", text(&check["status"]), reasons.iter().map(text).collect::<Vec<_>>().join("; ")),
+                None => text(&check["status"]),
+            };
+            [text(&check["list"]), text(&check["zone"]), result]
+        })
+        .collect();
+    let width = |column: usize, heading: &str| rows.iter().map(|row| row[column].len()).chain([heading.len()]).max().unwrap_or_default();
+    let (list_width, zone_width) = (width(0, "List"), width(1, "Zone"));
+    let mut lines = vec![format!("{:list_width$}  {:zone_width$}  Result", "List", "Zone")];
+    lines.extend(rows.iter().map(|[list, zone, result]| format!("{:list_width$}  {:zone_width$}  {}", list, zone, result)));
+    lines.join("\n")
+}
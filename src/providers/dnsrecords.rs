@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use hickory_resolver::proto::rr::{RData, RecordType};
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use super::dns;
+use crate::OsintError;
+
+const RECORD_TYPES: &[RecordType] = &[RecordType::A, RecordType::AAAA, RecordType::MX, RecordType::NS, RecordType::TXT, RecordType::SOA, RecordType::CAA];
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct VerificationToken {
+    pub service: String,
+    pub token: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DnsReport {
+    pub domain: String,
+    pub records: BTreeMap<String, Vec<Value>>,
+    /// Lookups that failed, by record type; the other types are still reported.
+    pub errors: BTreeMap<String, String>,
+    pub spf: Option<String>,
+    pub verification_tokens: Vec<VerificationToken>,
+    /// A and AAAA addresses, for sources that take IPs such as Shodan.
+    pub addresses: Vec<IpAddr>,
+}
+
+pub async fn fetch_dns(domain: &str) -> Result<Value, OsintError> {
+    records_with(&dns::resolver(), domain).await
+}
+
+/// The lookups through `resolver`, so tests can point it at a mock DNS server.
+pub async fn records_with(resolver: &TokioAsyncResolver, domain: &str) -> Result<Value, OsintError> {
+    let lookups = futures::future::join_all(RECORD_TYPES.iter().map(|&record_type| lookup(resolver, domain, record_type))).await;
+
+    let mut report = DnsReport { domain: domain.to_string(), ..DnsReport::default() };
+    for (record_type, result) in RECORD_TYPES.iter().zip(lookups) {
+        match result {
+            Ok(records) => {
+                report.records.insert(record_type.to_string(), records);
+            }
+            Err(err) => {
+                debug!(%domain, %record_type, %err, "DNS lookup failed");
+                report.errors.insert(record_type.to_string(), err.to_string());
+            }
+        }
+    }
+
+    for record in report.records.get("TXT").into_iter().flatten().filter_map(Value::as_str) {
+        if record.to_ascii_lowercase().starts_with("v=spf1") {
+            report.spf = Some(record.to_string());
+        } else if let Some(token) = verification_token(record) {
+            report.verification_tokens.push(token);
+        }
+    }
+    report.addresses = ["A", "AAAA"]
+        .into_iter()
+        .flat_map(|record_type| report.records.get(record_type).into_iter().flatten())
+        .filter_map(|address| address.as_str()?.parse().ok())
+        .collect();
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Records of one type at `domain`; a type with no records yields an empty list.
+async fn lookup(resolver: &TokioAsyncResolver, domain: &str, record_type: RecordType) -> Result<Vec<Value>, OsintError> {
+    let lookup = match resolver.lookup(domain, record_type).await {
+        Ok(lookup) => lookup,
+        Err(err) if dns::is_no_records(&err) => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(lookup.iter().filter_map(format_record).collect())
+}
+
+fn format_record(data: &RData) -> Option<Value> {
+    let name = |name: &hickory_resolver::Name| name.to_utf8().trim_end_matches('.').to_string();
+    Some(match data {
+        RData::A(a) => json!(a.0.to_string()),
+        RData::AAAA(aaaa) => json!(aaaa.0.to_string()),
+        RData::MX(mx) => json!({ "preference": mx.preference(), "exchange": name(mx.exchange()) }),
+        RData::NS(ns) => json!(name(&ns.0)),
+        RData::TXT(txt) => json!(txt.txt_data().iter().map(|part| String::from_utf8_lossy(part)).collect::<String>()),
+        RData::SOA(soa) => json!({
+            "primary_ns": name(soa.mname()),
+            "responsible": name(soa.rname()),
+            "serial": soa.serial(),
+            "refresh": soa.refresh(),
+            "retry": soa.retry(),
+            "expire": soa.expire(),
+            "minimum": soa.minimum(),
+        }),
+        RData::CAA(caa) => json!({ "critical": caa.issuer_critical(), "tag": caa.tag().as_str(), "value": caa.value().to_string() }),
+        // CNAMEs followed on the way to the requested type.
+        _ => return None,
+    })
+}
+
+/// Ownership tokens such as `google-site-verification=...` or `MS=ms123`.
+pub fn verification_token(record: &str) -> Option<VerificationToken> {
+    let (service, token) = record.split_once('=')?;
+    let service = service.trim();
+    let is_token = service.to_ascii_lowercase().ends_with("-verification") || service == "MS";
+    (is_token && !token.trim().is_empty()).then(|| VerificationToken { service: service.to_string(), token: token.trim().to_string() })
+}
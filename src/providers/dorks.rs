@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::info;
+use url::form_urlencoded;
+
+use crate::OsintError;
+
+const BUILTIN_TEMPLATES: &str = include_str!("dorks.txt");
+/// Category of templates in a custom file that come before any `[category]`.
+const CUSTOM_CATEGORY: &str = "custom";
+const SEARCH_ENGINES: &[(&str, &str)] = &[("google", "https://www.google.com/search?q="), ("bing", "https://www.bing.com/search?q="), ("duckduckgo", "https://duckduckgo.com/?q=")];
+
+/// One dork template and the category it is listed under.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub category: String,
+    pub template: String,
+}
+
+/// The built-in templates followed by those of `extra`, in the same format.
+pub fn load_templates(extra: Option<&Path>) -> Result<Vec<Template>, OsintError> {
+    let mut templates = parse_templates(BUILTIN_TEMPLATES, "the built-in dorks", "misc")?;
+    if let Some(path) = extra {
+        let text = fs::read_to_string(path)?;
+        templates.extend(parse_templates(&text, &path.display().to_string(), CUSTOM_CATEGORY)?);
+    }
+    Ok(templates)
+}
+
+/// Templates from `text`: `[category]` lines start a category and each
+/// other line is a template with `{domain}` in it. Blank lines and `#`
+/// comments are skipped; templates before any category go under `default`.
+pub fn parse_templates(text: &str, origin: &str, default: &str) -> Result<Vec<Template>, OsintError> {
+    let mut category = default.to_string();
+    let mut templates = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            category = name.trim().to_string();
+            continue;
+        }
+        if !line.contains("{domain}") {
+            return Err(OsintError::Config(format!("{} line {}: dork template {:?} has no {{domain}}", origin, number + 1, line)));
+        }
+        templates.push(Template { category: category.clone(), template: line.to_string() });
+    }
+    Ok(templates)
+}
+
+/// The templates filled in for `domain`, grouped by category in the order
+/// they first appear, with search links for each dork when `links` is set.
+/// Nothing is sent anywhere.
+pub fn generate(domain: &str, templates: &[Template], links: bool) -> Value {
+    let mut categories: Vec<(String, Vec<Value>)> = Vec::new();
+    for template in templates {
+        let query = template.template.replace("{domain}", domain);
+        let mut dork = json!({ "query": query });
+        if links {
+            let encoded: String = form_urlencoded::byte_serialize(query.as_bytes()).collect();
+            dork["links"] = SEARCH_ENGINES.iter().map(|(engine, base)| (engine.to_string(), json!(format!("{}{}", base, encoded)))).collect();
+        }
+        match categories.iter_mut().find(|(name, _)| *name == template.category) {
+            Some((_, dorks)) => dorks.push(dork),
+            None => categories.push((template.category.clone(), vec![dork])),
+        }
+    }
+    info!(domain, dorks = templates.len(), categories = categories.len(), "dorks generated");
+    json!({
+        "domain": domain,
+        "count": templates.len(),
+        "categories": categories.into_iter().map(|(category, dorks)| json!({ "category": category, "dorks": dorks })).collect::<Vec<_>>(),
+    })
+}
+
+/// The dorks one per line under their category headings, for the console.
+pub fn listing(data: &Value) -> String {
+    let mut lines = Vec::new();
+    for category in data["categories"].as_array().into_iter().flatten() {
+        lines.push(format!("[{}]", category["category"].as_str().unwrap_or_default()));
+        for dork in category["dorks"].as_array().into_iter().flatten() {
+            lines.push(format!("  {}", dork["query"].as_str().unwrap_or_default()));
+            if let Some(google) = dork["links"]["google"].as_str() {
+                lines.push(format!("    {}", google));
+            }
+        }
+    }
+    lines.join("\n")
+}
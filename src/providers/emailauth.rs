@@ -0,0 +1,315 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::future::Future;
+
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::dns;
+use crate::OsintError;
+
+/// Selectors common mail providers use, probed alongside `--dkim-selector`.
+pub const DEFAULT_DKIM_SELECTORS: &[&str] = &["default", "google", "selector1", "selector2"];
+/// RFC 7208 caps an SPF evaluation at ten DNS-querying terms.
+const SPF_LOOKUP_LIMIT: usize = 10;
+/// SPF mechanisms and modifiers that cost a DNS lookup.
+const SPF_LOOKUP_TERMS: &[&str] = &["include", "a", "mx", "ptr", "exists", "redirect"];
+const SPF_MECHANISMS: &[&str] = &["all", "include", "a", "mx", "ptr", "ip4", "ip6", "exists"];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MechanismCheck {
+    pub status: CheckStatus,
+    pub record: Option<String>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DkimCheck {
+    pub selector: String,
+    #[serde(flatten)]
+    pub check: MechanismCheck,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SpfCheck {
+    #[serde(flatten)]
+    pub check: MechanismCheck,
+    /// DNS lookups the record needs with every include followed.
+    pub dns_lookups: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DmarcPolicy {
+    pub policy: Option<String>,
+    pub subdomain_policy: Option<String>,
+    pub pct: Option<u8>,
+    pub rua: Vec<String>,
+    pub ruf: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DmarcCheck {
+    #[serde(flatten)]
+    pub check: MechanismCheck,
+    #[serde(flatten)]
+    pub policy: DmarcPolicy,
+}
+
+/// The overall verdict on whether mail from the domain can be spoofed.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Grade {
+    Protected,
+    PartiallyProtected,
+    Spoofable,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Assessment {
+    pub grade: Grade,
+    /// e.g. "DMARC p=none — spoofable".
+    pub summary: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EmailAuthReport {
+    pub domain: String,
+    pub spf: SpfCheck,
+    pub dmarc: DmarcCheck,
+    pub dkim: Vec<DkimCheck>,
+    pub assessment: Assessment,
+}
+
+impl MechanismCheck {
+    fn new(status: CheckStatus, record: Option<&str>, note: impl Into<String>) -> Self {
+        MechanismCheck { status, record: record.map(str::to_string), notes: vec![note.into()] }
+    }
+}
+
+pub async fn fetch_emailauth(domain: &str, dkim_selectors: &[String]) -> Result<Value, OsintError> {
+    emailauth_with(&dns::resolver(), domain, dkim_selectors).await
+}
+
+/// The checks through `resolver`, so tests can point it at a mock DNS server.
+pub async fn emailauth_with(resolver: &TokioAsyncResolver, domain: &str, dkim_selectors: &[String]) -> Result<Value, OsintError> {
+    let spf = dns::txt_records(resolver, domain).await?;
+    let dmarc = dns::txt_records(resolver, &format!("_dmarc.{}", domain)).await?;
+
+    // Guessed selectors are only reported when they exist; a selector the
+    // user named is reported either way.
+    let mut selectors: Vec<(&str, bool)> = dkim_selectors.iter().map(|selector| (selector.as_str(), true)).collect();
+    selectors.extend(DEFAULT_DKIM_SELECTORS.iter().filter(|default| !dkim_selectors.iter().any(|named| named == *default)).map(|default| (*default, false)));
+    let mut dkim = Vec::with_capacity(selectors.len());
+    for (selector, named) in selectors {
+        // A guessed selector failing to resolve is as good as absent, and
+        // must not cost the SPF and DMARC results.
+        let check = match dns::txt_records(resolver, &format!("{}._domainkey.{}", selector, domain)).await {
+            Ok(records) => evaluate_dkim(&records),
+            Err(err) if named => MechanismCheck::new(CheckStatus::Fail, None, format!("selector lookup failed: {}", err)),
+            Err(err) => {
+                warn!(%domain, selector, %err, "DKIM selector lookup failed, treating it as absent");
+                continue;
+            }
+        };
+        if named || check.record.is_some() {
+            dkim.push(DkimCheck { selector: selector.to_string(), check });
+        }
+    }
+
+    let spf = check_spf(&spf, |name| async move { dns::txt_records(resolver, &name).await.unwrap_or_default() }).await;
+    let dmarc = evaluate_dmarc(&dmarc);
+    let assessment = assess(&spf.check, &dmarc);
+
+    let report = EmailAuthReport { domain: domain.to_string(), spf, dmarc, dkim, assessment };
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Grades the apex TXT records as SPF and counts the DNS lookups the record
+/// takes against the limit, reading included records with `lookup`, which
+/// gives the TXT records at a name.
+pub async fn check_spf<F, Fut>(txt: &[String], lookup: F) -> SpfCheck
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Vec<String>>,
+{
+    let mut spf = SpfCheck { check: evaluate_spf(txt), dns_lookups: None };
+    if let Some(record) = spf.check.record.clone().filter(|_| spf.check.status != CheckStatus::Fail) {
+        let (lookups, broken) = count_spf_lookups(&record, lookup).await;
+        spf.dns_lookups = Some(lookups);
+        if lookups > SPF_LOOKUP_LIMIT {
+            spf.check.status = CheckStatus::Fail;
+            spf.check.notes.insert(0, format!("{} DNS lookups exceed the limit of {} (permerror)", lookups, SPF_LOOKUP_LIMIT));
+        }
+        for domain in broken {
+            spf.check.status = CheckStatus::Fail;
+            spf.check.notes.insert(0, format!("include:{} has no SPF record (permerror)", domain));
+        }
+    }
+    spf
+}
+
+/// Counts the DNS lookups evaluating `record` takes, following `include:`
+/// and `redirect=` into the records they name. Also returns the included
+/// domains that publish no SPF record.
+async fn count_spf_lookups<F, Fut>(record: &str, mut lookup: F) -> (usize, Vec<String>)
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Vec<String>>,
+{
+    let mut lookups = 0;
+    let mut broken = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut pending: VecDeque<String> = spf_includes(record).into();
+    lookups += spf_lookup_terms(record);
+    // Past the limit the evaluation has already failed, so stop querying.
+    while let Some(domain) = pending.pop_front().filter(|_| lookups <= SPF_LOOKUP_LIMIT) {
+        if !seen.insert(domain.to_ascii_lowercase()) {
+            continue;
+        }
+        let records = lookup(domain.clone()).await;
+        match records.iter().find(|record| has_version(record, "v=spf1")) {
+            Some(included) => {
+                debug!(%domain, lookups = spf_lookup_terms(included), "followed SPF include");
+                lookups += spf_lookup_terms(included);
+                pending.extend(spf_includes(included));
+            }
+            None => broken.push(domain),
+        }
+    }
+    (lookups, broken)
+}
+
+/// The terms of an SPF record without its version, qualifiers stripped and
+/// split into name and argument: `~include:_spf.example.com` gives
+/// (`include`, `_spf.example.com`).
+fn spf_terms(record: &str) -> impl Iterator<Item = (String, &str)> {
+    record.split_whitespace().skip(1).map(|term| {
+        let term = term.trim_start_matches(['+', '-', '~', '?']);
+        let split = term.find([':', '=', '/']).unwrap_or(term.len());
+        (term[..split].to_ascii_lowercase(), term[split..].trim_start_matches([':', '=']))
+    })
+}
+
+fn spf_lookup_terms(record: &str) -> usize {
+    spf_terms(record).filter(|(name, _)| SPF_LOOKUP_TERMS.contains(&name.as_str())).count()
+}
+
+fn spf_includes(record: &str) -> Vec<String> {
+    spf_terms(record).filter(|(name, argument)| matches!(name.as_str(), "include" | "redirect") && !argument.is_empty()).map(|(_, argument)| argument.to_string()).collect()
+}
+
+/// Grades spoofability: DMARC has to enforce a policy on all mail for the
+/// domain to count as protected, and SPF has to hold up for DMARC to act on.
+pub fn assess(spf: &MechanismCheck, dmarc: &DmarcCheck) -> Assessment {
+    let policy = dmarc.policy.policy.as_deref().unwrap_or_default();
+    let enforcing = matches!(policy, "reject" | "quarantine");
+    let (grade, summary) = if dmarc.check.record.is_none() {
+        (Grade::Spoofable, "no DMARC record — spoofable".to_string())
+    } else if !enforcing {
+        (Grade::Spoofable, format!("DMARC p={} — spoofable", if policy.is_empty() { "missing" } else { policy }))
+    } else if spf.status == CheckStatus::Fail {
+        (Grade::PartiallyProtected, format!("DMARC p={} but SPF is broken — DKIM-signed mail only", policy))
+    } else if dmarc.policy.pct.is_some_and(|pct| pct < 100) {
+        (Grade::PartiallyProtected, format!("DMARC p={} applies to only {}% of mail", policy, dmarc.policy.pct.unwrap_or_default()))
+    } else {
+        (Grade::Protected, format!("DMARC p={} — protected", policy))
+    };
+    Assessment { grade, summary }
+}
+
+/// Grades the apex TXT records as an SPF policy. `+all` authorises every
+/// sender and is treated as a failure; neutral or softfail endings warn.
+pub fn evaluate_spf(txt: &[String]) -> MechanismCheck {
+    let records: Vec<&str> = txt.iter().map(String::as_str).filter(|r| has_version(r, "v=spf1")).collect();
+    let record = match records.as_slice() {
+        [] => return MechanismCheck::new(CheckStatus::Fail, None, "no SPF record published"),
+        [record] => *record,
+        [first, ..] => return MechanismCheck::new(CheckStatus::Fail, Some(first), "multiple SPF records published (permerror)"),
+    };
+    // Unknown `name=value` modifiers are allowed; unknown mechanisms are not.
+    let malformed = record.split_whitespace().skip(1).zip(spf_terms(record)).find(|(term, (name, _))| {
+        let modifier = term.contains('=') && !term.contains(':');
+        !modifier && !SPF_MECHANISMS.contains(&name.as_str())
+    });
+    if let Some((term, _)) = malformed {
+        return MechanismCheck::new(CheckStatus::Fail, Some(record), format!("unknown mechanism `{}` (permerror)", term));
+    }
+
+    let all = record.split_whitespace().find(|term| term.trim_start_matches(['+', '-', '~', '?']).eq_ignore_ascii_case("all"));
+    match all {
+        Some("-all") => MechanismCheck::new(CheckStatus::Pass, Some(record), "hard fail for unlisted senders"),
+        Some("~all") => MechanismCheck::new(CheckStatus::Warn, Some(record), "softfail only (~all)"),
+        Some("?all") => MechanismCheck::new(CheckStatus::Warn, Some(record), "neutral policy (?all) does not reject spoofed mail"),
+        Some(_) => MechanismCheck::new(CheckStatus::Fail, Some(record), "+all authorises any sender"),
+        None => MechanismCheck::new(CheckStatus::Warn, Some(record), "no all mechanism; unlisted senders default to neutral"),
+    }
+}
+
+/// Grades the `_dmarc` TXT record by its `p=` policy and reads its reporting
+/// addresses and `pct`.
+pub fn evaluate_dmarc(txt: &[String]) -> DmarcCheck {
+    let Some(record) = txt.iter().map(String::as_str).find(|r| has_version(r, "v=DMARC1")) else {
+        return DmarcCheck { check: MechanismCheck::new(CheckStatus::Fail, None, "no DMARC record published"), policy: DmarcPolicy::default() };
+    };
+
+    let addresses = |tag: &str| tag_value(record, tag).into_iter().flat_map(|value| value.split(',')).map(|uri| uri.trim().trim_start_matches("mailto:").to_string()).filter(|uri| !uri.is_empty()).collect();
+    let policy = DmarcPolicy {
+        policy: tag_value(record, "p").map(str::to_ascii_lowercase),
+        subdomain_policy: tag_value(record, "sp").map(str::to_ascii_lowercase),
+        pct: tag_value(record, "pct").and_then(|pct| pct.parse().ok()),
+        rua: addresses("rua"),
+        ruf: addresses("ruf"),
+    };
+    let mut check = match policy.policy.as_deref() {
+        Some("reject") => MechanismCheck::new(CheckStatus::Pass, Some(record), "policy rejects failing mail"),
+        Some("quarantine") => MechanismCheck::new(CheckStatus::Pass, Some(record), "policy quarantines failing mail"),
+        Some("none") => MechanismCheck::new(CheckStatus::Warn, Some(record), "monitoring only (p=none)"),
+        Some(other) => MechanismCheck::new(CheckStatus::Fail, Some(record), format!("unknown policy p={}", other)),
+        None => MechanismCheck::new(CheckStatus::Fail, Some(record), "record has no p= policy tag"),
+    };
+    if let Some(pct) = policy.pct.filter(|pct| *pct < 100) {
+        check.notes.push(format!("policy applies to {}% of failing mail (pct={})", pct, pct));
+        if check.status == CheckStatus::Pass {
+            check.status = CheckStatus::Warn;
+        }
+    }
+    if policy.rua.is_empty() {
+        check.notes.push("no aggregate report address (rua=)".to_string());
+    }
+    DmarcCheck { check, policy }
+}
+
+/// Grades a DKIM selector record; an empty `p=` means the key was revoked.
+pub fn evaluate_dkim(txt: &[String]) -> MechanismCheck {
+    let Some(record) = txt.iter().map(String::as_str).find(|r| tag_value(r, "p").is_some()) else {
+        return MechanismCheck::new(CheckStatus::Fail, None, "no DKIM key published for selector");
+    };
+
+    match tag_value(record, "p") {
+        Some("") => MechanismCheck::new(CheckStatus::Warn, Some(record), "key revoked (empty p=)"),
+        _ => MechanismCheck::new(CheckStatus::Pass, Some(record), "public key published"),
+    }
+}
+
+fn has_version(record: &str, version: &str) -> bool {
+    record
+        .split([' ', ';'])
+        .next()
+        .is_some_and(|first| first.trim().eq_ignore_ascii_case(version))
+}
+
+fn tag_value<'a>(record: &'a str, tag: &str) -> Option<&'a str> {
+    record.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.trim().eq_ignore_ascii_case(tag).then(|| value.trim())
+    })
+}
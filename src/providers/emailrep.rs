@@ -0,0 +1,60 @@
+use std::env;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::{OsintError, RetryPolicy};
+
+const EMAILREP_URL: &str = "https://emailrep.io";
+
+pub async fn fetch_emailrep(email: &str) -> Result<Value, OsintError> {
+    let key = env::var("EMAILREP_API_KEY").ok().filter(|key| !key.is_empty());
+    lookup_from(EMAILREP_URL, key.as_deref(), email).await
+}
+
+/// The lookup against `base_url`, so tests can point it at a mock server.
+pub async fn lookup_from(base_url: &str, key: Option<&str>, email: &str) -> Result<Value, OsintError> {
+    let url = format!("{}/{}", base_url, email);
+
+    let client = crate::client_builder().build()?;
+    let mut request = client.get(&url);
+    if let Some(key) = key {
+        request = request.header("Key", key);
+    }
+    // Keyless limits are low and slow to reset, so only keyed lookups retry.
+    let policy = if key.is_some() { RetryPolicy::standard() } else { RetryPolicy::SINGLE_ATTEMPT };
+    let response = crate::send_with_policy(&client, request.build()?, policy).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::TOO_MANY_REQUESTS if key.is_none() => {
+            return Err(OsintError::QuotaExhausted(
+                "EmailRep's keyless rate limit was hit; set EMAILREP_API_KEY (free from https://emailrep.io/key) for higher limits".to_string(),
+            ));
+        }
+        StatusCode::TOO_MANY_REQUESTS => return Err(OsintError::QuotaExhausted("EmailRep rate limit for EMAILREP_API_KEY was hit".to_string())),
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials("EMAILREP_API_KEY".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+
+    let raw: Value = response.json().await?;
+    let details = &raw["details"];
+    Ok(json!({
+        "email": email,
+        "reputation": raw["reputation"],
+        "suspicious": raw["suspicious"],
+        "references": raw["references"],
+        "profiles": details["profiles"],
+        "details": {
+            "free_provider": details["free_provider"],
+            "disposable": details["disposable"],
+            "deliverable": details["deliverable"],
+            "blacklisted": details["blacklisted"],
+            "malicious_activity": details["malicious_activity"],
+            "credentials_leaked": details["credentials_leaked"],
+            "data_breach": details["data_breach"],
+            "first_seen": details["first_seen"],
+            "last_seen": details["last_seen"],
+            "spoofable": details["spoofable"],
+        },
+    }))
+}
@@ -0,0 +1,165 @@
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use super::dns;
+use crate::OsintError;
+
+/// Name announced in EHLO; mail servers only log it.
+const EHLO_NAME: &str = "localhost";
+/// Covers connecting, the banner and every command of one probe session.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Deliverability {
+    Deliverable,
+    Unknown,
+    Undeliverable,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MailExchanger {
+    pub preference: u16,
+    pub exchange: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SmtpProbe {
+    pub server: String,
+    pub result: Deliverability,
+    /// The server's reply to RCPT TO, e.g. "550 5.1.1 User unknown".
+    pub reply: Option<String>,
+    /// The server also accepted a random address, so acceptance means little.
+    pub catch_all: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EmailVerifyReport {
+    pub email: String,
+    pub domain: String,
+    pub has_mx: bool,
+    pub mx: Vec<MailExchanger>,
+    /// `deliverable` needs `--smtp-probe`; DNS alone can only rule mail out.
+    pub deliverability: Deliverability,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probe: Option<SmtpProbe>,
+}
+
+pub async fn fetch_emailverify(email: &str, smtp_probe: bool) -> Result<Value, OsintError> {
+    let (_, domain) = email.rsplit_once('@').ok_or_else(|| OsintError::InvalidTarget(format!("{} is not an email address", email)))?;
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let resolver = dns::resolver();
+
+    let mut mx: Vec<MailExchanger> = match resolver.mx_lookup(domain.as_str()).await {
+        Ok(lookup) => lookup.iter().map(|record| MailExchanger { preference: record.preference(), exchange: record.exchange().to_utf8().trim_end_matches('.').to_string() }).collect(),
+        Err(err) if dns::is_no_records(&err) => Vec::new(),
+        Err(err) => return Err(err.into()),
+    };
+    mx.sort_by_key(|record| record.preference);
+    // RFC 7505: a lone "." exchange says the domain accepts no mail at all.
+    let null_mx = mx.len() == 1 && mx[0].exchange.is_empty();
+    let has_mx = !mx.is_empty() && !null_mx;
+
+    // Without MX records mail goes to the domain's own address (RFC 5321).
+    let servers: Vec<String> = match (has_mx, null_mx) {
+        (true, _) => mx.iter().map(|record| record.exchange.clone()).collect(),
+        (false, false) if resolves(&resolver, &domain).await => vec![domain.clone()],
+        _ => Vec::new(),
+    };
+
+    let probe = match servers.first() {
+        Some(server) if smtp_probe => Some(probe(server, email, &domain).await),
+        _ => None,
+    };
+    let deliverability = match &probe {
+        Some(probe) => probe.result,
+        None if servers.is_empty() => Deliverability::Undeliverable,
+        None => Deliverability::Unknown,
+    };
+
+    let report = EmailVerifyReport { email: email.to_string(), domain, has_mx, mx, deliverability, probe };
+    Ok(serde_json::to_value(report)?)
+}
+
+async fn resolves(resolver: &TokioAsyncResolver, host: &str) -> bool {
+    resolver.lookup_ip(host).await.is_ok_and(|lookup| lookup.iter().next().is_some())
+}
+
+/// Asks `server` whether it would take mail for `email`, then for a random
+/// address at the same domain, and quits before DATA: no message is sent.
+async fn probe(server: &str, email: &str, domain: &str) -> SmtpProbe {
+    info!(%server, "probing SMTP server with RCPT TO");
+    let decoy = format!("{}@{}", Uuid::new_v4().simple(), domain);
+    let session = async {
+        let stream = TcpStream::connect((server, 25)).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        expect(&mut reader, 2).await?;
+        command(&mut writer, &mut reader, &format!("EHLO {}", EHLO_NAME), 2).await?;
+        command(&mut writer, &mut reader, "MAIL FROM:<>", 2).await?;
+        let target = command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", email), 0).await?;
+        // A server that rejected the real address need not be asked more.
+        let decoy = match target.0 {
+            2 => Some(command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", decoy), 0).await?),
+            _ => None,
+        };
+        let _ = writer.write_all(b"QUIT\r\n").await;
+        Ok::<_, std::io::Error>((target, decoy))
+    };
+
+    match timeout(PROBE_TIMEOUT, session).await {
+        Ok(Ok(((class, reply), decoy))) => {
+            let catch_all = decoy.is_some_and(|(class, _)| class == 2);
+            let result = match class {
+                2 if catch_all => Deliverability::Unknown,
+                2 => Deliverability::Deliverable,
+                5 => Deliverability::Undeliverable,
+                // 4xx: greylisting or a temporary failure.
+                _ => Deliverability::Unknown,
+            };
+            SmtpProbe { server: server.to_string(), result, reply: Some(reply), catch_all, error: None }
+        }
+        Ok(Err(err)) => SmtpProbe { server: server.to_string(), result: Deliverability::Unknown, reply: None, catch_all: false, error: Some(err.to_string()) },
+        Err(_) => SmtpProbe { server: server.to_string(), result: Deliverability::Unknown, reply: None, catch_all: false, error: Some(format!("timed out after {:?}", PROBE_TIMEOUT)) },
+    }
+}
+
+/// Sends one command and reads the reply. `expected` is the reply class
+/// (2 for 2xx) the session needs to continue, or 0 to accept any.
+async fn command<W, R>(writer: &mut W, reader: &mut R, line: &str, expected: u8) -> std::io::Result<(u8, String)>
+where
+    W: AsyncWriteExt + Unpin,
+    R: AsyncBufReadExt + Unpin,
+{
+    debug!(command = %line, "SMTP");
+    writer.write_all(format!("{}\r\n", line).as_bytes()).await?;
+    expect(reader, expected).await
+}
+
+/// Reads a possibly multi-line reply ("250-..." lines up to "250 ...").
+async fn expect<R: AsyncBufReadExt + Unpin>(reader: &mut R, expected: u8) -> std::io::Result<(u8, String)> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "server closed the connection"));
+        }
+        let line = line.trim_end().to_string();
+        debug!(reply = %line, "SMTP");
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        let class = line.bytes().next().filter(u8::is_ascii_digit).map_or(0, |digit| digit - b'0');
+        if expected != 0 && class != expected {
+            return Err(std::io::Error::other(format!("unexpected reply: {}", line)));
+        }
+        return Ok((class, line));
+    }
+}
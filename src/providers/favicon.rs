@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+use std::env;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+use url::Url;
+
+use super::http;
+use super::shodan::{self, ShodanMatch};
+use crate::OsintError;
+
+#[derive(Serialize, Debug)]
+pub struct Icon {
+    pub url: String,
+    /// `default` for /favicon.ico, `link` for one the page references.
+    pub origin: &'static str,
+    pub found: bool,
+    pub hash: Option<i32>,
+    pub shodan_query: Option<String>,
+    /// The first page of hosts sharing the icon; only present with
+    /// `--pivot-shodan` and a Shodan key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<ShodanMatch>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FaviconReport {
+    pub page: String,
+    pub icons: Vec<Icon>,
+}
+
+pub async fn fetch_favicon(target: &str, pivot_shodan: bool) -> Result<Value, OsintError> {
+    let page = http::target_url(target)?;
+    let client = crate::client_builder().build()?;
+
+    let default = page.join("/favicon.ico").map_err(|err| OsintError::InvalidTarget(format!("{}: {}", target, err)))?;
+    let mut candidates = vec![(default, "default")];
+    // A page that cannot be fetched still leaves /favicon.ico to try.
+    match linked_icons(&client, &page).await {
+        Ok(linked) => candidates.extend(linked.into_iter().map(|url| (url, "link"))),
+        Err(err) => warn!(%page, %err, "could not read the page for icon links"),
+    }
+    let mut seen = HashSet::new();
+    candidates.retain(|(url, _)| seen.insert(url.clone()));
+
+    let shodan_key = match env::var("SHODAN_API_KEY").ok().filter(|key| !key.is_empty()) {
+        Some(key) if pivot_shodan => Some(key),
+        None if pivot_shodan => {
+            warn!("--pivot-shodan needs SHODAN_API_KEY, skipping the search");
+            None
+        }
+        _ => None,
+    };
+
+    let mut icons: Vec<Icon> = Vec::with_capacity(candidates.len());
+    for (url, origin) in candidates {
+        let mut icon = fetch_icon(&client, url, origin).await?;
+        if let (Some(key), Some(query)) = (&shodan_key, &icon.shodan_query) {
+            // Icons served at several URLs need only one search.
+            if !icons.iter().any(|seen| seen.hash == icon.hash) {
+                icon.matches = Some(shodan::search(crate::SHODAN_BASE_URL, key, query, 1, None).await?.matches);
+            }
+        }
+        icons.push(icon);
+    }
+    Ok(serde_json::to_value(FaviconReport { page: page.to_string(), icons })?)
+}
+
+async fn fetch_icon(client: &Client, url: Url, origin: &'static str) -> Result<Icon, OsintError> {
+    let response = crate::send_with_retries(client, client.get(url.clone()).build()?).await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        info!(%url, "no favicon served");
+        return Ok(Icon { url: url.to_string(), origin, found: false, hash: None, shodan_query: None, matches: None });
+    }
+    // Still limited once send_with_retries has given up.
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(OsintError::RetriesExhausted(url.to_string()));
+    }
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: url.to_string() });
+    }
+    let body = response.bytes().await.map_err(|err| err.without_url())?;
+    let hash = favicon_hash(&body);
+    Ok(Icon { url: url.to_string(), origin, found: true, hash: Some(hash), shodan_query: Some(shodan_query(hash)), matches: None })
+}
+
+/// The icons `<link rel="icon">` tags on `page` point at, resolved against
+/// the URL the page was finally served from.
+async fn linked_icons(client: &Client, page: &Url) -> Result<Vec<Url>, OsintError> {
+    let response = crate::send_with_retries(client, client.get(page.clone()).build()?).await?;
+    if !response.status().is_success() {
+        return Ok(Vec::new());
+    }
+    let base = response.url().clone();
+    let content_type = response.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let (bytes, _) = http::read_capped(response, http::MAX_BODY_BYTES).await?;
+    let body = http::decode_body(&bytes, content_type.as_deref());
+    Ok(icon_links(&body).iter().filter_map(|href| base.join(href).ok()).filter(|url| matches!(url.scheme(), "http" | "https")).collect())
+}
+
+/// `href`s of the `<link>` tags whose `rel` includes an icon, such as
+/// `icon`, `shortcut icon` or `apple-touch-icon`.
+pub fn icon_links(html: &str) -> Vec<String> {
+    let lower = html.to_ascii_lowercase();
+    lower
+        .match_indices("<link")
+        .filter_map(|(start, _)| {
+            let end = start + lower[start..].find('>')?;
+            // Without the self-closing slash, which would end an unquoted value.
+            let tag = html[start..end].trim_end_matches('/');
+            let rel = attribute(tag, "rel")?.to_ascii_lowercase();
+            if !rel.split_whitespace().any(|token| token.contains("icon")) {
+                return None;
+            }
+            attribute(tag, "href").filter(|href| !href.is_empty()).map(str::to_string)
+        })
+        .collect()
+}
+
+/// The value of `name` in an HTML tag, quoted or not.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let pattern = format!("{}=", name);
+    let at = lower.match_indices(&pattern).map(|(at, _)| at).find(|&at| at > 0 && lower.as_bytes()[at - 1].is_ascii_whitespace())? + pattern.len();
+    let value = &tag[at..];
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split(|c: char| c.is_ascii_whitespace()).next(),
+    }
+}
+
+pub fn shodan_query(hash: i32) -> String {
+    format!("http.favicon.hash:{}", hash)
+}
+
+/// Shodan's favicon hash: MurmurHash3 of the MIME-style base64 encoding
+/// (76-char lines, each newline-terminated), read as a signed integer.
+pub fn favicon_hash(body: &[u8]) -> i32 {
+    let encoded = STANDARD.encode(body);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+    for line in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(std::str::from_utf8(line).expect("base64 output is ASCII"));
+        wrapped.push('\n');
+    }
+    murmur3_32(wrapped.as_bytes(), 0) as i32
+}
+
+/// MurmurHash3 x86 32-bit.
+pub fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mut hash = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        let mut k = u32::from_le_bytes([block[0], block[1], block[2], block[3]]);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+        hash = hash.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+
+    let tail = blocks.remainder();
+    if !tail.is_empty() {
+        let mut k = tail.iter().rev().fold(0u32, |acc, &byte| (acc << 8) | u32::from(byte));
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
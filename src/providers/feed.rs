@@ -0,0 +1,86 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use tracing::{debug, warn};
+
+use crate::OsintError;
+
+/// Where the text of a cached feed came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Downloaded,
+    /// The server answered 304 to the cached copy's ETag.
+    Revalidated,
+    Cached,
+    /// An expired copy, used because the refresh failed.
+    Stale,
+}
+
+/// A plain-text feed at `url`, cached in `cache_dir` as `name`. A copy under
+/// `max_age` is used as is; an older one is revalidated with its ETag, so an
+/// unchanged feed is not downloaded again. When the refresh fails, the
+/// expired copy is used with a warning. An empty download counts as failed.
+pub async fn fetch(url: &str, cache_dir: &Path, name: &str, max_age: Duration) -> Result<(String, Origin), OsintError> {
+    let path = cache_dir.join(name);
+    let etag_path = cache_dir.join(format!("{}.etag", name));
+    let age = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok().map(|modified| SystemTime::now().duration_since(modified).unwrap_or_default());
+    let cached = fs::read_to_string(&path).ok();
+    if let Some(text) = cached.as_ref().filter(|_| age.is_some_and(|age| age < max_age)) {
+        debug!(path = %path.display(), "using the cached feed");
+        return Ok((text.clone(), Origin::Cached));
+    }
+    let etag = cached.as_ref().and_then(|_| fs::read_to_string(&etag_path).ok());
+    match download(url, etag.as_deref()).await {
+        Ok(None) => {
+            debug!(url, "feed unchanged");
+            // Restart the copy's max age.
+            if let Err(err) = File::options().write(true).open(&path).and_then(|file| file.set_modified(SystemTime::now())) {
+                warn!(path = %path.display(), %err, "could not refresh the cached feed's age");
+            }
+            Ok((cached.unwrap_or_default(), Origin::Revalidated))
+        }
+        Ok(Some((text, etag))) => {
+            let written = fs::create_dir_all(cache_dir).and_then(|_| fs::write(&path, &text)).and_then(|_| match &etag {
+                Some(etag) => fs::write(&etag_path, etag),
+                None => fs::remove_file(&etag_path).or(Ok(())),
+            });
+            if let Err(err) = written {
+                warn!(path = %path.display(), %err, "could not cache the feed");
+            }
+            Ok((text, Origin::Downloaded))
+        }
+        Err(err) => match cached {
+            Some(text) => {
+                warn!(url, %err, age_secs = age.unwrap_or_default().as_secs(), "could not refresh the feed, using the cached copy");
+                Ok((text, Origin::Stale))
+            }
+            None => Err(err),
+        },
+    }
+}
+
+/// The feed and its ETag, or None when it matches `etag`.
+async fn download(url: &str, etag: Option<&str>) -> Result<Option<(String, Option<String>)>, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag.trim());
+    }
+    let response = crate::send_with_retries(&client, request.build()?).await?;
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED && etag.is_some() {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: url.to_string() });
+    }
+    let etag = response.headers().get(ETAG).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let text = response.text().await?;
+    if text.trim().is_empty() {
+        return Err(OsintError::ProviderError(format!("{} came back empty", url)));
+    }
+    Ok(Some((text, etag)))
+}
@@ -0,0 +1,93 @@
+use std::net::IpAddr;
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+use super::asn;
+use crate::geoip::{self, GeoLocation};
+use crate::OsintError;
+
+const IPAPI_URL: &str = "https://ipapi.co";
+/// ipapi.co's free tier allows about 1000 lookups a day, so `--enrich-geo`
+/// without a database locates at most this many IPs per report.
+const MAX_ONLINE_LOOKUPS: usize = 20;
+
+#[derive(Serialize, Debug)]
+pub struct GeoIpReport {
+    pub ip: String,
+    /// `geolite2` for the local databases, `ipapi` for the online API.
+    pub source: &'static str,
+    /// Absent for private and reserved addresses and ones nobody has placed.
+    pub location: Option<GeoLocation>,
+}
+
+/// Locates `ip` in the `--geoip-db` databases when there are any, otherwise
+/// with ipapi.co.
+pub async fn fetch_geoip(ip: &str) -> Result<Value, OsintError> {
+    let address: IpAddr = ip.parse().map_err(|_| OsintError::InvalidTarget(format!("{} is not an IP address", ip)))?;
+    let report = match geoip::installed() {
+        Some(database) => GeoIpReport { ip: ip.to_string(), source: "geolite2", location: database.lookup(address) },
+        None => GeoIpReport { ip: ip.to_string(), source: "ipapi", location: locate(address).await? },
+    };
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Asks ipapi.co about `ip`; addresses that cannot be on the internet are
+/// answered without a request.
+pub async fn locate(ip: IpAddr) -> Result<Option<GeoLocation>, OsintError> {
+    if !asn::is_routable(ip) {
+        return Ok(None);
+    }
+    let body = crate::fetch_with_retries(&format!("{}/{}/json/", IPAPI_URL, ip), HeaderMap::new()).await?;
+    parse_ipapi(&serde_json::from_str(&body)?)
+}
+
+/// Reads an ipapi.co answer. Reserved ranges come back as
+/// `{"error": true, "reserved": true}`; other errors, such as an exhausted
+/// quota, are failures.
+pub fn parse_ipapi(body: &Value) -> Result<Option<GeoLocation>, OsintError> {
+    if body["error"].as_bool() == Some(true) {
+        let reason = body["reason"].as_str().unwrap_or("unknown error");
+        return match body["reserved"].as_bool() {
+            Some(true) => Ok(None),
+            _ => Err(OsintError::ProviderError(format!("ipapi.co: {}", reason))),
+        };
+    }
+    let text = |key: &str| body[key].as_str().filter(|value| !value.is_empty()).map(str::to_string);
+    let location = GeoLocation {
+        country: text("country_code"),
+        country_name: text("country_name"),
+        region: text("region"),
+        city: text("city"),
+        latitude: body["latitude"].as_f64(),
+        longitude: body["longitude"].as_f64(),
+        asn: body["asn"].as_str().and_then(|asn| asn.trim_start_matches("AS").parse().ok()),
+        organization: text("org"),
+    };
+    Ok((!location.is_empty()).then_some(location))
+}
+
+/// `--enrich-geo` without a database: adds `geo` like the database
+/// enrichment does, locating the report's IPs one at a time with ipapi.co.
+pub async fn enrich_online(target: &str, data: Value) -> Value {
+    let ips: Vec<IpAddr> = geoip::report_ips(target, &data).iter().filter_map(|ip| ip.parse().ok()).filter(|ip| asn::is_routable(*ip)).collect();
+    if ips.len() > MAX_ONLINE_LOOKUPS {
+        warn!(found = ips.len(), limit = MAX_ONLINE_LOOKUPS, "locating only the first IPs online; pass --geoip-db to locate them all");
+    }
+    info!(count = ips.len().min(MAX_ONLINE_LOOKUPS), "locating IPs with ipapi.co");
+    let mut locations = Map::new();
+    for ip in ips.into_iter().take(MAX_ONLINE_LOOKUPS) {
+        match locate(ip).await {
+            Ok(Some(location)) => {
+                if let Ok(location) = serde_json::to_value(location) {
+                    locations.insert(ip.to_string(), location);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => warn!(%ip, %err, "geolocation failed"),
+        }
+    }
+    geoip::attach(data, locations)
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Request, Response, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::paginate::{paginate, Paginated};
+use crate::ratelimit::RateLimiter;
+use crate::{OsintError, RetryPolicy};
+
+const GITHUB_CODE_SEARCH_URL: &str = "https://api.github.com/search/code";
+const PAGE_SIZE: usize = 30;
+/// Longest `Retry-After` we are willing to sit through.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+/// Code searches GitHub allows a token each minute.
+const SEARCH_REQUESTS_PER_MINUTE: u32 = 10;
+/// Terms searched next to the target under `--github-secrets`, for files
+/// that mention it along with what looks like a credential. GitHub's code
+/// search has no regular expressions, so these are plain terms, filenames and
+/// extensions; each is one more search, reading only its first page.
+const SECRET_PATTERNS: &[&str] = &["password", "secret", "api_key", "token", "filename:.env", "extension:pem"];
+
+#[derive(Serialize, Debug)]
+pub struct CodeHit {
+    pub path: String,
+    pub html_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<String>,
+}
+
+pub async fn fetch_github(target: &str, max_results: usize, secrets: bool) -> Result<Value, OsintError> {
+    let token = crate::api_key("GITHUB_TOKEN")?;
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    let limiter = LIMITER.get_or_init(|| RateLimiter::per_minute(SEARCH_REQUESTS_PER_MINUTE));
+    search_from(GITHUB_CODE_SEARCH_URL, &token, target, max_results, secrets, Some(limiter)).await
+}
+
+/// Searches code at `url`, so tests can point it at a mock server, for the
+/// quoted target, up to `max_results` hits, and under `secrets` for each of
+/// the secret patterns next to it. Every request waits for `limiter`.
+pub async fn search_from(url: &str, token: &str, target: &str, max_results: usize, secrets: bool, limiter: Option<&RateLimiter>) -> Result<Value, OsintError> {
+    // Quoting makes GitHub match the whole target rather than its tokens.
+    let query = format!("\"{}\"", target);
+    let client = crate::client_builder().build()?;
+    let results = search(&client, url, token, &query, max_results, limiter).await?;
+    let total_count = results.first["total_count"].as_u64().unwrap_or(0);
+    let (fetched, capped) = (results.items.len(), results.capped);
+    let mut data = json!({
+        "query": query,
+        "total_count": total_count,
+        "fetched": fetched,
+        "capped": capped,
+        "repositories": by_repository(results.items),
+    });
+    if secrets {
+        let mut searches = Vec::with_capacity(SECRET_PATTERNS.len());
+        for pattern in SECRET_PATTERNS {
+            let query = format!("{} {}", query, pattern);
+            let results = search(&client, url, token, &query, PAGE_SIZE.min(max_results), limiter).await?;
+            searches.push(json!({
+                "pattern": pattern,
+                "query": query,
+                "total_count": results.first["total_count"].as_u64().unwrap_or(0),
+                "fetched": results.items.len(),
+                "repositories": by_repository(results.items),
+            }));
+        }
+        let matched = searches.iter().filter(|search| search["fetched"] != 0).count();
+        info!(target, patterns = searches.len(), matched, "GitHub secret patterns searched");
+        data["secrets"] = Value::Array(searches);
+    }
+    Ok(data)
+}
+
+/// Up to `max_results` code search hits for `query`, a page at a time.
+async fn search(client: &Client, url: &str, token: &str, query: &str, max_results: usize, limiter: Option<&RateLimiter>) -> Result<Paginated<Value, Value>, OsintError> {
+    let fetch = |page: u32| async move {
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        let request = client
+            .get(url)
+            .query(&[("q", query), ("per_page", &PAGE_SIZE.to_string()), ("page", &page.to_string())])
+            .bearer_auth(token)
+            // The text-match media type adds the matching fragments.
+            .header("Accept", "application/vnd.github.v3.text-match+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .build()?;
+        Ok(send(client, request, url).await?.json::<Value>().await?)
+    };
+    paginate(1, Some(max_results), fetch, |results: &Value, &page, before| {
+        let items = results["items"].as_array().cloned().unwrap_or_default();
+        let more = items.len() >= PAGE_SIZE && ((before + items.len()) as u64) < results["total_count"].as_u64().unwrap_or(0);
+        (items, more.then_some(page + 1))
+    })
+    .await
+}
+
+/// The hits grouped by repository, each with its file and first fragment.
+fn by_repository(items: Vec<Value>) -> Vec<Value> {
+    let mut repositories: BTreeMap<String, Vec<CodeHit>> = BTreeMap::new();
+    for item in items {
+        let repository = item["repository"]["full_name"].as_str().unwrap_or("unknown").to_string();
+        let fragment = item["text_matches"][0]["fragment"].as_str().map(str::to_string);
+        let hit = CodeHit { path: item["path"].as_str().unwrap_or_default().to_string(), html_url: item["html_url"].as_str().unwrap_or_default().to_string(), fragment };
+        repositories.entry(repository).or_default().push(hit);
+    }
+    repositories.into_iter().map(|(repository, files)| json!({ "repository": repository, "files": files })).collect()
+}
+
+/// Sends a search request, sitting out GitHub's secondary rate limits, which
+/// arrive as 403 or 429 with a `Retry-After` header.
+async fn send(client: &Client, request: Request, url: &str) -> Result<Response, OsintError> {
+    let policy = RetryPolicy::standard();
+    for attempt in 1..=policy.attempts {
+        let attempt_request = request.try_clone().expect("search requests have no streaming body");
+        let response = crate::send_with_policy(client, attempt_request, RetryPolicy::SINGLE_ATTEMPT).await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(OsintError::InvalidCredentials("GITHUB_TOKEN".to_string()));
+        }
+        if status == StatusCode::UNPROCESSABLE_ENTITY {
+            return Err(OsintError::ProviderError("GitHub rejected the search query".to_string()));
+        }
+        if !matches!(status, StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS) {
+            return Err(OsintError::ApiStatus { status, url: url.to_string() });
+        }
+        match retry_after(response.headers()) {
+            Some(delay) if delay <= MAX_RETRY_AFTER && attempt < policy.attempts => {
+                warn!(attempt, delay_secs = delay.as_secs(), "GitHub secondary rate limit, waiting");
+                sleep(delay).await;
+            }
+            _ if response.headers().get("x-ratelimit-remaining").is_some_and(|remaining| remaining == "0") => {
+                return Err(OsintError::QuotaExhausted("GitHub code search rate limit reached for GITHUB_TOKEN".to_string()));
+            }
+            Some(_) => return Err(OsintError::RetriesExhausted(url.to_string())),
+            None => return Err(OsintError::ApiStatus { status, url: url.to_string() }),
+        }
+    }
+    Err(OsintError::RetriesExhausted(url.to_string()))
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
@@ -0,0 +1,75 @@
+use md5::{Digest, Md5};
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::OsintError;
+
+const AVATAR_BASE_URL: &str = "https://www.gravatar.com";
+const PROFILE_BASE_URL: &str = "https://gravatar.com";
+
+pub async fn fetch_gravatar(email: &str) -> Result<Value, OsintError> {
+    lookup(AVATAR_BASE_URL, PROFILE_BASE_URL, email).await
+}
+
+/// Gravatar identifies an address by the MD5 of its trimmed, lowercased form.
+pub fn email_hash(email: &str) -> String {
+    Md5::digest(email.trim().to_lowercase().as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Whether `email` has a Gravatar and, when it does, its public profile.
+/// The bases are parameters so tests can point both at a mock server. An
+/// address without a Gravatar is a normal result with `exists: false`.
+pub async fn lookup(avatar_base: &str, profile_base: &str, email: &str) -> Result<Value, OsintError> {
+    let hash = email_hash(email);
+    let client = crate::client_builder().build()?;
+    let exists = get(&client, &format!("{}/avatar/{}?d=404", avatar_base, hash)).await?.is_some();
+    // A profile can be private even when the avatar is public.
+    let mut profile = match exists {
+        true => get(&client, &format!("{}/{}.json", profile_base, hash)).await?.map(|response| parse_profile(&response)),
+        false => None,
+    };
+    info!(exists, profile = profile.is_some(), "gravatar lookup done");
+    // Linked accounts sit at the top level, next to the other pivots.
+    let accounts = profile.as_mut().and_then(Value::as_object_mut).and_then(|profile| profile.remove("accounts")).unwrap_or_else(|| Value::Array(Vec::new()));
+    Ok(json!({
+        "email": email,
+        "hash": hash,
+        "exists": exists,
+        "avatar_url": exists.then(|| format!("{}/avatar/{}", avatar_base, hash)),
+        "profile": profile,
+        "accounts": accounts,
+    }))
+}
+
+/// The body of a successful GET, or None on a 404.
+async fn get(client: &Client, url: &str) -> Result<Option<String>, OsintError> {
+    let response = crate::send_with_retries(client, client.get(url).build()?).await?;
+    match response.status() {
+        status if status.is_success() => Ok(Some(response.text().await?)),
+        StatusCode::NOT_FOUND => Ok(None),
+        status => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+}
+
+/// The public fields of a `{hash}.json` profile, whose one `entry` holds the
+/// display name, location and the accounts the owner linked.
+fn parse_profile(body: &str) -> Value {
+    let body: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+    let entry = &body["entry"][0];
+    let accounts: Vec<Value> = entry["accounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|account| json!({ "service": account["shortname"].as_str().or(account["domain"].as_str()), "username": account["username"].as_str().or(account["display"].as_str()), "url": account["url"], "verified": account["verified"] == true || account["verified"].as_str().is_some_and(|verified| verified.eq_ignore_ascii_case("true")) }))
+        .collect();
+    json!({
+        "display_name": entry["displayName"],
+        "preferred_username": entry["preferredUsername"],
+        "location": entry["currentLocation"],
+        "about": entry["aboutMe"],
+        "profile_url": entry["profileUrl"],
+        "urls": entry["urls"].as_array().into_iter().flatten().map(|link| link["value"].clone()).collect::<Vec<_>>(),
+        "accounts": accounts,
+    })
+}
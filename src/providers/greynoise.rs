@@ -0,0 +1,112 @@
+use std::env;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::OsintError;
+
+const COMMUNITY_URL: &str = "https://api.greynoise.io/v3/community";
+const CONTEXT_URL: &str = "https://api.greynoise.io/v2/noise/context";
+
+/// Which GreyNoise API a lookup goes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Free, unauthenticated and coarse.
+    Community,
+    /// Needs `GREYNOISE_API_KEY`; adds actor and tag detail.
+    Context,
+}
+
+impl Mode {
+    pub fn select(key: Option<&str>) -> Mode {
+        match key {
+            Some(key) if !key.is_empty() => Mode::Context,
+            _ => Mode::Community,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Community => "community",
+            Mode::Context => "context",
+        }
+    }
+}
+
+pub async fn fetch_greynoise(ip: &str) -> Result<Value, OsintError> {
+    let key = env::var("GREYNOISE_API_KEY").ok().filter(|key| !key.is_empty());
+    lookup_from(COMMUNITY_URL, CONTEXT_URL, key.as_deref(), ip).await
+}
+
+/// The lookup against `community_url` or, with a key, `context_url`, so
+/// tests can point both at a mock server.
+pub async fn lookup_from(community_url: &str, context_url: &str, key: Option<&str>, ip: &str) -> Result<Value, OsintError> {
+    let mode = Mode::select(key);
+    let url = match mode {
+        Mode::Community => format!("{}/{}", community_url, ip),
+        Mode::Context => format!("{}/{}", context_url, ip),
+    };
+
+    let client = crate::client_builder().build()?;
+    let mut request = client.get(&url).header("Accept", "application/json");
+    if let (Mode::Context, Some(key)) = (mode, key) {
+        request = request.header("key", key);
+    }
+    let response = crate::send_with_retries(&client, request.build()?).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        // The community API answers 404 for addresses it has never seen.
+        StatusCode::NOT_FOUND => {
+            info!(%ip, "IP not observed by GreyNoise");
+            return Ok(not_observed(ip, mode));
+        }
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials("GREYNOISE_API_KEY".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+
+    let raw: Value = response.json().await?;
+    Ok(normalize(ip, mode, raw))
+}
+
+fn not_observed(ip: &str, mode: Mode) -> Value {
+    json!({ "ip": ip, "mode": mode.name(), "observed": false, "classification": "unknown", "actor": null, "tags": [], "last_seen": null })
+}
+
+fn normalize(ip: &str, mode: Mode, raw: Value) -> Value {
+    match mode {
+        Mode::Community => {
+            if raw["noise"] != true && raw["riot"] != true {
+                return not_observed(ip, mode);
+            }
+            json!({
+                "ip": ip,
+                "mode": mode.name(),
+                "observed": true,
+                "classification": raw["classification"],
+                "actor": raw["name"],
+                "tags": [],
+                "last_seen": raw["last_seen"],
+                "riot": raw["riot"],
+                "link": raw["link"],
+            })
+        }
+        Mode::Context => {
+            // The context API answers 200 with `seen: false` for unknown IPs.
+            if raw["seen"] != true {
+                return not_observed(ip, mode);
+            }
+            json!({
+                "ip": ip,
+                "mode": mode.name(),
+                "observed": true,
+                "classification": raw["classification"],
+                "actor": raw["actor"],
+                "tags": raw["tags"],
+                "first_seen": raw["first_seen"],
+                "last_seen": raw["last_seen"],
+                "raw": raw,
+            })
+        }
+    }
+}
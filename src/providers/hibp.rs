@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+use crate::OsintError;
+
+/// Data classes whose exposure makes a breach serious: credentials and
+/// payment or identity data, as opposed to contact details.
+const SENSITIVE_DATA_CLASSES: &[&str] = &["Passwords", "Password hints", "Auth tokens", "Security questions and answers", "Credit cards", "Credit card CVV", "Partial credit card data", "Bank account numbers", "Social security numbers", "Government issued IDs", "Passport numbers", "PINs"];
+
+/// Breach details by name from HIBP's public `/breach/{name}` endpoint.
+/// Breaches never change once published, so each is fetched once: per run
+/// in memory, and across runs from a file per breach in `cache_dir`.
+pub struct BreachCatalog {
+    base_url: String,
+    cache_dir: PathBuf,
+    entries: Mutex<HashMap<String, Arc<OnceCell<Option<Value>>>>>,
+}
+
+impl BreachCatalog {
+    pub fn new(base_url: &str, cache_dir: PathBuf) -> Self {
+        BreachCatalog { base_url: base_url.to_string(), cache_dir, entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// Merges the catalog details (`BreachDate`, `PwnCount`, `Description`,
+    /// `DataClasses`, ...) into each of an account's breaches. Breaches the
+    /// catalog cannot resolve keep just their name.
+    pub async fn expand(&self, breaches: Value) -> Value {
+        let Value::Array(breaches) = breaches else {
+            return breaches;
+        };
+        let mut expanded = Vec::with_capacity(breaches.len());
+        for mut breach in breaches {
+            if let (Some(name), Value::Object(fields)) = (breach["Name"].as_str().map(str::to_string), &mut breach) {
+                if let Some(Value::Object(details)) = self.lookup(&name).await {
+                    fields.extend(details);
+                }
+            }
+            expanded.push(breach);
+        }
+        Value::Array(expanded)
+    }
+
+    /// The breach called `name`, or None when HIBP does not know it or could
+    /// not be reached; either way it is not asked again this run.
+    pub async fn lookup(&self, name: &str) -> Option<Value> {
+        let entry = self.entries.lock().unwrap().entry(name.to_string()).or_default().clone();
+        entry.get_or_init(|| self.load(name)).await.clone()
+    }
+
+    async fn load(&self, name: &str) -> Option<Value> {
+        let path = self.cache_dir.join(format!("{}.json", name.replace(['/', '\\', '.'], "_")));
+        if let Some(cached) = fs::read_to_string(&path).ok().and_then(|text| serde_json::from_str(&text).ok()) {
+            debug!(name, "using the cached breach");
+            return Some(cached);
+        }
+        let url = format!("{}/breach/{}", self.base_url, name);
+        let breach: Value = match crate::fetch_with_retries(&url, HeaderMap::new()).await.and_then(|body| Ok(serde_json::from_str(&body)?)) {
+            Ok(breach) => breach,
+            Err(err) => {
+                warn!(name, %err, "could not look up the breach");
+                return None;
+            }
+        };
+        if let Err(err) = cache(&path, &breach) {
+            warn!(path = %path.display(), %err, "could not cache the breach");
+        }
+        Some(breach)
+    }
+}
+
+fn cache(path: &Path, breach: &Value) -> Result<(), OsintError> {
+    fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+    fs::write(path, breach.to_string())?;
+    Ok(())
+}
+
+/// Where breach details are cached between runs.
+pub fn cache_dir() -> PathBuf {
+    env::temp_dir().join("osint-recon-tool-hibp-breaches")
+}
+
+/// Whether a breach leaked credentials, payment or identity data. A breach
+/// without known data classes is assumed to have.
+pub fn exposes_secrets(breach: &Value) -> bool {
+    match breach["DataClasses"].as_array() {
+        Some(classes) => classes.iter().filter_map(Value::as_str).any(|class| SENSITIVE_DATA_CLASSES.contains(&class)),
+        None => true,
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::net::Ipv6Addr;
+use std::time::Duration;
+
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::header::{HeaderMap, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::Response;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::OsintError;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Bodies are read up to this size for the title and body signatures.
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+const SECURITY_HEADERS: &[&str] = &[
+    "strict-transport-security",
+    "content-security-policy",
+    "x-frame-options",
+    "x-content-type-options",
+    "referrer-policy",
+    "permissions-policy",
+];
+
+/// Where a signature looks for its marker.
+enum Marker {
+    /// Header name and a lowercase substring of its value. An empty
+    /// substring matches on the header's presence alone.
+    Header(&'static str, &'static str),
+    /// Case-sensitive substring of the response body.
+    Body(&'static str),
+}
+
+struct Signature {
+    technology: &'static str,
+    marker: Marker,
+}
+
+const SIGNATURES: &[Signature] = &[
+    Signature { technology: "nginx", marker: Marker::Header("server", "nginx") },
+    Signature { technology: "Apache", marker: Marker::Header("server", "apache") },
+    Signature { technology: "Microsoft IIS", marker: Marker::Header("server", "microsoft-iis") },
+    Signature { technology: "Cloudflare", marker: Marker::Header("cf-ray", "") },
+    Signature { technology: "PHP", marker: Marker::Header("x-powered-by", "php") },
+    Signature { technology: "ASP.NET", marker: Marker::Header("x-aspnet-version", "") },
+    Signature { technology: "ASP.NET", marker: Marker::Header("x-powered-by", "asp.net") },
+    Signature { technology: "Express", marker: Marker::Header("x-powered-by", "express") },
+    Signature { technology: "Next.js", marker: Marker::Header("x-powered-by", "next.js") },
+    Signature { technology: "Next.js", marker: Marker::Body("__NEXT_DATA__") },
+    Signature { technology: "Drupal", marker: Marker::Header("x-generator", "drupal") },
+    Signature { technology: "WordPress", marker: Marker::Body("/wp-content/") },
+    Signature { technology: "WordPress", marker: Marker::Body("content=\"WordPress") },
+    Signature { technology: "Joomla", marker: Marker::Body("content=\"Joomla") },
+    Signature { technology: "React", marker: Marker::Body("data-reactroot") },
+    Signature { technology: "Angular", marker: Marker::Body("ng-version=") },
+    Signature { technology: "Vue.js", marker: Marker::Body("data-v-app") },
+    Signature { technology: "Nuxt", marker: Marker::Body("window.__NUXT__") },
+    Signature { technology: "Gatsby", marker: Marker::Body("id=\"___gatsby\"") },
+    Signature { technology: "jQuery", marker: Marker::Body("jquery") },
+];
+
+#[derive(Serialize, Debug)]
+pub struct Hop {
+    pub url: String,
+    pub status: u16,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SecurityHeaders {
+    pub present: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HttpReport {
+    pub requested_url: String,
+    pub final_url: String,
+    pub status: u16,
+    pub redirects: Vec<Hop>,
+    pub redirect_limit_reached: bool,
+    pub title: Option<String>,
+    /// The page's `<meta name="generator">`, e.g. "WordPress 6.4.2".
+    pub generator: Option<String>,
+    pub server: Option<String>,
+    pub powered_by: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub security_headers: SecurityHeaders,
+    pub technologies: Vec<String>,
+    /// Body bytes read, at most 1 MiB.
+    pub body_bytes: usize,
+    pub body_truncated: bool,
+}
+
+/// One scheme's result; a failed probe does not fail the other.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum Probe {
+    Ok(Box<HttpReport>),
+    Failed { requested_url: String, error: String },
+}
+
+#[derive(Serialize, Debug)]
+pub struct WebProbeReport {
+    pub target: String,
+    /// https first. A URL target is probed as given, a bare host over both
+    /// schemes.
+    pub probes: Vec<Probe>,
+}
+
+pub async fn fetch_http(target: &str, max_redirects: usize) -> Result<Value, OsintError> {
+    let urls = target_urls(target)?;
+    // Redirects are followed by hand so every hop can be recorded.
+    let client = crate::client_builder().redirect(Policy::none()).timeout(REQUEST_TIMEOUT).build()?;
+
+    let mut probes = Vec::with_capacity(urls.len());
+    let mut failures = Vec::new();
+    for url in urls {
+        match probe(&client, url.clone(), max_redirects).await {
+            Ok(report) => probes.push(Probe::Ok(Box::new(report))),
+            Err(err) => {
+                info!(%url, %err, "HTTP probe failed");
+                probes.push(Probe::Failed { requested_url: url.to_string(), error: err.to_string() });
+                failures.push((url, err));
+            }
+        }
+    }
+    if failures.len() == probes.len() {
+        // A single URL keeps its own error, and with it its exit code.
+        if failures.len() == 1 {
+            return Err(failures.remove(0).1);
+        }
+        return Err(OsintError::ProviderError(failures.iter().map(|(url, err)| format!("{}: {}", url.scheme(), err)).collect::<Vec<_>>().join("; ")));
+    }
+    Ok(serde_json::to_value(WebProbeReport { target: target.to_string(), probes })?)
+}
+
+async fn probe(client: &reqwest::Client, requested: Url, max_redirects: usize) -> Result<HttpReport, OsintError> {
+    let mut url = requested.clone();
+    let mut redirects = Vec::new();
+    let response = loop {
+        let response = crate::send_with_retries(client, client.get(url.clone()).build()?).await?;
+        let next = response
+            .status()
+            .is_redirection()
+            .then(|| response.headers().get(LOCATION))
+            .flatten()
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok());
+        match next {
+            Some(next) if redirects.len() < max_redirects => {
+                redirects.push(Hop { url: url.to_string(), status: response.status().as_u16() });
+                url = next;
+            }
+            _ => break response,
+        }
+    };
+
+    let status = response.status().as_u16();
+    let redirect_limit_reached = response.status().is_redirection() && redirects.len() >= max_redirects;
+    let headers = header_map(response.headers());
+    let (bytes, body_truncated) = read_capped(response, MAX_BODY_BYTES).await?;
+    let body = decode_body(&bytes, headers.get(CONTENT_TYPE.as_str()).map(String::as_str));
+    debug!(%url, bytes = bytes.len(), truncated = body_truncated, "response body read");
+
+    let (present, missing) = SECURITY_HEADERS.iter().map(|name| name.to_string()).partition(|name| headers.contains_key(name));
+    Ok(HttpReport {
+        requested_url: requested.to_string(),
+        final_url: url.to_string(),
+        status,
+        redirects,
+        redirect_limit_reached,
+        title: page_title(&body),
+        generator: meta_generator(&body),
+        server: headers.get("server").cloned(),
+        powered_by: headers.get("x-powered-by").cloned(),
+        technologies: detect_technologies(&headers, &body),
+        security_headers: SecurityHeaders { present, missing },
+        headers,
+        body_bytes: bytes.len(),
+        body_truncated,
+    })
+}
+
+/// Accepts bare hosts as well as URLs, defaulting to https. A bare IPv6
+/// address is bracketed, as a URL needs it.
+pub fn target_url(target: &str) -> Result<Url, OsintError> {
+    let candidate = match target.parse::<Ipv6Addr>() {
+        Ok(ip) => format!("https://[{}]", ip),
+        Err(_) if target.contains("://") => target.to_string(),
+        Err(_) => format!("https://{}", target),
+    };
+    Url::parse(&candidate).map_err(|err| OsintError::InvalidTarget(format!("{}: {}", target, err)))
+}
+
+/// The URL a URL target names, or both schemes for a bare host.
+pub fn target_urls(target: &str) -> Result<Vec<Url>, OsintError> {
+    let https = target_url(target)?;
+    if target.contains("://") {
+        return Ok(vec![https]);
+    }
+    let mut http = https.clone();
+    // Switching between the special schemes http and https cannot fail.
+    let _ = http.set_scheme("http");
+    Ok(vec![https, http])
+}
+
+/// Reads at most `limit` bytes of the body, and whether there was more.
+pub async fn read_capped(mut response: Response, limit: usize) -> Result<(Vec<u8>, bool), OsintError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|err| err.without_url())? {
+        let room = limit - bytes.len();
+        if chunk.len() > room {
+            bytes.extend_from_slice(&chunk[..room]);
+            return Ok((bytes, true));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((bytes, false))
+}
+
+/// Decodes with the charset of the Content-Type header or of a `<meta
+/// charset>` near the top of the page, falling back to lossy UTF-8.
+pub fn decode_body(bytes: &[u8], content_type: Option<&str>) -> String {
+    let from_header = content_type.and_then(|value| value.split(';').find_map(|param| param.trim().strip_prefix("charset="))).map(|label| label.trim_matches('"').to_string());
+    let from_meta = || {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+        let start = head.find("charset=")? + "charset=".len();
+        let label: String = head[start..].trim_start_matches(['"', '\'']).chars().take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')).collect();
+        (!label.is_empty()).then_some(label)
+    };
+    let encoding = from_header.or_else(from_meta).and_then(|label| Encoding::for_label(label.as_bytes())).unwrap_or(UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+/// The `<title>` text with whitespace collapsed and common entities decoded.
+pub fn page_title(body: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = decode_entities(&body[start..end].split_whitespace().collect::<Vec<_>>().join(" "));
+    (!title.is_empty()).then_some(title)
+}
+
+/// The content of `<meta name="generator" content="...">`, attributes in
+/// either order.
+pub fn meta_generator(body: &str) -> Option<String> {
+    meta_content(body, "generator")
+}
+
+/// The content of the `<meta>` tag whose `name` or `property` is `key`
+/// (ASCII case-insensitive), as in `<meta property="og:site_name"
+/// content="...">`, attributes in either order.
+pub fn meta_content(body: &str, key: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let key = key.to_ascii_lowercase();
+    let names = ["name", "property"].map(|attribute| [format!("{}=\"{}\"", attribute, key), format!("{}='{}'", attribute, key)]);
+    lower.match_indices("<meta").find_map(|(start, _)| {
+        let end = start + lower[start..].find('>')?;
+        let tag = &lower[start..end];
+        if !names.iter().flatten().any(|name| tag.contains(name.as_str())) {
+            return None;
+        }
+        let from = start + tag.find("content=")? + "content=".len();
+        let quote = body[from..].chars().next().filter(|c| matches!(c, '"' | '\''))?;
+        let value = &body[from + 1..];
+        Some(decode_entities(&value[..value.find(quote)?]))
+    })
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&#39;", "'").replace("&#x27;", "'").replace("&nbsp;", " ").replace("&amp;", "&").trim().to_string()
+}
+
+fn header_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+        map.entry(name.as_str().to_string())
+            .and_modify(|existing: &mut String| {
+                existing.push_str(", ");
+                existing.push_str(&value);
+            })
+            .or_insert(value);
+    }
+    map
+}
+
+fn detect_technologies(headers: &BTreeMap<String, String>, body: &str) -> Vec<String> {
+    let mut found: Vec<String> = SIGNATURES
+        .iter()
+        .filter(|signature| match signature.marker {
+            Marker::Header(name, needle) => headers.get(name).is_some_and(|value| value.to_ascii_lowercase().contains(needle)),
+            Marker::Body(needle) => body.contains(needle),
+        })
+        .map(|signature| signature.technology.to_string())
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
@@ -0,0 +1,67 @@
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::paginate::paginate;
+use crate::OsintError;
+
+const HUNTER_DOMAIN_SEARCH_URL: &str = "https://api.hunter.io/v2/domain-search";
+/// The largest page the domain-search endpoint serves.
+const PAGE_SIZE: u64 = 100;
+
+/// Every address Hunter has for `domain`, or the first `max_results`.
+pub async fn fetch_hunter(domain: &str, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("HUNTER_API_KEY")?;
+    search_from(HUNTER_DOMAIN_SEARCH_URL, &key, domain, max_results).await
+}
+
+/// The paged domain search against `search_url`, so tests can point it at
+/// a mock server.
+pub async fn search_from(search_url: &str, key: &str, domain: &str, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let fetch = |offset| page(&client, search_url, key, domain, offset);
+    let results = paginate(0, max_results, fetch, |body: &Value, &offset: &u64, _| {
+        let next = offset + PAGE_SIZE;
+        (page_emails(body), (next < body["meta"]["results"].as_u64().unwrap_or(0)).then_some(next))
+    })
+    .await?;
+    let first = &results.first;
+    let total = first["meta"]["results"].as_u64().unwrap_or(0);
+    debug!(%domain, total, fetched = results.items.len(), "hunter domain search finished");
+
+    Ok(json!({
+        "domain": domain,
+        "organization": first["data"]["organization"],
+        "pattern": first["data"]["pattern"],
+        "total_results": total,
+        "capped": results.capped,
+        "emails": results.items,
+    }))
+}
+
+async fn page(client: &Client, search_url: &str, key: &str, domain: &str, offset: u64) -> Result<Value, OsintError> {
+    let request = client
+        .get(search_url)
+        .query(&[("domain", domain), ("api_key", key), ("limit", &PAGE_SIZE.to_string()), ("offset", &offset.to_string())])
+        .build()?;
+    let response = crate::send_with_retries(client, request).await?;
+    match response.status() {
+        status if status.is_success() => Ok(response.json().await?),
+        StatusCode::UNAUTHORIZED => Err(OsintError::InvalidCredentials("HUNTER_API_KEY".to_string())),
+        status => Err(OsintError::ApiStatus { status, url: search_url.to_string() }),
+    }
+}
+
+fn page_emails(page: &Value) -> Vec<Value> {
+    page["data"]["emails"].as_array().into_iter().flatten().map(normalize_email).collect()
+}
+
+fn normalize_email(email: &Value) -> Value {
+    let sources: Vec<&Value> = email["sources"].as_array().into_iter().flatten().map(|source| &source["uri"]).collect();
+    json!({
+        "email": email["value"],
+        "type": email["type"],
+        "confidence": email["confidence"],
+        "sources": sources,
+    })
+}
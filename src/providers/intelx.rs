@@ -0,0 +1,143 @@
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Map, Value};
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::OsintError;
+
+const INTELX_BASE_URL: &str = "https://2.intelx.io";
+/// Records kept per bucket group without `--max-results`.
+const DEFAULT_MAX_RESULTS: usize = 100;
+/// The groups a target is searched in, each with the IntelX buckets it
+/// covers. Every group is its own search, so one the key is not licensed
+/// for does not cost the others.
+pub const BUCKET_GROUPS: &[(&str, &[&str])] = &[("pastes", &["pastes"]), ("leaks", &["leaks.public", "leaks.private"]), ("darknet", &["darknet.tor", "darknet.i2p"])];
+
+/// How often the result endpoint is asked and for how long.
+#[derive(Debug, Clone, Copy)]
+pub struct Polling {
+    pub delay: Duration,
+    pub max_delay: Duration,
+    pub deadline: Duration,
+}
+
+impl Polling {
+    pub const DEFAULT: Polling = Polling { delay: Duration::from_secs(1), max_delay: Duration::from_secs(5), deadline: Duration::from_secs(60) };
+}
+
+/// The records IntelX holds on an email, domain or IP across its pastes,
+/// leaks and darknet buckets, grouped by bucket.
+pub async fn fetch_intelx(target: &str, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("INTELX_API_KEY")?;
+    search_from(INTELX_BASE_URL, &key, target, max_results.unwrap_or(DEFAULT_MAX_RESULTS), Polling::DEFAULT).await
+}
+
+/// The searches against `base_url`, so tests can point them at a mock
+/// server. Groups whose buckets the key is not licensed for are reported
+/// as `not licensed`; the run only fails when none of them are.
+pub async fn search_from(base_url: &str, key: &str, target: &str, max_results: usize, polling: Polling) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut groups = Map::new();
+    let mut not_licensed = Vec::new();
+    // One at a time: IntelX caps the searches a key may run at once.
+    for &(group, buckets) in BUCKET_GROUPS {
+        let records = match submit(&client, base_url, key, target, buckets, max_results).await? {
+            Some(id) => poll(&client, base_url, key, &id, max_results, polling).await?,
+            None => {
+                warn!(group, "IntelX key is not licensed for these buckets");
+                not_licensed.push(group);
+                groups.insert(group.to_string(), json!({ "status": "not licensed", "count": 0, "records": [] }));
+                continue;
+            }
+        };
+        let records: Vec<Value> = records.iter().map(record).collect();
+        groups.insert(group.to_string(), json!({ "status": "ok", "count": records.len(), "records": records }));
+    }
+    if not_licensed.len() == BUCKET_GROUPS.len() {
+        return Err(OsintError::InvalidCredentials("INTELX_API_KEY (not licensed for any of pastes, leaks or darknet)".to_string()));
+    }
+    let total: u64 = groups.values().filter_map(|group| group["count"].as_u64()).sum();
+    let mut summary = groups.iter().filter(|(_, group)| group["status"] == "ok").map(|(name, group)| format!("{} {}", group["count"], name)).collect::<Vec<_>>().join(", ");
+    if !not_licensed.is_empty() {
+        summary.push_str(&format!("; not licensed: {}", not_licensed.join(", ")));
+    }
+    info!(target, total, not_licensed = not_licensed.len(), "intelx search done");
+    Ok(json!({
+        "target": target,
+        "summary": summary,
+        "total": total,
+        "not_licensed": not_licensed,
+        "buckets": groups,
+    }))
+}
+
+/// Starts a search of `buckets` and returns its id, or `None` when the key
+/// may not search them.
+async fn submit(client: &Client, base_url: &str, key: &str, target: &str, buckets: &[&str], max_results: usize) -> Result<Option<String>, OsintError> {
+    let url = format!("{}/intelligent/search", base_url);
+    let body = json!({ "term": target, "buckets": buckets, "lookuplevel": 0, "maxresults": max_results, "timeout": 0, "sort": 4, "media": 0 });
+    let response = crate::send_with_retries(client, client.post(&url).header("x-key", key).json(&body).build()?).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Ok(None),
+        StatusCode::PAYMENT_REQUIRED => return Err(OsintError::QuotaExhausted("IntelX search credits exhausted".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+    let accepted: Value = response.json().await?;
+    match accepted["status"].as_u64().unwrap_or_default() {
+        0 => {}
+        1 => return Err(OsintError::InvalidTarget(format!("IntelX rejected the search term {}", target))),
+        2 => return Err(OsintError::ProviderError("IntelX: too many searches running for this key".to_string())),
+        status => return Err(OsintError::ProviderError(format!("IntelX search refused with status {}", status))),
+    }
+    accepted["id"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .map(|id| Some(id.to_string()))
+        .ok_or_else(|| OsintError::ProviderError("IntelX accepted the search without returning an id".to_string()))
+}
+
+/// Collects a search's records. Each answer hands over the records found
+/// since the last one; status 0 means more may follow, 3 that none are
+/// ready yet, and 1 that the search is done.
+async fn poll(client: &Client, base_url: &str, key: &str, id: &str, max_results: usize, polling: Polling) -> Result<Vec<Value>, OsintError> {
+    let url = format!("{}/intelligent/search/result?id={}&limit={}", base_url, id, max_results);
+    let started = Instant::now();
+    let mut delay = polling.delay;
+    let mut records = Vec::new();
+    loop {
+        let response = crate::send_with_retries(client, client.get(&url).header("x-key", key).build()?).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(OsintError::ApiStatus { status, url });
+        }
+        let body: Value = response.json().await?;
+        records.extend(body["records"].as_array().into_iter().flatten().cloned());
+        match body["status"].as_u64().unwrap_or_default() {
+            1 => break,
+            0 | 3 if records.len() >= max_results => break,
+            0 | 3 => debug!(%id, records = records.len(), elapsed_secs = started.elapsed().as_secs(), "intelx search still running"),
+            2 => return Err(OsintError::ProviderError(format!("IntelX no longer knows search {}", id))),
+            status => return Err(OsintError::ProviderError(format!("IntelX search {} answered status {}", id, status))),
+        }
+        if started.elapsed() >= polling.deadline {
+            return Err(OsintError::ProviderError(format!("IntelX search {} did not finish within {:?}", id, polling.deadline)));
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(polling.max_delay);
+    }
+    records.truncate(max_results);
+    Ok(records)
+}
+
+/// One record's bucket, name, date and media type; `mediah` is IntelX's
+/// human-readable name for the numeric `media`.
+pub fn record(record: &Value) -> Value {
+    json!({
+        "bucket": record["bucket"],
+        "name": record["name"],
+        "date": record["date"],
+        "media": record["mediah"].as_str().map(Value::from).unwrap_or_else(|| record["media"].clone()),
+        "systemid": record["systemid"],
+    })
+}
@@ -0,0 +1,118 @@
+use std::env;
+use std::net::IpAddr;
+
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use tracing::{info, warn};
+
+use super::asn;
+use crate::OsintError;
+
+const IPINFO_URL: &str = "https://ipinfo.io";
+/// IPs of one report enriched at most, to keep a big `all` run from
+/// spending the token's monthly allowance.
+const MAX_ENRICHED_IPS: usize = 50;
+
+/// Attribution for an IP from ipinfo.io. `IPINFO_TOKEN` is optional: without
+/// it ipinfo answers a limited number of requests a day.
+pub async fn fetch_ipinfo(ip: &str) -> Result<Value, OsintError> {
+    let token = token();
+    if token.is_none() {
+        warn!("IPINFO_TOKEN is not set, using ipinfo.io's keyless daily allowance");
+    }
+    lookup_from(IPINFO_URL, token.as_deref(), ip).await
+}
+
+fn token() -> Option<String> {
+    env::var("IPINFO_TOKEN").ok().filter(|token| !token.is_empty())
+}
+
+/// The lookup against `base_url`, so tests can point it at a mock server.
+pub async fn lookup_from(base_url: &str, token: Option<&str>, ip: &str) -> Result<Value, OsintError> {
+    let url = format!("{}/{}/json", base_url, ip);
+    let client = crate::client_builder().build()?;
+    let mut request = client.get(&url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = crate::send_with_retries(&client, request.build()?).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials("IPINFO_TOKEN".to_string())),
+        StatusCode::TOO_MANY_REQUESTS if token.is_none() => return Err(OsintError::QuotaExhausted("ipinfo.io's keyless limit was hit; set IPINFO_TOKEN (free from https://ipinfo.io/signup)".to_string())),
+        StatusCode::TOO_MANY_REQUESTS => return Err(OsintError::QuotaExhausted("ipinfo.io's limit for IPINFO_TOKEN was hit".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url }),
+    }
+    Ok(parse_ipinfo(ip, &response.json().await?))
+}
+
+/// Reads an ipinfo.io answer. Private and reserved addresses come back as
+/// `{"bogon": true}`. The `privacy` and `abuse` blocks only come with paid
+/// plans and are left out when missing.
+pub fn parse_ipinfo(ip: &str, body: &Value) -> Value {
+    if body["bogon"] == true {
+        return json!({ "ip": ip, "bogon": true });
+    }
+    let text = |value: &Value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+    // Free plans fold the ASN into `org`, as in "AS15169 Google LLC".
+    let (org_asn, org_name) = match text(&body["org"]) {
+        Some(org) => match org.split_once(' ').filter(|(asn, _)| asn.starts_with("AS")) {
+            Some((asn, name)) => (asn.trim_start_matches("AS").parse::<u64>().ok(), Some(name.to_string())),
+            None => (None, Some(org)),
+        },
+        None => (None, None),
+    };
+    let mut report = json!({
+        "ip": body["ip"].as_str().unwrap_or(ip),
+        "bogon": false,
+        "hostname": text(&body["hostname"]),
+        "city": text(&body["city"]),
+        "region": text(&body["region"]),
+        "country": text(&body["country"]),
+        "location": text(&body["loc"]),
+        "asn": body["asn"]["asn"].as_str().and_then(|asn| asn.trim_start_matches("AS").parse::<u64>().ok()).or(org_asn),
+        "org": text(&body["asn"]["name"]).or(org_name),
+    });
+    if let Some(privacy) = body["privacy"].as_object() {
+        let flag = |key: &str| privacy.get(key).and_then(Value::as_bool);
+        report["privacy"] = json!({ "vpn": flag("vpn"), "proxy": flag("proxy"), "tor": flag("tor"), "relay": flag("relay"), "hosting": flag("hosting"), "service": privacy.get("service").and_then(text) });
+    }
+    if let Some(abuse) = body["abuse"].as_object() {
+        report["abuse"] = json!({ "email": abuse.get("email").and_then(text), "name": abuse.get("name").and_then(text), "phone": abuse.get("phone").and_then(text), "network": abuse.get("network").and_then(text) });
+    }
+    report
+}
+
+/// Adds `ipinfo`, keyed by IP, for the IPs a report found, such as a `dns`
+/// run's addresses or the subdomains `all --resolve-via` mapped. Only runs
+/// when `IPINFO_TOKEN` is set.
+pub async fn enrich(target: &str, data: Value) -> Value {
+    match token() {
+        Some(token) => enrich_from(IPINFO_URL, &token, target, data).await,
+        None => data,
+    }
+}
+
+pub async fn enrich_from(base_url: &str, token: &str, target: &str, mut data: Value) -> Value {
+    let ips: Vec<IpAddr> = crate::geoip::report_ips(target, &data).iter().filter_map(|ip| ip.parse().ok()).filter(|ip| asn::is_routable(*ip)).collect();
+    if ips.is_empty() || !data.is_object() {
+        return data;
+    }
+    if ips.len() > MAX_ENRICHED_IPS {
+        warn!(found = ips.len(), limit = MAX_ENRICHED_IPS, "enriching only the first IPs with ipinfo.io");
+    }
+    let mut enriched = Map::new();
+    for ip in ips.into_iter().take(MAX_ENRICHED_IPS) {
+        match lookup_from(base_url, Some(token), &ip.to_string()).await {
+            Ok(info) => {
+                enriched.insert(ip.to_string(), info);
+            }
+            Err(err) => warn!(%ip, %err, "ipinfo enrichment failed"),
+        }
+    }
+    info!(count = enriched.len(), "IPs enriched with ipinfo.io");
+    if !enriched.is_empty() {
+        data["ipinfo"] = Value::Object(enriched);
+    }
+    data
+}
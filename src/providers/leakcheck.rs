@@ -0,0 +1,99 @@
+use std::collections::BTreeSet;
+
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use tracing::{info, warn};
+
+use crate::OsintError;
+
+const LEAKCHECK_BASE_URL: &str = "https://leakcheck.io/api/v2";
+/// Fields of a leaked record holding a credential. Without
+/// `--include-secrets` only their presence is reported.
+pub const SECRET_FIELDS: &[&str] = &["password", "hash", "hashed_password", "salt"];
+
+/// The leaks LeakCheck knows of for an email: each source, its breach date
+/// and the fields it exposed.
+pub async fn fetch_leakcheck(email: &str, include_secrets: bool) -> Result<Value, OsintError> {
+    let key = crate::api_key("LEAKCHECK_API_KEY")?;
+    query_from(LEAKCHECK_BASE_URL, &key, email, include_secrets).await
+}
+
+/// `/query/{email}` against `base_url`, so tests can point it at a mock
+/// server.
+pub async fn query_from(base_url: &str, key: &str, email: &str, include_secrets: bool) -> Result<Value, OsintError> {
+    let url = format!("{}/query/{}?type=email", base_url, email);
+    let client = crate::client_builder().build()?;
+    let response = crate::send_with_retries(&client, client.get(&url).header("X-API-Key", key).build()?).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    let error = body["error"].as_str().unwrap_or_default();
+    // LeakCheck answers an address it has nothing on with a "Not found" error.
+    if status == StatusCode::NOT_FOUND || error.eq_ignore_ascii_case("not found") {
+        return Ok(exposures(email, "leakcheck", &[], include_secrets));
+    }
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => return Err(OsintError::RetriesExhausted(url)),
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials(format!("LEAKCHECK_API_KEY ({})", if error.is_empty() { status.as_str() } else { error }))),
+        // A spent daily limit and a lapsed plan both come back as 403.
+        StatusCode::FORBIDDEN => return Err(OsintError::QuotaExhausted(format!("LeakCheck refused the query ({})", if error.is_empty() { status.as_str() } else { error }))),
+        status if !status.is_success() => return Err(OsintError::ApiStatus { status, url }),
+        _ if body["success"] == false => return Err(OsintError::ProviderError(format!("LeakCheck: {}", error))),
+        _ => {}
+    }
+    let records: Vec<Value> = body["result"].as_array().into_iter().flatten().map(leakcheck_record).collect();
+    let mut data = exposures(email, "leakcheck", &records, include_secrets);
+    data["quota"] = body["quota"].clone();
+    Ok(data)
+}
+
+/// A LeakCheck result in the shape `exposures` reads: the source's name and
+/// breach date, and the leaked fields by name.
+pub fn leakcheck_record(result: &Value) -> Value {
+    let leaked: Map<String, Value> = result.as_object().into_iter().flatten().filter(|(field, _)| !matches!(field.as_str(), "source" | "fields")).map(|(field, value)| (field.clone(), value.clone())).collect();
+    json!({
+        "source": result["source"]["name"],
+        "breach_date": result["source"]["breach_date"].as_str().filter(|date| !date.is_empty()),
+        "unverified": result["source"]["unverified"].as_u64().is_some_and(|flag| flag != 0),
+        "fields": result["fields"],
+        "leaked": leaked,
+    })
+}
+
+/// The report for `records` from `service`. Each source lists the fields it
+/// exposed and whether a password was among them; the secrets themselves
+/// are dropped unless `include_secrets` is set.
+pub fn exposures(target: &str, service: &str, records: &[Value], include_secrets: bool) -> Value {
+    if include_secrets && !records.is_empty() {
+        warn!(target, service, "--include-secrets: leaked passwords are written to the report in plain text");
+    }
+    let sources: Vec<Value> = records
+        .iter()
+        .map(|record| {
+            let leaked = record["leaked"].as_object().cloned().unwrap_or_default();
+            let mut fields: BTreeSet<String> = record["fields"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect();
+            fields.extend(leaked.iter().filter(|(_, value)| !value.is_null() && *value != "").map(|(field, _)| field.clone()));
+            let password_present = fields.iter().any(|field| SECRET_FIELDS.contains(&field.as_str()));
+            let mut source = json!({
+                "source": record["source"],
+                "breach_date": record["breach_date"],
+                "unverified": record["unverified"],
+                "fields": fields,
+                "password_present": password_present,
+            });
+            if include_secrets {
+                source["secrets"] = leaked.into_iter().filter(|(field, _)| SECRET_FIELDS.contains(&field.as_str())).collect::<Map<String, Value>>().into();
+            }
+            source
+        })
+        .collect();
+    let with_password = sources.iter().filter(|source| source["password_present"] == true).count();
+    info!(target, service, sources = sources.len(), with_password, "credential exposure lookup done");
+    json!({
+        "target": target,
+        "service": service,
+        "found": sources.len(),
+        "password_exposed": with_password,
+        "secrets_included": include_secrets,
+        "sources": sources,
+    })
+}
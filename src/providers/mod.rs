@@ -0,0 +1,49 @@
+pub mod abuseipdb;
+pub mod anonymity;
+pub mod asn;
+pub mod axfr;
+pub mod binaryedge;
+pub mod censys;
+pub mod company;
+pub mod crtsh;
+pub mod dns;
+pub mod dnsbl;
+pub mod dnsrecords;
+pub mod dorks;
+pub mod emailauth;
+pub mod emailrep;
+pub mod emailverify;
+pub mod favicon;
+pub mod feed;
+pub mod geoip;
+pub mod github;
+pub mod gravatar;
+pub mod greynoise;
+pub mod hibp;
+pub mod http;
+pub mod hunter;
+pub mod intelx;
+pub mod ipinfo;
+pub mod leakcheck;
+pub mod nvd;
+pub mod onyphe;
+pub mod otx;
+pub mod phish;
+pub mod phone;
+pub mod port43;
+pub mod rdap;
+pub mod reversedns;
+pub mod robots;
+pub mod screenshot;
+pub mod securitytrails;
+pub mod serpapi;
+pub mod shodan;
+pub mod subbrute;
+pub mod threatfox;
+pub mod tlscert;
+pub mod urlscan;
+pub mod username;
+pub mod virustotal;
+pub mod wayback;
+pub mod whois;
+pub mod zoomeye;
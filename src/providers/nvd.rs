@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::ratelimit::RateLimiter;
+use crate::OsintError;
+
+pub const NVD_BASE_URL: &str = "https://services.nvd.nist.gov/rest/json/cves/2.0";
+/// NVD allows 5 requests per 30 seconds without a key and 50 with one.
+const REQUESTS_PER_MINUTE: u32 = 10;
+const KEYED_REQUESTS_PER_MINUTE: u32 = 100;
+/// Published CVEs are rarely rescored, so enrichments are reused for a week.
+const CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// A CVE with its NVD details, as listed under a Shodan result's `cves`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Cve {
+    pub id: String,
+    /// CVSS v3 base score.
+    pub cvss: Option<f64>,
+    /// CVSS v3 severity, lowercase: `critical`, `high`, `medium`, `low` or `none`.
+    pub severity: Option<String>,
+    pub description: Option<String>,
+    pub published: Option<String>,
+    /// Why NVD could not enrich the CVE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The CVE ids in a Shodan host record: its top-level `vulns` and those of
+/// each service banner, either a list of ids or an object keyed by id.
+pub fn cve_ids(host: &Value) -> Vec<String> {
+    let banners = host["data"].as_array().into_iter().flatten().map(|banner| &banner["vulns"]);
+    let ids: BTreeSet<String> = std::iter::once(&host["vulns"])
+        .chain(banners)
+        .flat_map(|vulns| match vulns {
+            Value::Object(vulns) => vulns.keys().cloned().collect(),
+            Value::Array(vulns) => vulns.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+            _ => Vec::new(),
+        })
+        .map(|id| id.to_ascii_uppercase())
+        .filter(|id| id.starts_with("CVE-"))
+        .collect();
+    ids.into_iter().collect()
+}
+
+/// `ids` enriched from NVD under its rate limit, using `NVD_API_KEY` when set.
+pub async fn enrich(ids: &[String]) -> Vec<Cve> {
+    let key = env::var("NVD_API_KEY").ok().filter(|key| !key.is_empty());
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    let limiter = LIMITER.get_or_init(|| RateLimiter::per_minute(if key.is_some() { KEYED_REQUESTS_PER_MINUTE } else { REQUESTS_PER_MINUTE }));
+    enrich_from(NVD_BASE_URL, key.as_deref(), ids, &cache_dir(), Some(limiter)).await
+}
+
+/// The enrichment against `base_url` with its cache in `cache_dir`, so tests
+/// can point both elsewhere. Results are sorted by CVSS score, highest first;
+/// a CVE NVD failed on keeps its id and the error.
+pub async fn enrich_from(base_url: &str, key: Option<&str>, ids: &[String], cache_dir: &Path, limiter: Option<&RateLimiter>) -> Vec<Cve> {
+    let mut cves = Vec::new();
+    for id in ids {
+        if let Some(cve) = cached(cache_dir, id) {
+            debug!(id, "using cached NVD enrichment");
+            cves.push(cve);
+            continue;
+        }
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        let cve = match lookup(base_url, key, id).await {
+            Ok(cve) => {
+                store(cache_dir, &cve);
+                cve
+            }
+            Err(err) => {
+                warn!(id, %err, "NVD enrichment failed");
+                Cve { id: id.clone(), cvss: None, severity: None, description: None, published: None, error: Some(err.to_string()) }
+            }
+        };
+        cves.push(cve);
+    }
+    cves.sort_by(|a, b| b.cvss.unwrap_or(-1.0).total_cmp(&a.cvss.unwrap_or(-1.0)).then_with(|| a.id.cmp(&b.id)));
+    info!(count = cves.len(), "CVEs enriched");
+    cves
+}
+
+async fn lookup(base_url: &str, key: Option<&str>, id: &str) -> Result<Cve, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut request = client.get(base_url).query(&[("cveId", id)]);
+    if let Some(key) = key {
+        request = request.header("apiKey", key);
+    }
+    let response = crate::send_with_retries(&client, request.build()?).await?;
+    let url = format!("{}?cveId={}", base_url, id);
+    match response.status() {
+        status if status.is_success() => parse_cve(id, &response.json().await?),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::FORBIDDEN => Err(OsintError::RetriesExhausted(url)),
+        status => Err(OsintError::ApiStatus { status, url }),
+    }
+}
+
+/// Reads the CVE from an NVD 2.0 response, preferring the primary CVSS v3.1
+/// metric over v3.0 and over third-party scores.
+pub fn parse_cve(id: &str, body: &Value) -> Result<Cve, OsintError> {
+    let cve = body["vulnerabilities"].as_array().into_iter().flatten().map(|entry| &entry["cve"]).find(|cve| cve["id"].as_str().is_some_and(|found| found.eq_ignore_ascii_case(id))).ok_or_else(|| OsintError::ProviderError(format!("NVD has no record of {}", id)))?;
+    let metrics = ["cvssMetricV31", "cvssMetricV30"].into_iter().flat_map(|version| cve["metrics"][version].as_array().into_iter().flatten());
+    let mut metrics: Vec<&Value> = metrics.collect();
+    // A stable sort, so the newer CVSS version still wins among primaries.
+    metrics.sort_by_key(|metric| metric["type"] != "Primary");
+    let cvss_data = metrics.first().map(|metric| &metric["cvssData"]);
+    Ok(Cve {
+        id: id.to_string(),
+        cvss: cvss_data.and_then(|data| data["baseScore"].as_f64()),
+        severity: cvss_data.and_then(|data| data["baseSeverity"].as_str()).map(str::to_ascii_lowercase),
+        description: cve["descriptions"].as_array().into_iter().flatten().find(|description| description["lang"] == "en").and_then(|description| description["value"].as_str()).map(str::to_string),
+        published: cve["published"].as_str().map(str::to_string),
+        error: None,
+    })
+}
+
+/// `CVE-2021-44228 (10.0 critical), CVE-...` for the CVEs of a Shodan
+/// result, in their order.
+pub fn summary(cves: &Value) -> Option<String> {
+    let listed: Vec<String> = cves
+        .as_array()?
+        .iter()
+        .filter_map(|cve| {
+            let id = cve["id"].as_str()?;
+            Some(match (cve["cvss"].as_f64(), cve["severity"].as_str()) {
+                (Some(score), Some(severity)) => format!("{} ({:.1} {})", id, score, severity),
+                (Some(score), None) => format!("{} ({:.1})", id, score),
+                _ => id.to_string(),
+            })
+        })
+        .collect();
+    (!listed.is_empty()).then(|| listed.join(", "))
+}
+
+/// Where enrichments are cached, one file per CVE.
+pub fn cache_dir() -> PathBuf {
+    env::temp_dir().join("osint-recon-tool-nvd")
+}
+
+fn cache_file(cache_dir: &Path, id: &str) -> PathBuf {
+    cache_dir.join(format!("{}.json", id))
+}
+
+fn cached(cache_dir: &Path, id: &str) -> Option<Cve> {
+    let path = cache_file(cache_dir, id);
+    let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+    if SystemTime::now().duration_since(modified).map_or(true, |age| age >= CACHE_MAX_AGE) {
+        return None;
+    }
+    serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()
+}
+
+fn store(cache_dir: &Path, cve: &Cve) {
+    let path = cache_file(cache_dir, &cve.id);
+    let written = fs::create_dir_all(cache_dir).and_then(|_| fs::write(&path, serde_json::to_string(cve).unwrap_or_default()));
+    if let Err(err) = written {
+        warn!(path = %path.display(), %err, "could not cache the NVD enrichment");
+    }
+}
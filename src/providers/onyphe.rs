@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use tracing::{info, warn};
+
+use crate::target::{self, TargetKind};
+use crate::OsintError;
+
+const ONYPHE_BASE_URL: &str = "https://www.onyphe.io/api/v2";
+/// Results kept without `--max-results`; a busy IP's summary can hold
+/// thousands of them.
+const DEFAULT_MAX_RESULTS: usize = 1000;
+
+/// Onyphe's summary of an IP or domain: everything it has, across its
+/// categories, up to `max_results` results.
+pub async fn fetch_onyphe(target: &str, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("ONYPHE_API_KEY")?;
+    summary_from(ONYPHE_BASE_URL, &key, target, max_results.unwrap_or(DEFAULT_MAX_RESULTS)).await
+}
+
+/// The summary lookup against `base_url`, so tests can point it at a mock
+/// server.
+pub async fn summary_from(base_url: &str, key: &str, target: &str, max_results: usize) -> Result<Value, OsintError> {
+    let kind = match target::classify(target) {
+        TargetKind::Ip => "ip",
+        _ => "domain",
+    };
+    let url = format!("{}/summary/{}/{}", base_url, kind, target);
+    let client = crate::client_builder().build()?;
+    let request = client.get(&url).header("Authorization", format!("apikey {}", key)).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    // Onyphe also reports failures with `error` set and a `text`, whatever the status.
+    let text = body["text"].as_str().unwrap_or_default();
+    if rate_limited(status, text) {
+        return Err(OsintError::QuotaExhausted(format!("Onyphe rate limit reached ({})", if text.is_empty() { "HTTP 429" } else { text })));
+    }
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials(format!("ONYPHE_API_KEY ({})", if text.is_empty() { status.as_str() } else { text }))),
+        status if !status.is_success() => return Err(OsintError::ApiStatus { status, url }),
+        _ if body["error"].as_u64().is_some_and(|error| error != 0) => return Err(OsintError::ProviderError(format!("Onyphe: {}", text))),
+        _ => {}
+    }
+    Ok(group(target, &body, max_results))
+}
+
+fn rate_limited(status: StatusCode, text: &str) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || text.to_ascii_lowercase().contains("rate limit")
+}
+
+/// The first `max_results` results grouped under their `@category`, with
+/// the threat lists the target appears on pulled out.
+pub fn group(target: &str, body: &Value, max_results: usize) -> Value {
+    let results = body["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let truncated = results.len() > max_results;
+    if truncated {
+        warn!(target, results = results.len(), kept = max_results, "Onyphe summary truncated");
+    }
+    let mut categories: BTreeMap<&str, Vec<&Value>> = BTreeMap::new();
+    for result in results.iter().take(max_results) {
+        categories.entry(result["@category"].as_str().unwrap_or("unknown")).or_default().push(result);
+    }
+    let counts: Map<String, Value> = categories.iter().map(|(category, results)| (category.to_string(), json!(results.len()))).collect();
+    // Threat list hits count even when the cap drops their results.
+    let mut threatlists: Vec<&str> = results.iter().filter(|result| result["@category"] == "threatlist").filter_map(|result| result["threatlist"].as_str()).collect();
+    threatlists.sort_unstable();
+    threatlists.dedup();
+    let summary = categories.iter().map(|(category, results)| format!("{} {}", results.len(), category)).collect::<Vec<_>>().join(", ");
+    info!(target, results = results.len(), categories = categories.len(), "onyphe summary done");
+    json!({
+        "target": target,
+        "summary": if summary.is_empty() { "no results".to_string() } else { summary },
+        "total": body["total"].as_u64().unwrap_or(results.len() as u64),
+        "returned": results.len().min(max_results),
+        "truncated": truncated,
+        "counts": counts,
+        "threatlists": threatlists,
+        "categories": categories,
+    })
+}
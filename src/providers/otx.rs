@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::net::IpAddr;
+
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::OsintError;
+
+const OTX_INDICATORS_URL: &str = "https://otx.alienvault.com/api/v1/indicators";
+
+/// The OTX indicator section a target belongs under: registrable domains
+/// and their subdomains are separate types.
+pub fn indicator_type(target: &str) -> &'static str {
+    match target.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => "IPv4",
+        Ok(IpAddr::V6(_)) => "IPv6",
+        Err(_) => {
+            let host = target.trim_end_matches('.').to_ascii_lowercase();
+            match psl::domain(host.as_bytes()) {
+                Some(domain) if domain.as_bytes() == host.as_bytes() => "domain",
+                _ => "hostname",
+            }
+        }
+    }
+}
+
+pub fn section_url(indicators_url: &str, target: &str, section: &str) -> String {
+    format!("{}/{}/{}/{}", indicators_url, indicator_type(target), target.trim_end_matches('.'), section)
+}
+
+pub async fn fetch_otx(target: &str) -> Result<Value, OsintError> {
+    let key = env::var("OTX_API_KEY").ok().filter(|key| !key.is_empty());
+    if key.is_none() {
+        warn!("OTX_API_KEY is not set, querying OTX anonymously at reduced rate limits");
+    }
+    lookup_from(OTX_INDICATORS_URL, key.as_deref(), target).await
+}
+
+/// The three section lookups under `indicators_url`, so tests can point
+/// them at a mock server.
+pub async fn lookup_from(indicators_url: &str, key: Option<&str>, target: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let (general, malware, url_list) = tokio::try_join!(
+        get(&client, key, section_url(indicators_url, target, "general")),
+        get(&client, key, section_url(indicators_url, target, "malware")),
+        get(&client, key, section_url(indicators_url, target, "url_list")),
+    )?;
+
+    let pulses: Vec<&Value> = general["pulse_info"]["pulses"].as_array().into_iter().flatten().collect();
+    let pulse_families = pulses
+        .iter()
+        .flat_map(|pulse| pulse["malware_families"].as_array().into_iter().flatten())
+        .filter_map(|family| family["display_name"].as_str().or_else(|| family.as_str()));
+    let related_families = ["alienvault", "other"]
+        .into_iter()
+        .flat_map(|origin| general["pulse_info"]["related"][origin]["malware_families"].as_array().into_iter().flatten())
+        .filter_map(Value::as_str);
+    let malware_families: BTreeSet<&str> = pulse_families.chain(related_families).collect();
+
+    let malware_samples: Vec<&Value> = malware["data"].as_array().into_iter().flatten().map(|sample| &sample["hash"]).collect();
+    let urls: Vec<&Value> = url_list["url_list"].as_array().into_iter().flatten().map(|entry| &entry["url"]).collect();
+
+    Ok(json!({
+        "indicator": target,
+        "indicator_type": indicator_type(target),
+        "pulse_count": general["pulse_info"]["count"].as_u64().unwrap_or(pulses.len() as u64),
+        "pulses": pulses.iter().map(|pulse| &pulse["name"]).collect::<Vec<_>>(),
+        "malware_families": malware_families,
+        "malware_samples": malware_samples,
+        "urls": urls,
+        "reputation": general["reputation"],
+    }))
+}
+
+async fn get(client: &Client, key: Option<&str>, url: String) -> Result<Value, OsintError> {
+    let mut request = client.get(&url);
+    if let Some(key) = key {
+        request = request.header("X-OTX-API-KEY", key);
+    }
+    let response = crate::send_with_retries(client, request.build()?).await?;
+    match response.status() {
+        status if status.is_success() => Ok(response.json().await?),
+        StatusCode::FORBIDDEN => Err(OsintError::InvalidCredentials("OTX_API_KEY".to_string())),
+        status => Err(OsintError::ApiStatus { status, url }),
+    }
+}
@@ -0,0 +1,138 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use url::Url;
+
+use super::feed::{self, Origin};
+use crate::OsintError;
+
+const OPENPHISH_FEED_URL: &str = "https://openphish.com/feed.txt";
+const PHISHTANK_URL: &str = "https://checkurl.phishtank.com/checkurl/";
+/// OpenPhish refreshes its free feed every few hours.
+const FEED_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+const FEED_FILE: &str = "openphish-feed.txt";
+/// Matching feed URLs kept in the report; the count covers them all.
+const MAX_LISTED_MATCHES: usize = 100;
+
+/// Whether the target, or any URL on the domain or under the URL, is a known
+/// phish on OpenPhish and, with `PHISHTANK_API_KEY`, on PhishTank.
+pub async fn fetch_phish(target: &str) -> Result<Value, OsintError> {
+    let key = env::var("PHISHTANK_API_KEY").ok().filter(|key| !key.is_empty());
+    check_from(OPENPHISH_FEED_URL, &cache_dir(), PHISHTANK_URL, key.as_deref(), target).await
+}
+
+/// The check against the feed at `feed_url`, cached in `cache_dir`, and the
+/// PhishTank API at `phishtank_url`, so tests can point both at a mock server.
+pub async fn check_from(feed_url: &str, cache_dir: &Path, phishtank_url: &str, phishtank_key: Option<&str>, target: &str) -> Result<Value, OsintError> {
+    let (text, origin) = feed::fetch(feed_url, cache_dir, FEED_FILE, FEED_MAX_AGE).await?;
+    let entries = text.lines().filter(|line| !line.trim().is_empty()).count();
+    let matches = feed_matches(&text, target);
+    let phishtank = match phishtank_key {
+        Some(key) => Some(phishtank_from(phishtank_url, key, &target_url(target)).await?),
+        None => {
+            info!("PHISHTANK_API_KEY is not set, checking the OpenPhish feed only");
+            None
+        }
+    };
+    let listed = !matches.is_empty() || phishtank.as_ref().is_some_and(|result| result["in_database"] == true && result["valid"] != false);
+    info!(target, listed, feed_matches = matches.len(), "phish check done");
+    Ok(json!({
+        "target": target,
+        "listed": listed,
+        "openphish": {
+            "feed_entries": entries,
+            "stale": origin == Origin::Stale,
+            "match_count": matches.len(),
+            "matches": matches.iter().take(MAX_LISTED_MATCHES).collect::<Vec<_>>(),
+        },
+        "phishtank": phishtank,
+    }))
+}
+
+/// The feed URLs on `target`: for a domain any URL on it or its subdomains,
+/// for a URL that URL itself and any under its path.
+pub fn feed_matches(feed: &str, target: &str) -> Vec<String> {
+    let wanted = Url::parse(target).ok().filter(|url| url.host_str().is_some());
+    let domain = match &wanted {
+        Some(url) => url.host_str().unwrap_or_default().to_ascii_lowercase(),
+        None => target.trim_end_matches('.').to_ascii_lowercase(),
+    };
+    feed.lines()
+        .map(str::trim)
+        .filter_map(|line| Url::parse(line).ok().map(|url| (line, url)))
+        .filter(|(_, url)| {
+            let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+            match &wanted {
+                Some(wanted) => host == domain && url.path().starts_with(wanted.path().trim_end_matches('/')) && path_boundary(url.path(), wanted.path()),
+                None => host == domain || host.ends_with(&format!(".{}", domain)),
+            }
+        })
+        .map(|(line, _)| line.to_string())
+        .collect()
+}
+
+/// Whether `path` is `prefix` itself or lies beneath it, so `/login` does not
+/// match `/login-help`.
+fn path_boundary(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.len() == prefix.len() || prefix.is_empty() || path[prefix.len()..].starts_with('/')
+}
+
+/// PhishTank looks up URLs, so a domain target is asked about as its root.
+fn target_url(target: &str) -> String {
+    match Url::parse(target) {
+        Ok(url) if url.host_str().is_some() => target.to_string(),
+        _ => format!("http://{}/", target),
+    }
+}
+
+/// One PhishTank `checkurl` call, with the URL base64-encoded as it asks.
+pub async fn phishtank_from(base_url: &str, key: &str, url: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let encoded = STANDARD.encode(url);
+    let request = client.post(base_url).form(&[("url", encoded.as_str()), ("format", "json"), ("app_key", key)]).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials("PHISHTANK_API_KEY".to_string())),
+        // PhishTank signals its rate limit with 509.
+        status if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 509 => return Err(OsintError::QuotaExhausted("PhishTank's request limit was hit".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url: base_url.to_string() }),
+    }
+    parse_phishtank(&response.json().await?)
+}
+
+/// Reads a `checkurl` answer; `results` holds the URL's status.
+pub fn parse_phishtank(body: &Value) -> Result<Value, OsintError> {
+    if let Some(message) = body["errortext"].as_str() {
+        warn!(message, "PhishTank refused the lookup");
+        return Err(OsintError::ProviderError(format!("PhishTank: {}", message)));
+    }
+    let results = &body["results"];
+    if !results.is_object() {
+        return Err(OsintError::ProviderError("PhishTank: the answer has no results".to_string()));
+    }
+    let flag = |value: &Value| value.as_bool().or_else(|| value.as_str().map(|text| text == "true" || text == "y"));
+    let in_database = flag(&results["in_database"]).unwrap_or(false);
+    Ok(json!({
+        "url": results["url"],
+        "in_database": in_database,
+        "phish_id": results["phish_id"].as_u64().map(|id| id.to_string()).or_else(|| results["phish_id"].as_str().map(str::to_string)),
+        "detail_page": results["phish_detail_page"],
+        "verified": flag(&results["verified"]),
+        "verified_at": results["verified_at"].as_str().filter(|at| !at.is_empty()),
+        "valid": flag(&results["valid"]),
+        "target": results["target"].as_str().filter(|brand| !brand.is_empty() && !brand.eq_ignore_ascii_case("other")),
+    }))
+}
+
+/// Where the OpenPhish feed is cached between runs.
+pub fn cache_dir() -> PathBuf {
+    env::temp_dir().join("osint-recon-tool-phish")
+}
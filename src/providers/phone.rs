@@ -0,0 +1,106 @@
+use std::env;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::{target, OsintError, RetryPolicy};
+
+/// numverify's free plan only serves plain HTTP.
+const NUMVERIFY_URL: &str = "http://apilayer.net/api/validate";
+const VERIPHONE_URL: &str = "https://api.veriphone.io/v2/verify";
+
+/// Validates and enriches an E.164 number with numverify when
+/// `NUMVERIFY_API_KEY` is set, else with Veriphone (`VERIPHONE_API_KEY`).
+pub async fn fetch_phone(number: &str) -> Result<Value, OsintError> {
+    let number = target::normalize_phone(number).ok_or_else(|| OsintError::InvalidTarget(format!("{} is not an E.164 phone number", number)))?;
+    let key = |name: &str| env::var(name).ok().filter(|key| !key.is_empty());
+    match (key("NUMVERIFY_API_KEY"), key("VERIPHONE_API_KEY")) {
+        (Some(key), _) => numverify_from(NUMVERIFY_URL, &key, &number).await,
+        (None, Some(key)) => veriphone_from(VERIPHONE_URL, &key, &number).await,
+        (None, None) => Err(OsintError::MissingApiKey("NUMVERIFY_API_KEY".to_string())),
+    }
+}
+
+/// The numverify lookup against `base_url`, so tests can point it at a mock
+/// server. numverify answers 200 to failures too, with `success: false`.
+pub async fn numverify_from(base_url: &str, key: &str, number: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let request = client.get(base_url).query(&[("access_key", key), ("number", number.trim_start_matches('+'))]).build()?;
+    // Quotas are monthly, so waiting out a failure does not help.
+    let response = crate::send_with_policy(&client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if body["success"] == false || !status.is_success() {
+        let info = body["error"]["info"].as_str().unwrap_or("no details given");
+        return Err(match (body["error"]["code"].as_u64(), body["error"]["type"].as_str()) {
+            (Some(101), _) | (_, Some("invalid_access_key" | "missing_access_key")) => OsintError::InvalidCredentials("NUMVERIFY_API_KEY".to_string()),
+            (Some(104), _) | (_, Some("usage_limit_reached")) => OsintError::QuotaExhausted(format!("numverify free-tier quota used up: {}", info)),
+            (Some(210 | 211), _) => OsintError::InvalidTarget(format!("numverify rejected {}: {}", number, info)),
+            (Some(code), kind) => OsintError::ProviderError(format!("numverify error {} ({}): {}", code, kind.unwrap_or("unknown"), info)),
+            (None, _) => OsintError::ApiStatus { status, url: base_url.to_string() },
+        });
+    }
+    info!(valid = body["valid"] == true, "numverify lookup done");
+    Ok(json!({
+        "number": number,
+        "provider": "numverify",
+        "valid": body["valid"],
+        "country_code": body["country_code"],
+        "country": body["country_name"],
+        "location": body["location"],
+        "carrier": blank_to_null(&body["carrier"]),
+        "line_type": line_type(&body["line_type"]),
+        "local_format": body["local_format"],
+        "international_format": body["international_format"],
+    }))
+}
+
+/// The Veriphone lookup against `base_url`.
+pub async fn veriphone_from(base_url: &str, key: &str, number: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let request = client.get(base_url).query(&[("key", key), ("phone", number)]).build()?;
+    let response = crate::send_with_policy(&client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    match status {
+        status if status.is_success() && body["status"] == "success" => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials("VERIPHONE_API_KEY".to_string())),
+        StatusCode::PAYMENT_REQUIRED | StatusCode::TOO_MANY_REQUESTS => {
+            return Err(OsintError::QuotaExhausted(format!("Veriphone free-tier quota used up: {}", body["message"].as_str().unwrap_or("no credits left"))));
+        }
+        StatusCode::BAD_REQUEST => return Err(OsintError::InvalidTarget(format!("Veriphone rejected {}: {}", number, body["message"].as_str().unwrap_or("bad request")))),
+        status if status.is_success() => return Err(OsintError::ProviderError(format!("Veriphone: {}", body["message"].as_str().unwrap_or("lookup failed")))),
+        status => return Err(OsintError::ApiStatus { status, url: base_url.to_string() }),
+    }
+    info!(valid = body["phone_valid"] == true, "veriphone lookup done");
+    Ok(json!({
+        "number": number,
+        "provider": "veriphone",
+        "valid": body["phone_valid"],
+        "country_code": body["country_code"],
+        "country": body["country"],
+        "location": body["phone_region"],
+        "carrier": blank_to_null(&body["carrier"]),
+        "line_type": line_type(&body["phone_type"]),
+        "local_format": body["local_number"],
+        "international_format": body["international_number"],
+    }))
+}
+
+/// Both services' line types in one vocabulary: `mobile`, `landline`,
+/// `voip`, `toll_free`, ... as they come otherwise.
+fn line_type(value: &Value) -> Value {
+    match value.as_str().map(str::to_ascii_lowercase).as_deref() {
+        None | Some("") | Some("unknown") => Value::Null,
+        Some("fixed_line") => json!("landline"),
+        Some(kind) => json!(kind),
+    }
+}
+
+fn blank_to_null(value: &Value) -> Value {
+    match value.as_str() {
+        Some(text) if text.trim().is_empty() => Value::Null,
+        _ => value.clone(),
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use encoding_rs::WINDOWS_1252;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info, warn};
+
+use super::rdap;
+use super::whois::{self, WhoisRecord};
+use crate::OsintError;
+
+pub const WHOIS_PORT: u16 = 43;
+/// How long one server gets to take the query and send its whole answer.
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Servers asked per lookup: the registry, the registrar, and IANA first
+/// for a TLD missing from `SERVERS`.
+const MAX_HOPS: usize = 3;
+/// Answers are cut off here; a real one is a few kilobytes.
+const MAX_ANSWER_BYTES: u64 = 256 * 1024;
+/// Asked for TLDs `SERVERS` does not list; it names the registry's server
+/// under `refer:`.
+const IANA_SERVER: &str = "whois.iana.org";
+
+/// Registry WHOIS servers by TLD.
+const SERVERS: &[(&str, &str)] = &[
+    ("com", "whois.verisign-grs.com"),
+    ("net", "whois.verisign-grs.com"),
+    ("org", "whois.publicinterestregistry.org"),
+    ("info", "whois.nic.info"),
+    ("biz", "whois.nic.biz"),
+    ("io", "whois.nic.io"),
+    ("co", "whois.nic.co"),
+    ("me", "whois.nic.me"),
+    ("xyz", "whois.nic.xyz"),
+    ("app", "whois.nic.google"),
+    ("dev", "whois.nic.google"),
+    ("us", "whois.nic.us"),
+    ("uk", "whois.nic.uk"),
+    ("de", "whois.denic.de"),
+    ("fr", "whois.nic.fr"),
+    ("nl", "whois.domain-registry.nl"),
+    ("eu", "whois.eu"),
+    ("it", "whois.nic.it"),
+    ("ch", "whois.nic.ch"),
+    ("se", "whois.iis.se"),
+    ("ru", "whois.tcinet.ru"),
+    ("jp", "whois.jprs.jp"),
+    ("au", "whois.auda.org.au"),
+    ("ca", "whois.cira.ca"),
+    ("br", "whois.registro.br"),
+];
+
+/// Phrases registries answer an unregistered domain with, matched
+/// case-insensitively.
+const NOT_FOUND_MARKERS: &[&str] = &["no match for", "not found", "no entries found", "no data found", "no object found", "status: free", "status: available"];
+
+/// The WHOIS server of the registry for `domain`'s TLD, or IANA's.
+pub fn server_for(domain: &str) -> &'static str {
+    let tld = domain.trim_end_matches('.').rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+    SERVERS.iter().find(|(known, _)| *known == tld).map_or(IANA_SERVER, |(_, server)| server)
+}
+
+/// Asks `server` on `port` about `domain`, then each server its answer
+/// refers to, all on the same port so tests can serve the chain from one
+/// listener. Every server gets `limit` to answer. The record is the
+/// registry's, with the gaps filled from the registrar's, and `raw` keeps
+/// every answer.
+pub async fn lookup_from(server: &str, port: u16, domain: &str, limit: Duration) -> Result<WhoisRecord, OsintError> {
+    let mut visited = HashSet::new();
+    let mut answers: Vec<(String, String)> = Vec::new();
+    let mut next = Some(server.to_ascii_lowercase());
+    while let Some(server) = next.take() {
+        if !visited.insert(server.clone()) {
+            warn!(%domain, %server, "WHOIS referral loop, keeping the answers so far");
+            break;
+        }
+        if answers.len() == MAX_HOPS {
+            warn!(%domain, %server, "WHOIS referral chain longer than {} servers, not following it", MAX_HOPS);
+            break;
+        }
+        let text = match query(&server, port, domain, limit).await {
+            Ok(text) => text,
+            // The registry's answer stands when the registrar's server fails.
+            Err(err) if !answers.is_empty() => {
+                warn!(%domain, %server, %err, "WHOIS referral failed, keeping the answers so far");
+                break;
+            }
+            Err(err) => return Err(err),
+        };
+        debug!(%domain, %server, bytes = text.len(), "WHOIS server answered");
+        // Registrars often name their own server; that is not a referral.
+        next = referral(&text).filter(|referred| *referred != server);
+        answers.push((server, text));
+    }
+
+    // IANA's answer describes the TLD, not the domain.
+    let mut records = answers.iter().filter(|(server, _)| server != IANA_SERVER).map(|(server, text)| (server, parse(domain, text)));
+    let Some((_, registry)) = records.next() else {
+        return Err(OsintError::ProviderError(format!("no WHOIS server known for {}", domain)));
+    };
+    let mut record = registry;
+    for (server, registrar) in records {
+        record = WhoisRecord { referral: Some(server.clone()), ..rdap::merge(record, registrar) };
+    }
+    record.raw = Some(answers.iter().map(|(server, text)| format!("% {}\n{}", server, text.trim_end())).collect::<Vec<_>>().join("\n\n"));
+    info!(%domain, servers = answers.len(), registered = record.registered, "WHOIS lookup done");
+    Ok(record)
+}
+
+/// One query and the whole answer, which ends when the server closes the
+/// connection.
+async fn query(server: &str, port: u16, domain: &str, limit: Duration) -> Result<String, OsintError> {
+    let exchange = async {
+        let mut stream = TcpStream::connect((server, port)).await?;
+        stream.write_all(format!("{}\r\n", query_line(server, domain)).as_bytes()).await?;
+        let mut answer = Vec::new();
+        stream.take(MAX_ANSWER_BYTES).read_to_end(&mut answer).await?;
+        Ok::<_, std::io::Error>(answer)
+    };
+    let answer = timeout(limit, exchange)
+        .await
+        .map_err(|_| OsintError::Connection(format!("WHOIS server {} did not answer within {:?}", server, limit)))?
+        .map_err(|err| OsintError::Connection(format!("WHOIS server {}: {}", server, err)))?;
+    Ok(decode(&answer))
+}
+
+/// The query some registries need beyond the bare domain: Verisign would
+/// also match nameservers by that name, DENIC wants the ASCII query type
+/// and JPRS answers in Japanese without `/e`.
+fn query_line(server: &str, domain: &str) -> String {
+    match server {
+        "whois.verisign-grs.com" => format!("domain {}", domain),
+        "whois.denic.de" => format!("-T dn,ace {}", domain),
+        "whois.jprs.jp" => format!("{}/e", domain),
+        _ => domain.to_string(),
+    }
+}
+
+/// The answer as text. Registries that do not send UTF-8 send Latin-1,
+/// read as its superset windows-1252.
+pub fn decode(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => WINDOWS_1252.decode_without_bom_handling(bytes).0.into_owned(),
+    }
+}
+
+/// The server an answer refers to: a registry's `Registrar WHOIS Server`,
+/// or IANA's `refer`. Web addresses are not WHOIS servers.
+fn referral(text: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter(|(key, _)| matches!(key.trim().to_ascii_lowercase().as_str(), "registrar whois server" | "whois server" | "refer" | "whois"))
+        .map(|(_, value)| value.trim())
+        .map(|value| value.strip_prefix("whois://").unwrap_or(value).trim_end_matches('/'))
+        .find(|value| !value.is_empty() && !value.contains("://") && !value.contains(char::is_whitespace))
+        .map(str::to_ascii_lowercase)
+}
+
+/// Reads the `Key: value` lines of a WHOIS answer, under the labels the
+/// common registries use for each field.
+pub fn parse(domain: &str, text: &str) -> WhoisRecord {
+    let fields: Vec<(String, &str)> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(['%', '#']))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    let all = |labels: &[&str]| fields.iter().filter(|(key, _)| labels.contains(&key.as_str())).map(|(_, value)| value.to_string()).collect::<Vec<_>>();
+    let first = |labels: &[&str]| all(labels).into_iter().next();
+    let tokens = |labels: &[&str]| {
+        let mut values: Vec<String> = all(labels).iter().filter_map(|value| value.split_whitespace().next()).map(|value| value.trim_end_matches('.').to_string()).collect();
+        values.sort_unstable();
+        values.dedup();
+        values
+    };
+
+    let mut redacted = Vec::new();
+    let record = WhoisRecord {
+        domain: domain.to_string(),
+        source: "port43".to_string(),
+        registered: true,
+        registrar: first(&["registrar", "sponsoring registrar", "registrar name"]),
+        created: first(&["creation date", "created", "created on", "registered on", "registration time"]).map(whois::iso_date),
+        updated: first(&["updated date", "last updated", "last modified", "last-modified", "changed"]).map(whois::iso_date),
+        expires: first(&["registry expiry date", "registrar registration expiration date", "expiry date", "expiration date", "expires", "paid-till"]).map(whois::iso_date),
+        status: tokens(&["domain status", "status", "state"]),
+        nameservers: tokens(&["name server", "nameserver", "nserver"]).into_iter().map(|name| name.to_ascii_lowercase()).collect(),
+        registrant_org: whois::disclosed("Registrant Organization", first(&["registrant organization", "registrant organisation"]), &mut redacted),
+        registrant_country: whois::disclosed("Registrant Country", first(&["registrant country", "registrant country code"]), &mut redacted),
+        redacted,
+        referral: None,
+        raw: None,
+    };
+    let lowered = text.to_ascii_lowercase();
+    if record.registrar.is_none() && record.created.is_none() && record.nameservers.is_empty() && NOT_FOUND_MARKERS.iter().any(|marker| lowered.contains(marker)) {
+        return WhoisRecord { domain: domain.to_string(), source: "port43".to_string(), registered: false, ..WhoisRecord::default() };
+    }
+    record
+}
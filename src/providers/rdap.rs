@@ -0,0 +1,336 @@
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+use super::whois::{self, WhoisRecord};
+use crate::target::{self, TargetKind};
+use crate::OsintError;
+
+const BOOTSTRAP_BASE_URL: &str = "https://data.iana.org/rdap";
+/// IANA updates the bootstrap files a few times a year at most.
+const BOOTSTRAP_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The IANA bootstrap registries, one per kind of RDAP object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Registry {
+    Dns,
+    Ipv4,
+    Ipv6,
+    Asn,
+}
+
+impl Registry {
+    fn file(self) -> &'static str {
+        match self {
+            Registry::Dns => "dns.json",
+            Registry::Ipv4 => "ipv4.json",
+            Registry::Ipv6 => "ipv6.json",
+            Registry::Asn => "asn.json",
+        }
+    }
+
+    /// Loaded once per run, and kept on disk between runs.
+    fn cached(self) -> &'static OnceCell<Bootstrap> {
+        static DNS: OnceCell<Bootstrap> = OnceCell::const_new();
+        static IPV4: OnceCell<Bootstrap> = OnceCell::const_new();
+        static IPV6: OnceCell<Bootstrap> = OnceCell::const_new();
+        static ASN: OnceCell<Bootstrap> = OnceCell::const_new();
+        match self {
+            Registry::Dns => &DNS,
+            Registry::Ipv4 => &IPV4,
+            Registry::Ipv6 => &IPV6,
+            Registry::Asn => &ASN,
+        }
+    }
+}
+
+/// A bootstrap registry (RFC 9224): which RDAP servers answer for which
+/// TLDs, address blocks or AS number ranges.
+#[derive(Debug)]
+pub struct Bootstrap {
+    services: Vec<(Vec<String>, Vec<String>)>,
+}
+
+impl Bootstrap {
+    pub fn parse(body: &Value) -> Bootstrap {
+        let strings = |value: &Value| value.as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect::<Vec<_>>();
+        let services = body["services"].as_array().into_iter().flatten().map(|service| (strings(&service[0]), strings(&service[1]))).collect();
+        Bootstrap { services }
+    }
+
+    /// The server for the longest registered suffix of `domain`.
+    pub fn server_for_domain(&self, domain: &str) -> Option<&str> {
+        let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+        self.best_match(|entry| {
+            let entry = entry.to_ascii_lowercase();
+            (domain == entry || domain.ends_with(&format!(".{}", entry))).then_some(entry.len())
+        })
+    }
+
+    /// The server for the most specific block containing `ip`.
+    pub fn server_for_ip(&self, ip: IpAddr) -> Option<&str> {
+        self.best_match(|entry| {
+            let (network, length) = entry.split_once('/')?;
+            let (network, length): (IpAddr, u32) = (network.parse().ok()?, length.parse().ok()?);
+            in_block(ip, network, length).then_some(length as usize)
+        })
+    }
+
+    pub fn server_for_asn(&self, asn: u32) -> Option<&str> {
+        self.best_match(|entry| {
+            let (start, end) = entry.split_once('-').unwrap_or((entry, entry));
+            let (start, end): (u32, u32) = (start.parse().ok()?, end.parse().ok()?);
+            (start..=end).contains(&asn).then_some(0)
+        })
+    }
+
+    /// The first HTTPS server of the entry `specificity` ranks highest.
+    fn best_match(&self, specificity: impl Fn(&str) -> Option<usize>) -> Option<&str> {
+        self.services
+            .iter()
+            .filter_map(|(entries, servers)| Some((entries.iter().filter_map(|entry| specificity(entry)).max()?, servers)))
+            .max_by_key(|(rank, _)| *rank)
+            .and_then(|(_, servers)| servers.iter().find(|server| server.starts_with("https://")).or(servers.first()))
+            .map(String::as_str)
+    }
+}
+
+fn in_block(ip: IpAddr, network: IpAddr, length: u32) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if length <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - length).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if length <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - length).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Registration of an IP block or AS number range.
+#[derive(Serialize, Debug)]
+pub struct RdapNetwork {
+    pub query: String,
+    pub server: String,
+    pub handle: Option<String>,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub country: Option<String>,
+    /// First and last address, or first and last AS number.
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub registrant: Option<String>,
+    pub abuse_email: Option<String>,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+}
+
+pub async fn fetch_rdap(target: &str) -> Result<Value, OsintError> {
+    match target::classify(target) {
+        TargetKind::Domain => Ok(serde_json::to_value(domain_record(target).await?)?),
+        TargetKind::Ip => {
+            let ip: IpAddr = target.parse().map_err(|_| OsintError::InvalidTarget(format!("{} is not an IP address", target)))?;
+            let registry = if ip.is_ipv4() { Registry::Ipv4 } else { Registry::Ipv6 };
+            let server = server(registry, |bootstrap| bootstrap.server_for_ip(ip), target).await?;
+            let body = get(&format!("{}ip/{}", server, ip)).await?;
+            Ok(serde_json::to_value(parse_network(target, &server, &body))?)
+        }
+        TargetKind::Asn => {
+            let asn = target::asn_number(target).ok_or_else(|| OsintError::InvalidTarget(format!("{} is not an AS number", target)))?;
+            let server = server(Registry::Asn, |bootstrap| bootstrap.server_for_asn(asn), target).await?;
+            let body = get(&format!("{}autnum/{}", server, asn)).await?;
+            Ok(serde_json::to_value(parse_network(target, &server, &body))?)
+        }
+        got => Err(OsintError::InvalidTarget(format!("rdap cannot look up {} ({})", target, got))),
+    }
+}
+
+/// Looks `domain` up at its TLD's RDAP server, as named by IANA.
+pub async fn domain_record(domain: &str) -> Result<WhoisRecord, OsintError> {
+    let server = server(Registry::Dns, |bootstrap| bootstrap.server_for_domain(domain), domain).await?;
+    domain_record_at(&server, domain).await
+}
+
+/// The domain lookup at `server`, so tests can point it at a mock server.
+/// A registry that refers to the registrar's own RDAP server in its `links`
+/// is followed there once; the registrar's answer fills in what the
+/// registry's leaves out, usually the registrant.
+pub async fn domain_record_at(server: &str, domain: &str) -> Result<WhoisRecord, OsintError> {
+    let url = format!("{}domain/{}", server, domain.trim_end_matches('.'));
+    let body = get(&url).await?;
+    let record = parse_domain(domain, &body);
+    let Some(referral) = referral(&body, &url) else {
+        return Ok(record);
+    };
+    match get(&referral).await {
+        Ok(registrar) => {
+            debug!(%domain, %referral, "followed RDAP referral");
+            let mut merged = merge(record, parse_domain(domain, &registrar));
+            merged.referral = Some(referral);
+            Ok(merged)
+        }
+        Err(err) => {
+            warn!(%domain, %referral, %err, "RDAP referral failed, keeping the registry's answer");
+            Ok(record)
+        }
+    }
+}
+
+/// The registrar's RDAP URL a registry answer links to, if any.
+fn referral(body: &Value, url: &str) -> Option<String> {
+    body["links"]
+        .as_array()?
+        .iter()
+        .filter(|link| link["rel"] == "related" && link["type"].as_str().is_none_or(|kind| kind.starts_with("application/rdap+json")))
+        .filter_map(|link| link["href"].as_str())
+        .find(|href| href.starts_with("http") && !href.eq_ignore_ascii_case(url))
+        .map(str::to_string)
+}
+
+/// The registry's record with the gaps filled from the registrar's, whose
+/// registrant details are preferred when it has them.
+pub(super) fn merge(registry: WhoisRecord, registrar: WhoisRecord) -> WhoisRecord {
+    let mut redacted = registry.redacted;
+    redacted.extend(registrar.redacted);
+    redacted.sort_unstable();
+    redacted.dedup();
+    let registrant_known = registrar.registrant_org.is_some() || registrar.registrant_country.is_some();
+    WhoisRecord {
+        registrar: registry.registrar.or(registrar.registrar),
+        created: registry.created.or(registrar.created),
+        updated: registry.updated.or(registrar.updated),
+        expires: registry.expires.or(registrar.expires),
+        status: if registry.status.is_empty() { registrar.status } else { registry.status },
+        nameservers: if registry.nameservers.is_empty() { registrar.nameservers } else { registry.nameservers },
+        registrant_org: if registrant_known { registrar.registrant_org } else { registry.registrant_org },
+        registrant_country: if registrant_known { registrar.registrant_country } else { registry.registrant_country },
+        redacted,
+        ..registry
+    }
+}
+
+/// Reads an RDAP domain object (RFC 9083): dates come from `events`, the
+/// registrar from the entity with that role. Registrant details withheld
+/// for privacy, whether listed under `redacted` (RFC 9537) or replaced by
+/// a placeholder, are named in `redacted` rather than left out.
+pub fn parse_domain(domain: &str, body: &Value) -> WhoisRecord {
+    let strings = |values: &Value, key: Option<&str>| -> Vec<String> {
+        values.as_array().into_iter().flatten().filter_map(|value| key.map_or(value, |key| &value[key]).as_str()).map(str::to_string).collect()
+    };
+    let mut redacted: Vec<String> = body["redacted"].as_array().into_iter().flatten().filter_map(|entry| entry["name"]["type"].as_str().or(entry["name"]["description"].as_str())).map(str::to_string).collect();
+    let registrant = entity(body, "registrant");
+    let registrant_org = whois::disclosed("Registrant Organization", registrant.and_then(|registrant| vcard(registrant, "org").or_else(|| vcard(registrant, "fn"))), &mut redacted);
+    // The country is the last component of the jCard address.
+    let registrant_country = whois::disclosed("Registrant Country", registrant.and_then(|registrant| registrant["vcardArray"][1].as_array()?.iter().find(|entry| entry[0] == "adr")?[3][6].as_str().filter(|country| !country.is_empty()).map(str::to_string)), &mut redacted);
+    redacted.sort_unstable();
+    redacted.dedup();
+    WhoisRecord {
+        domain: body["ldhName"].as_str().map_or_else(|| domain.to_string(), str::to_ascii_lowercase),
+        source: "rdap".to_string(),
+        registered: true,
+        registrar: entity(body, "registrar").and_then(|registrar| vcard(registrar, "fn")),
+        created: event(body, "registration"),
+        updated: event(body, "last changed"),
+        expires: event(body, "expiration"),
+        status: strings(&body["status"], None),
+        nameservers: strings(&body["nameservers"], Some("ldhName")).into_iter().map(|name| name.trim_end_matches('.').to_ascii_lowercase()).collect(),
+        registrant_org,
+        registrant_country,
+        redacted,
+        referral: None,
+        raw: None,
+    }
+}
+
+/// Reads an RDAP ip network or autnum object.
+pub fn parse_network(query: &str, server: &str, body: &Value) -> RdapNetwork {
+    let text = |value: &Value| match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    };
+    // Abuse contacts usually sit under the registrant rather than at the top.
+    let abuse = entity(body, "abuse").or_else(|| body["entities"].as_array().into_iter().flatten().find_map(|nested| entity(nested, "abuse")));
+    RdapNetwork {
+        query: query.to_string(),
+        server: server.to_string(),
+        handle: text(&body["handle"]),
+        name: text(&body["name"]),
+        kind: text(&body["type"]),
+        country: text(&body["country"]),
+        start: text(&body["startAddress"]).or_else(|| text(&body["startAutnum"])),
+        end: text(&body["endAddress"]).or_else(|| text(&body["endAutnum"])),
+        registrant: entity(body, "registrant").and_then(|registrant| vcard(registrant, "fn")),
+        abuse_email: abuse.and_then(|abuse| vcard(abuse, "email")),
+        created: event(body, "registration"),
+        updated: event(body, "last changed"),
+    }
+}
+
+fn event(body: &Value, action: &str) -> Option<String> {
+    body["events"].as_array()?.iter().find(|event| event["eventAction"] == action)?["eventDate"].as_str().map(str::to_string)
+}
+
+/// The first entity of `body` holding `role`.
+fn entity<'a>(body: &'a Value, role: &str) -> Option<&'a Value> {
+    body["entities"].as_array()?.iter().find(|entity| entity["roles"].as_array().is_some_and(|roles| roles.iter().any(|held| held == role)))
+}
+
+/// A property of an entity's jCard, such as `fn` (formatted name) or `email`.
+fn vcard(entity: &Value, property: &str) -> Option<String> {
+    entity["vcardArray"][1].as_array()?.iter().find(|entry| entry[0] == property)?[3].as_str().map(str::to_string)
+}
+
+async fn server(registry: Registry, pick: impl Fn(&Bootstrap) -> Option<&str>, target: &str) -> Result<String, OsintError> {
+    let bootstrap = registry.cached().get_or_try_init(|| load_bootstrap(registry)).await?;
+    let server = pick(bootstrap).ok_or_else(|| OsintError::ProviderError(format!("IANA lists no RDAP server for {}", target)))?;
+    // Servers are listed with a trailing slash, but not all of them.
+    Ok(format!("{}/", server.trim_end_matches('/')))
+}
+
+/// Reads the registry from the on-disk cache when it is fresh, else fetches
+/// it from IANA and refreshes the cache.
+async fn load_bootstrap(registry: Registry) -> Result<Bootstrap, OsintError> {
+    let path = cache_path(registry);
+    let fresh = fs::metadata(&path).and_then(|metadata| metadata.modified()).is_ok_and(|modified| SystemTime::now().duration_since(modified).is_ok_and(|age| age < BOOTSTRAP_MAX_AGE));
+    if fresh {
+        if let Ok(body) = fs::read_to_string(&path).map_err(OsintError::from).and_then(|text| Ok(serde_json::from_str(&text)?)) {
+            debug!(path = %path.display(), "using cached RDAP bootstrap");
+            return Ok(Bootstrap::parse(&body));
+        }
+    }
+
+    let url = format!("{}/{}", BOOTSTRAP_BASE_URL, registry.file());
+    let body = crate::fetch_with_retries(&url, HeaderMap::new()).await?;
+    if let Err(err) = fs::write(&path, &body) {
+        warn!(path = %path.display(), %err, "could not cache the RDAP bootstrap");
+    }
+    Ok(Bootstrap::parse(&serde_json::from_str(&body)?))
+}
+
+fn cache_path(registry: Registry) -> PathBuf {
+    env::temp_dir().join(format!("osint-recon-tool-rdap-{}", registry.file()))
+}
+
+async fn get(url: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let request = client.get(url).header("Accept", "application/rdap+json").build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    match response.status() {
+        status if status.is_success() => Ok(response.json().await?),
+        StatusCode::TOO_MANY_REQUESTS => Err(OsintError::RetriesExhausted(url.to_string())),
+        status => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+}
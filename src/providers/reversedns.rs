@@ -0,0 +1,75 @@
+use std::net::IpAddr;
+
+use futures::stream::{self, StreamExt};
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::{Name, TokioAsyncResolver};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::dns;
+use crate::OsintError;
+
+/// Forward lookups in flight at once when an IP has many PTR names.
+const FORWARD_LOOKUP_CONCURRENCY: usize = 8;
+
+#[derive(Serialize, Debug)]
+pub struct PtrName {
+    pub hostname: String,
+    pub forward_addresses: Vec<IpAddr>,
+    /// Whether the hostname resolves back to the queried IP.
+    pub forward_confirmed: bool,
+    /// Why the forward lookup failed; the other names are still reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReverseDnsReport {
+    pub ip: IpAddr,
+    pub query: String,
+    pub names: Vec<PtrName>,
+    /// "no PTR record" when the reverse zone has nothing for the IP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+pub async fn fetch_reversedns(target: &str) -> Result<Value, OsintError> {
+    let ip: IpAddr = target.parse().map_err(|_| OsintError::InvalidTarget(format!("{} is not an IP address", target)))?;
+    Ok(serde_json::to_value(reverse_with(&dns::resolver(), ip).await?)?)
+}
+
+/// The PTR lookup of `ip` under `in-addr.arpa`, or `ip6.arpa` by nibble for
+/// IPv6, and the forward check of each name, through `resolver`.
+pub async fn reverse_with(resolver: &TokioAsyncResolver, ip: IpAddr) -> Result<ReverseDnsReport, OsintError> {
+    let hostnames: Vec<String> = match resolver.reverse_lookup(ip).await {
+        Ok(lookup) => lookup.iter().map(|ptr| ptr.to_utf8().trim_end_matches('.').to_string()).collect(),
+        // Many reverse zones are simply never answered, so a timeout means
+        // the same as NXDOMAIN here.
+        Err(err) if dns::is_no_records(&err) || matches!(err.kind(), ResolveErrorKind::Timeout) => {
+            debug!(%ip, %err, "no PTR record");
+            Vec::new()
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let names: Vec<PtrName> = stream::iter(hostnames)
+        .map(|hostname| async move {
+            let (forward_addresses, error): (Vec<IpAddr>, _) = match resolver.lookup_ip(hostname.as_str()).await {
+                Ok(lookup) => (lookup.iter().collect(), None),
+                Err(err) if dns::is_no_records(&err) => (Vec::new(), None),
+                Err(err) => {
+                    warn!(%hostname, %err, "forward lookup failed");
+                    (Vec::new(), Some(err.to_string()))
+                }
+            };
+            let forward_confirmed = forward_addresses.contains(&ip);
+            PtrName { hostname, forward_addresses, forward_confirmed, error }
+        })
+        .buffered(FORWARD_LOOKUP_CONCURRENCY)
+        .collect()
+        .await;
+
+    let note = names.is_empty().then(|| "no PTR record".to_string());
+    Ok(ReverseDnsReport { ip, query: Name::from(ip).to_utf8(), names, note })
+}
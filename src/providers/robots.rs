@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use super::http;
+use crate::OsintError;
+
+/// Sitemap indexes nested deeper than this are not followed.
+const MAX_SITEMAP_DEPTH: usize = 3;
+/// Sitemaps fetched per scan, however many the indexes list.
+const MAX_SITEMAPS: usize = 50;
+/// The protocol caps an uncompressed sitemap at 50 MB.
+const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+/// Path segments that make a disallowed path worth a look.
+const INTERESTING_KEYWORDS: &[&str] = &["admin", "backup", "api", "staging", "dev", "test", "internal", "private", "login", "config", "debug", "old", "tmp", ".git", ".env"];
+
+/// The directives of a robots.txt, across all user-agent groups.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct RobotsTxt {
+    pub user_agents: BTreeSet<String>,
+    pub disallow: BTreeSet<String>,
+    pub allow: BTreeSet<String>,
+    pub sitemaps: Vec<String>,
+    /// Lines that are neither a `field: value` pair nor a comment.
+    pub malformed_lines: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InterestingPath {
+    pub path: String,
+    pub keyword: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SitemapError {
+    pub url: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RobotsReport {
+    pub domain: String,
+    pub robots_found: bool,
+    pub robots: RobotsTxt,
+    /// Disallowed paths naming admin panels, backups, APIs and the like.
+    pub interesting: Vec<InterestingPath>,
+    pub sitemaps_fetched: Vec<String>,
+    /// Sitemaps that failed or were cut short; what they yielded is kept.
+    pub sitemap_errors: Vec<SitemapError>,
+    pub url_count: usize,
+    pub limit_reached: bool,
+    /// Sitemap URLs by first path segment, e.g. `/blog`.
+    pub urls_by_prefix: BTreeMap<String, Vec<String>>,
+}
+
+/// What one sitemap document lists: pages, or further sitemaps.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Sitemap {
+    pub urls: Vec<String>,
+    pub sitemaps: Vec<String>,
+    /// Set when the XML broke off; the entries before the error are kept.
+    pub error: Option<String>,
+}
+
+pub async fn fetch_robots(domain: &str, limit: usize) -> Result<Value, OsintError> {
+    let base = http::target_url(domain)?;
+    let client = crate::client_builder().build()?;
+
+    let robots_url = base.join("/robots.txt").map_err(|err| OsintError::InvalidTarget(format!("{}: {}", domain, err)))?;
+    let (robots_found, robots) = match fetch_capped(&client, &robots_url, http::MAX_BODY_BYTES).await? {
+        Some(body) => (true, parse_robots(&String::from_utf8_lossy(&body))),
+        None => {
+            info!(%robots_url, "no robots.txt served");
+            (false, RobotsTxt::default())
+        }
+    };
+
+    // Without a Sitemap directive, the conventional location is worth a try.
+    let mut pending: VecDeque<(Url, usize)> = robots.sitemaps.iter().filter_map(|sitemap| base.join(sitemap).ok()).map(|url| (url, 0)).collect();
+    if pending.is_empty() {
+        pending.extend(base.join("/sitemap.xml").ok().map(|url| (url, 0)));
+    }
+    let mut seen = BTreeSet::new();
+    let mut urls = BTreeSet::new();
+    let mut sitemaps_fetched = Vec::new();
+    let mut sitemap_errors = Vec::new();
+    let mut limit_reached = false;
+    while let Some((url, depth)) = pending.pop_front() {
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        if sitemaps_fetched.len() >= MAX_SITEMAPS {
+            warn!(limit = MAX_SITEMAPS, "sitemap limit reached, skipping the rest");
+            break;
+        }
+        let body = match fetch_capped(&client, &url, MAX_SITEMAP_BYTES).await {
+            Ok(Some(body)) => body,
+            // The guessed /sitemap.xml is often simply absent.
+            Ok(None) => continue,
+            Err(err) => {
+                sitemap_errors.push(SitemapError { url: url.to_string(), error: err.to_string() });
+                continue;
+            }
+        };
+        sitemaps_fetched.push(url.to_string());
+        let sitemap = match decompress(&body) {
+            Ok(xml) => parse_sitemap(&xml),
+            Err(err) => Sitemap { error: Some(format!("gzip: {}", err)), ..Sitemap::default() },
+        };
+        debug!(%url, urls = sitemap.urls.len(), sitemaps = sitemap.sitemaps.len(), "sitemap parsed");
+        if let Some(error) = sitemap.error {
+            sitemap_errors.push(SitemapError { url: url.to_string(), error });
+        }
+        for child in sitemap.sitemaps.iter().filter_map(|child| url.join(child).ok()) {
+            match depth + 1 {
+                next if next <= MAX_SITEMAP_DEPTH => pending.push_back((child, next)),
+                _ => debug!(%child, "sitemap index nested too deep"),
+            }
+        }
+        for page in sitemap.urls {
+            if urls.len() >= limit {
+                limit_reached = true;
+                break;
+            }
+            urls.insert(page);
+        }
+        if limit_reached {
+            break;
+        }
+    }
+
+    let report = RobotsReport {
+        domain: domain.to_string(),
+        robots_found,
+        interesting: interesting_paths(&robots.disallow),
+        robots,
+        sitemaps_fetched,
+        sitemap_errors,
+        url_count: urls.len(),
+        limit_reached,
+        urls_by_prefix: group_by_prefix(urls),
+    };
+    Ok(serde_json::to_value(report)?)
+}
+
+/// The body of `url`, or `None` for a 404 or 410.
+async fn fetch_capped(client: &Client, url: &Url, limit: usize) -> Result<Option<Vec<u8>>, OsintError> {
+    let response = crate::send_with_retries(client, client.get(url.clone()).build()?).await?;
+    match response.status() {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+        status if status.is_success() => Ok(Some(http::read_capped(response, limit).await?.0)),
+        status => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+}
+
+/// Parses robots.txt leniently: field names in any case, `#` comments, a
+/// byte-order mark and CRLF line ends are all accepted.
+pub fn parse_robots(text: &str) -> RobotsTxt {
+    let mut robots = RobotsTxt::default();
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((field, value)) = line.split_once(':') else {
+            robots.malformed_lines += 1;
+            continue;
+        };
+        let value = value.trim().to_string();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" if !value.is_empty() => {
+                robots.user_agents.insert(value);
+            }
+            // An empty Disallow allows everything, so there is nothing to record.
+            "disallow" if !value.is_empty() => {
+                robots.disallow.insert(value);
+            }
+            "allow" if !value.is_empty() => {
+                robots.allow.insert(value);
+            }
+            "sitemap" if !value.is_empty() && !robots.sitemaps.contains(&value) => robots.sitemaps.push(value),
+            "user-agent" | "disallow" | "allow" | "sitemap" | "crawl-delay" | "host" | "clean-param" => {}
+            other => {
+                debug!(field = other, "unknown robots.txt field");
+            }
+        }
+    }
+    robots
+}
+
+/// Disallowed paths with a word naming something sensitive: `/wp-admin/`
+/// and `/backups.zip` count, `/latest/` does not. Longer keywords also match
+/// as prefixes (`administrator`).
+pub fn interesting_paths(disallow: &BTreeSet<String>) -> Vec<InterestingPath> {
+    disallow
+        .iter()
+        .filter_map(|path| {
+            let lower = path.to_ascii_lowercase();
+            let words: Vec<&str> = lower.split(['/', '*', '?', '$', '-', '_', '.', '=', '&']).filter(|word| !word.is_empty()).collect();
+            let keyword = INTERESTING_KEYWORDS.iter().find(|keyword| {
+                let keyword = keyword.trim_start_matches('.');
+                words.iter().any(|word| *word == keyword || (keyword.len() >= 5 && word.starts_with(keyword)))
+            })?;
+            Some(InterestingPath { path: path.clone(), keyword })
+        })
+        .collect()
+}
+
+/// Gunzips `body` when it starts with the gzip magic bytes, whatever the
+/// URL or Content-Type say.
+pub fn decompress(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    if !body.starts_with(&[0x1f, 0x8b]) {
+        return Ok(body.to_vec());
+    }
+    let mut xml = Vec::new();
+    GzDecoder::new(body).take(MAX_SITEMAP_BYTES as u64).read_to_end(&mut xml)?;
+    Ok(xml)
+}
+
+/// Reads the `<loc>` entries of a `<urlset>` or `<sitemapindex>`, ignoring
+/// namespace prefixes. A parse error or a truncated document ends it early,
+/// keeping the entries completed so far.
+pub fn parse_sitemap(xml: &[u8]) -> Sitemap {
+    let mut reader = Reader::from_reader(xml);
+    reader.config_mut().trim_text(true);
+    let mut sitemap = Sitemap::default();
+    let mut in_index = false;
+    let mut loc: Option<String> = None;
+    let mut closed = false;
+    let mut buffer = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buffer) {
+            Ok(Event::Start(tag)) => match tag.local_name().as_ref() {
+                b"sitemapindex" => in_index = true,
+                b"loc" => loc = Some(String::new()),
+                _ => {}
+            },
+            Ok(Event::End(tag)) => match tag.local_name().as_ref() {
+                b"loc" => push_loc(&mut sitemap, in_index, loc.take().unwrap_or_default().trim()),
+                b"urlset" | b"sitemapindex" => closed = true,
+                _ => {}
+            },
+            Ok(Event::Text(text)) => match (&mut loc, text.unescape()) {
+                (Some(loc), Ok(text)) => loc.push_str(&text),
+                (Some(_), Err(err)) => debug!(%err, "bad entity in sitemap <loc>"),
+                (None, _) => {}
+            },
+            Ok(Event::CData(text)) => {
+                if let Some(loc) = &mut loc {
+                    loc.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Ok(Event::Eof) => {
+                if !closed {
+                    sitemap.error = Some(format!("document ends early at byte {}", reader.buffer_position()));
+                }
+                break;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                sitemap.error = Some(format!("XML error at byte {}: {}", reader.buffer_position(), err));
+                break;
+            }
+        }
+        buffer.clear();
+    }
+    sitemap
+}
+
+fn push_loc(sitemap: &mut Sitemap, in_index: bool, loc: &str) {
+    if loc.is_empty() {
+        return;
+    }
+    match in_index {
+        true => sitemap.sitemaps.push(loc.to_string()),
+        false => sitemap.urls.push(loc.to_string()),
+    }
+}
+
+/// Groups URLs by their first path segment; unparseable URLs are dropped.
+pub fn group_by_prefix(urls: impl IntoIterator<Item = String>) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for url in urls {
+        let Ok(parsed) = Url::parse(&url) else {
+            continue;
+        };
+        let prefix = parsed.path_segments().and_then(|mut segments| segments.next()).filter(|segment| !segment.is_empty()).map_or_else(|| "/".to_string(), |segment| format!("/{}", segment));
+        groups.entry(prefix).or_default().push(url);
+    }
+    groups
+}
@@ -0,0 +1,175 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use chromiumoxide::browser::{Browser, BrowserConfig, HeadlessMode};
+use chromiumoxide::cdp::browser_protocol::page::{CaptureScreenshotFormat, EventLifecycleEvent};
+use chromiumoxide::detection::{self, DetectionOptions};
+use chromiumoxide::page::ScreenshotParams;
+use chromiumoxide::Page;
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use super::http;
+use crate::OsintError;
+
+/// How long a page gets to load before the attempt fails.
+const NAVIGATION_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long to wait, after the load, for the network to go quiet. Pages that
+/// keep polling are captured anyway once it passes.
+const NETWORK_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+const VIEWPORT: (u32, u32) = (1366, 768);
+
+#[derive(Serialize, Debug)]
+pub struct ScreenshotReport {
+    pub target: String,
+    /// The URL that was captured, after redirects.
+    pub url: String,
+    pub path: String,
+    pub bytes: usize,
+    /// False when the page was still busy once the idle wait ran out.
+    pub network_idle: bool,
+    /// Why https failed for a bare host, when the capture fell back to http.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub https_error: Option<String>,
+}
+
+/// Captures the target's landing page with headless Chrome and saves it as
+/// `<target>_screenshot.png`. A bare host is tried over https, then http.
+pub async fn fetch_screenshot(target: &str, insecure: bool) -> Result<Value, OsintError> {
+    let (png, report) = capture(target, insecure).await?;
+    fs::write(&report.path, &png)?;
+    info!(path = %report.path, bytes = png.len(), "screenshot saved");
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Runs Chrome over the target URLs until one can be captured.
+async fn capture(target: &str, insecure: bool) -> Result<(Vec<u8>, ScreenshotReport), OsintError> {
+    let urls = http::target_urls(target)?;
+    let executable = chrome_executable(env::var_os("CHROME_PATH").map(PathBuf::from))?;
+    let mut config = BrowserConfig::builder().chrome_executable(&executable).headless_mode(HeadlessMode::New).window_size(VIEWPORT.0, VIEWPORT.1).viewport(None).request_timeout(NAVIGATION_TIMEOUT);
+    if !insecure {
+        config = config.respect_https_errors();
+    }
+    if let Some(proxy) = crate::proxy() {
+        config = config.arg(format!("--proxy-server={}", proxy));
+    }
+    if running_as_root() {
+        // Chrome refuses to start its sandbox as root, as in most containers.
+        config = config.no_sandbox();
+    }
+    let config = config.build().map_err(OsintError::Browser)?;
+
+    info!(chrome = %executable.display(), "launching headless Chrome");
+    let (mut browser, mut handler) = Browser::launch(config).await.map_err(|err| OsintError::Browser(format!("could not start {}: {}", executable.display(), err)))?;
+    let events = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    let mut errors = Vec::new();
+    let mut captured = None;
+    for url in &urls {
+        match capture_url(&browser, url).await {
+            Ok(result) => {
+                captured = Some(result);
+                break;
+            }
+            Err(err) => {
+                warn!(%url, %err, "page capture failed");
+                errors.push((url.scheme().to_string(), err));
+            }
+        }
+    }
+    if let Err(err) = browser.close().await {
+        debug!(%err, "closing Chrome failed");
+    }
+    let _ = browser.wait().await;
+    events.abort();
+
+    let Some((png, final_url, network_idle)) = captured else {
+        return Err(match errors.len() {
+            1 => errors.remove(0).1,
+            _ => OsintError::Connection(errors.iter().map(|(scheme, err)| format!("{}: {}", scheme, err)).collect::<Vec<_>>().join("; ")),
+        });
+    };
+    let https_error = errors.into_iter().find(|(scheme, _)| scheme == "https").map(|(_, err)| err.to_string());
+    let report = ScreenshotReport {
+        target: target.to_string(),
+        url: final_url,
+        path: crate::output_path(&format!("{}_screenshot.png", target.replace(['/', ':'], "_"))).display().to_string(),
+        bytes: png.len(),
+        network_idle,
+        https_error,
+    };
+    Ok((png, report))
+}
+
+/// Loads `url` in a fresh tab and captures the whole page.
+async fn capture_url(browser: &Browser, url: &Url) -> Result<(Vec<u8>, String, bool), OsintError> {
+    let page = browser.new_page("about:blank").await.map_err(|err| OsintError::Browser(err.to_string()))?;
+    let result = load_and_capture(&page, url).await;
+    if let Err(err) = page.close().await {
+        debug!(%err, "closing the tab failed");
+    }
+    result
+}
+
+async fn load_and_capture(page: &Page, url: &Url) -> Result<(Vec<u8>, String, bool), OsintError> {
+    let mut lifecycle = page.event_listener::<EventLifecycleEvent>().await.map_err(|err| OsintError::Browser(err.to_string()))?;
+    info!(%url, "loading page");
+    match timeout(NAVIGATION_TIMEOUT, page.goto(url.as_str())).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => return Err(navigation_error(url, &err.to_string())),
+        Err(_) => return Err(OsintError::Connection(format!("{} did not load within {}s", url, NAVIGATION_TIMEOUT.as_secs()))),
+    }
+    let network_idle = timeout(NETWORK_IDLE_TIMEOUT, async {
+        while let Some(event) = lifecycle.next().await {
+            if event.name == "networkIdle" {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    if !network_idle {
+        debug!(%url, "network still busy, capturing anyway");
+    }
+    let final_url = page.url().await.ok().flatten().unwrap_or_else(|| url.to_string());
+    let png = page.screenshot(ScreenshotParams::builder().format(CaptureScreenshotFormat::Png).full_page(true).build()).await.map_err(|err| OsintError::Browser(format!("screenshot of {} failed: {}", url, err)))?;
+    Ok((png, final_url, network_idle))
+}
+
+/// Chrome reports failed loads as `net::ERR_*` codes.
+fn navigation_error(url: &Url, message: &str) -> OsintError {
+    match message {
+        message if message.contains("ERR_CERT") => OsintError::Connection(format!("{} has an untrusted certificate ({}); pass --screenshot-insecure to capture it anyway", url, message)),
+        message => OsintError::Connection(format!("could not load {}: {}", url, message)),
+    }
+}
+
+/// `CHROME_PATH` when set, otherwise the first Chrome or Chromium found in
+/// the usual places.
+pub fn chrome_executable(override_path: Option<PathBuf>) -> Result<PathBuf, OsintError> {
+    match override_path {
+        Some(path) if path.is_file() => Ok(path),
+        Some(path) => Err(OsintError::Browser(format!("CHROME_PATH is {}, which is not a file", path.display()))),
+        None => detection::default_executable(DetectionOptions::default()).map_err(|err| {
+            debug!(%err, "Chrome detection failed");
+            OsintError::Browser("no Chrome or Chromium binary found; install one or set CHROME_PATH to its executable".to_string())
+        }),
+    }
+}
+
+#[cfg(unix)]
+fn running_as_root() -> bool {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata("/proc/self").is_ok_and(|proc| proc.uid() == 0)
+}
+
+#[cfg(not(unix))]
+fn running_as_root() -> bool {
+    false
+}
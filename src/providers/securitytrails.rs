@@ -0,0 +1,68 @@
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+use crate::{OsintError, RetryPolicy};
+
+const SECURITYTRAILS_BASE_URL: &str = "https://api.securitytrails.com/v1";
+
+pub async fn fetch_securitytrails(domain: &str) -> Result<Value, OsintError> {
+    let key = crate::api_key("SECURITYTRAILS_API_KEY")?;
+    lookup_from(SECURITYTRAILS_BASE_URL, &key, domain).await
+}
+
+/// The three lookups against `base_url`, so tests can point them at a mock
+/// server.
+pub async fn lookup_from(base_url: &str, key: &str, domain: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let (current, history, subdomains) = tokio::try_join!(
+        get(&client, key, format!("{}/domain/{}", base_url, domain)),
+        get(&client, key, format!("{}/history/{}/dns/a", base_url, domain)),
+        get(&client, key, format!("{}/domain/{}/subdomains", base_url, domain)),
+    )?;
+
+    let mut a_history: Vec<Value> = history["records"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|record| {
+            let ips: Vec<&Value> = record["values"].as_array().into_iter().flatten().map(|value| &value["ip"]).collect();
+            json!({
+                "first_seen": record["first_seen"],
+                "last_seen": record["last_seen"],
+                "ips": ips,
+                "organizations": record["organizations"],
+            })
+        })
+        .collect();
+    // The API lists newest first; ISO dates sort chronologically as strings.
+    a_history.sort_by(|a, b| a["first_seen"].as_str().cmp(&b["first_seen"].as_str()));
+
+    let subdomains: Vec<String> = subdomains["subdomains"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|label| format!("{}.{}", label, domain))
+        .collect();
+
+    Ok(json!({
+        "domain": domain,
+        "current_dns": current["current_dns"],
+        "a_history": a_history,
+        "subdomains": subdomains,
+    }))
+}
+
+async fn get(client: &Client, key: &str, url: String) -> Result<Value, OsintError> {
+    let request = client.get(&url).header("APIKEY", key).build()?;
+    // A 429 here means the monthly quota is spent, so retrying only burns time.
+    let response = crate::send_with_policy(client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(OsintError::QuotaExhausted("SecurityTrails API quota is used up for this billing period".to_string()));
+    }
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url });
+    }
+    Ok(response.json().await?)
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::target::{self, TargetKind};
+use crate::OsintError;
+
+const SERPAPI_URL: &str = "https://serpapi.com/search.json";
+/// Where the target goes in a template.
+pub const PLACEHOLDER: &str = "{{target}}";
+/// Results kept per dork without `--max-results`.
+const DEFAULT_MAX_RESULTS: usize = 10;
+const DOMAIN_TEMPLATES: &[&str] = &[
+    "site:{{target}} filetype:pdf",
+    "site:{{target}} (filetype:xls OR filetype:xlsx OR filetype:doc OR filetype:docx)",
+    "site:{{target}} inurl:login OR inurl:admin",
+    "site:{{target}} intitle:\"index of\"",
+    "site:pastebin.com \"{{target}}\"",
+    "\"@{{target}}\" -site:{{target}}",
+];
+const EMAIL_TEMPLATES: &[&str] = &["\"{{target}}\"", "site:pastebin.com \"{{target}}\"", "\"{{target}}\" filetype:pdf OR filetype:xls OR filetype:txt"];
+
+static EXTRA_TEMPLATES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Adds the templates of the config file's `dorks` setting to the built-in
+/// ones.
+pub fn use_extra_templates(templates: Vec<String>) {
+    let _ = EXTRA_TEMPLATES.set(templates);
+}
+
+/// The dorks run for `target`: those of `file` when given, one per line
+/// with `#` comments, otherwise the built-in ones for an email or a domain
+/// followed by the configured ones.
+pub fn templates(target: &str, file: Option<&Path>) -> Result<Vec<String>, OsintError> {
+    let Some(path) = file else {
+        let builtin = match target::classify(target) {
+            TargetKind::Email => EMAIL_TEMPLATES,
+            _ => DOMAIN_TEMPLATES,
+        };
+        return Ok(builtin.iter().map(|template| template.to_string()).chain(EXTRA_TEMPLATES.get().into_iter().flatten().cloned()).collect());
+    };
+    let text = fs::read_to_string(path)?;
+    let templates: Vec<String> = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(str::to_string).collect();
+    if let Some(template) = templates.iter().find(|template| !template.contains(PLACEHOLDER)) {
+        return Err(OsintError::Config(format!("{}: dork template {:?} has no {}", path.display(), template, PLACEHOLDER)));
+    }
+    if templates.is_empty() {
+        return Err(OsintError::Config(format!("{} holds no dork templates", path.display())));
+    }
+    Ok(templates)
+}
+
+/// `template` with every placeholder replaced by `target`.
+pub fn expand(template: &str, target: &str) -> String {
+    template.replace(PLACEHOLDER, target)
+}
+
+pub async fn fetch_dork(target: &str, file: Option<&Path>, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("SERPAPI_KEY")?;
+    search_from(SERPAPI_URL, &key, target, &templates(target, file)?, max_results.unwrap_or(DEFAULT_MAX_RESULTS)).await
+}
+
+/// Runs each of `templates` for `target` through Google via SerpAPI at
+/// `url`, so tests can point it at a mock server, keeping the top
+/// `max_results` of each. Every dork is one search off the plan, so they go
+/// one at a time and the first quota error ends the run.
+pub async fn search_from(url: &str, key: &str, target: &str, templates: &[String], max_results: usize) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut dorks = Vec::with_capacity(templates.len());
+    for template in templates {
+        let query = expand(template, target);
+        let request = client.get(url).query(&[("engine", "google"), ("q", query.as_str()), ("num", &max_results.to_string()), ("api_key", key)]).build()?;
+        let response = crate::send_with_policy(&client, request, crate::RetryPolicy::SINGLE_ATTEMPT).await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+        let error = body["error"].as_str().unwrap_or_default();
+        let lowered = error.to_ascii_lowercase();
+        if status == StatusCode::TOO_MANY_REQUESTS || lowered.contains("run out of searches") || lowered.contains("searches for the month") {
+            warn!(target, done = dorks.len(), left = templates.len() - dorks.len(), "SerpAPI searches exhausted");
+            return Err(OsintError::QuotaExhausted(format!("SerpAPI: {}", if error.is_empty() { "searches exhausted" } else { error })));
+        }
+        // Google having nothing for a dork is an answer, not a failure.
+        let no_results = lowered.contains("hasn't returned any results");
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::InvalidCredentials(format!("SERPAPI_KEY ({})", if error.is_empty() { status.as_str() } else { error }))),
+            _ if no_results => {}
+            status if !status.is_success() => return Err(OsintError::ApiStatus { status, url: url.to_string() }),
+            _ if !error.is_empty() => return Err(OsintError::ProviderError(format!("SerpAPI: {}", error))),
+            _ => {}
+        }
+        let results: Vec<Value> = body["organic_results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(max_results)
+            .map(|result| json!({ "title": result["title"], "url": result["link"], "snippet": result["snippet"] }))
+            .collect();
+        dorks.push(json!({
+            "template": template,
+            "query": query,
+            "total_results": body["search_information"]["total_results"],
+            "count": results.len(),
+            "results": results,
+        }));
+    }
+    let with_results = dorks.iter().filter(|dork| dork["count"] != 0).count();
+    info!(target, dorks = dorks.len(), with_results, "dorks searched");
+    Ok(json!({
+        "target": target,
+        "engine": "google",
+        "searched": dorks.len(),
+        "with_results": with_results,
+        "dorks": dorks,
+    }))
+}
+
+/// Each dork with its results' titles and URLs, for the console.
+pub fn listing(data: &Value) -> String {
+    let mut lines = Vec::new();
+    for dork in data["dorks"].as_array().into_iter().flatten() {
+        lines.push(format!("{} ({} results)", dork["query"].as_str().unwrap_or_default(), dork["count"]));
+        for result in dork["results"].as_array().into_iter().flatten() {
+            lines.push(format!("  {} - {}", result["title"].as_str().unwrap_or_default(), result["url"].as_str().unwrap_or_default()));
+        }
+    }
+    lines.join("\n")
+}
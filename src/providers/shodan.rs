@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::fs;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::paginate::paginate;
+use crate::ratelimit::RateLimiter;
+use crate::{logging, OsintError};
+
+/// Shodan returns search results in pages of this many matches.
+const PAGE_SIZE: usize = 100;
+/// Shodan's API allows one request a second.
+const SEARCH_REQUESTS_PER_MINUTE: u32 = 60;
+/// Longest comma-joined list sent in one `/dns` request, well inside the
+/// URL length Shodan's servers accept.
+const MAX_LIST_CHARS: usize = 1500;
+
+/// A host from a Shodan search.
+#[derive(Serialize, Debug)]
+pub struct ShodanMatch {
+    pub ip: String,
+    pub port: Option<u64>,
+    pub hostnames: Vec<String>,
+    pub org: Option<String>,
+    pub product: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchReport {
+    pub query: String,
+    /// All hosts Shodan has for the query, however many pages were read.
+    pub total: u64,
+    pub pages_fetched: u32,
+    /// Whether `--max-results` left matches unread.
+    pub capped: bool,
+    pub matches: Vec<ShodanMatch>,
+}
+
+/// Runs `query` (Shodan search syntax) reading up to `max_pages` pages, and
+/// no more than it takes to collect `max_results` matches. Every page of a
+/// filtered search costs a query credit.
+pub async fn fetch_shodan_search(query: &str, max_pages: u32, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("SHODAN_API_KEY")?;
+    Ok(serde_json::to_value(search(crate::SHODAN_BASE_URL, &key, query, max_pages, max_results).await?)?)
+}
+
+/// The search against `base_url`, so tests can point it at a mock server.
+pub async fn search(base_url: &str, key: &str, query: &str, max_pages: u32, max_results: Option<usize>) -> Result<SearchReport, OsintError> {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    let limiter = LIMITER.get_or_init(|| RateLimiter::per_minute(SEARCH_REQUESTS_PER_MINUTE));
+    let client = crate::client_builder().build()?;
+    let fetch = |page| {
+        let client = &client;
+        async move {
+            limiter.acquire().await;
+            search_page(client, base_url, key, query, page).await
+        }
+    };
+    let results = paginate(1, max_results, fetch, |body: &Value, &page: &u32, before| {
+        let total = body["total"].as_u64().unwrap_or_default();
+        let matches = parse_matches(body);
+        debug!(page, total, fetched = before + matches.len(), "shodan search page read");
+        let more = matches.len() >= PAGE_SIZE && ((before + matches.len()) as u64) < total && page < max_pages;
+        (matches, more.then_some(page + 1))
+    })
+    .await?;
+    let report = SearchReport { query: query.to_string(), total: results.first["total"].as_u64().unwrap_or_default(), pages_fetched: results.pages, capped: results.capped, matches: results.items };
+    info!(query, total = report.total, matches = report.matches.len(), "shodan search done");
+    Ok(report)
+}
+
+/// One hostname-to-IP pairing, as the report's entity lists hold them.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DnsEntity {
+    pub hostname: String,
+    pub ip: String,
+    pub source: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DnsReport {
+    pub entities: Vec<DnsEntity>,
+    /// Inputs Shodan had no answer for.
+    pub unresolved: Vec<String>,
+}
+
+/// Maps the hostnames in `input` to IPs and the IPs to hostnames. `input` is
+/// a comma-separated list, or `@path` to a file with one entry per line.
+pub async fn fetch_shodan_dns(input: &str) -> Result<Value, OsintError> {
+    let key = crate::api_key("SHODAN_API_KEY")?;
+    let entries = parse_input(input)?;
+    let (ips, hostnames): (Vec<String>, Vec<String>) = entries.into_iter().partition(|entry| entry.parse::<IpAddr>().is_ok());
+    let mut report = resolve_from(crate::SHODAN_BASE_URL, &key, &hostnames).await?;
+    let reverse = reverse_from(crate::SHODAN_BASE_URL, &key, &ips).await?;
+    report.entities.extend(reverse.entities);
+    report.unresolved.extend(reverse.unresolved);
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Splits the `shodan-dns` input, dropping blanks, `#` comments and repeats.
+pub fn parse_input(input: &str) -> Result<Vec<String>, OsintError> {
+    let text = match input.strip_prefix('@') {
+        Some(path) => fs::read_to_string(path)?,
+        None => input.replace(',', "\n"),
+    };
+    let mut seen = HashSet::new();
+    let entries: Vec<String> = text.lines().map(|line| line.split('#').next().unwrap_or_default().trim().trim_end_matches('.').to_ascii_lowercase()).filter(|entry| !entry.is_empty() && seen.insert(entry.clone())).collect();
+    if entries.is_empty() {
+        return Err(OsintError::InvalidTarget(format!("no hostnames or IPs in {}", input)));
+    }
+    Ok(entries)
+}
+
+/// `/dns/resolve` for `hostnames`, in as many requests as the URL limit needs.
+pub async fn resolve_from(base_url: &str, key: &str, hostnames: &[String]) -> Result<DnsReport, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut report = DnsReport { entities: Vec::new(), unresolved: Vec::new() };
+    for chunk in chunk_list(hostnames, MAX_LIST_CHARS) {
+        let body = dns_request(&client, base_url, "resolve", "hostnames", key, &chunk).await?;
+        for hostname in chunk {
+            match body[&hostname].as_str() {
+                Some(ip) => report.entities.push(DnsEntity { hostname, ip: ip.to_string(), source: "shodan" }),
+                None => report.unresolved.push(hostname),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// `/dns/reverse` for `ips`; an IP can map to several hostnames.
+pub async fn reverse_from(base_url: &str, key: &str, ips: &[String]) -> Result<DnsReport, OsintError> {
+    let client = crate::client_builder().build()?;
+    let mut report = DnsReport { entities: Vec::new(), unresolved: Vec::new() };
+    for chunk in chunk_list(ips, MAX_LIST_CHARS) {
+        let body = dns_request(&client, base_url, "reverse", "ips", key, &chunk).await?;
+        for ip in chunk {
+            let hostnames: Vec<&str> = body[&ip].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            if hostnames.is_empty() {
+                report.unresolved.push(ip);
+                continue;
+            }
+            report.entities.extend(hostnames.into_iter().map(|hostname| DnsEntity { hostname: hostname.to_string(), ip: ip.clone(), source: "shodan" }));
+        }
+    }
+    Ok(report)
+}
+
+async fn dns_request(client: &Client, base_url: &str, endpoint: &str, parameter: &str, key: &str, items: &[String]) -> Result<Value, OsintError> {
+    let url = Url::parse_with_params(&format!("{}/dns/{}", base_url, endpoint), [(parameter, items.join(",").as_str()), ("key", key)]).map_err(|err| OsintError::InvalidTarget(err.to_string()))?;
+    debug!(endpoint, count = items.len(), "shodan dns request");
+    let response = crate::send_with_retries(client, client.get(url.clone()).build()?).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    match status.is_success() {
+        true => Ok(body),
+        false => Err(api_error(status, &body, &url)),
+    }
+}
+
+/// Groups `items` so each comma-joined group stays within `limit` characters.
+/// An item longer than `limit` still gets a group of its own.
+pub fn chunk_list(items: &[String], limit: usize) -> Vec<Vec<String>> {
+    let mut chunks: Vec<Vec<String>> = Vec::new();
+    let mut length = 0;
+    for item in items {
+        match chunks.last_mut() {
+            Some(chunk) if length + 1 + item.len() <= limit => {
+                length += 1 + item.len();
+                chunk.push(item.clone());
+            }
+            _ => {
+                length = item.len();
+                chunks.push(vec![item.clone()]);
+            }
+        }
+    }
+    chunks
+}
+
+async fn search_page(client: &Client, base_url: &str, key: &str, query: &str, page: u32) -> Result<Value, OsintError> {
+    let url = Url::parse_with_params(&format!("{}/shodan/host/search", base_url), [("key", key), ("query", query), ("page", &page.to_string())]).map_err(|err| OsintError::InvalidTarget(err.to_string()))?;
+    let response = crate::send_with_retries(client, client.get(url.clone()).build()?).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    match status.is_success() {
+        true => Ok(body),
+        false => Err(api_error(status, &body, &url)),
+    }
+}
+
+/// Shodan explains failures in an `error` field; running out of query
+/// credits gets a message of its own.
+fn api_error(status: StatusCode, body: &Value, url: &Url) -> OsintError {
+    let safe_url = logging::redact_url(url.as_str());
+    match body["error"].as_str() {
+        Some(message) if message.to_ascii_lowercase().contains("query credits") => OsintError::QuotaExhausted(format!("Shodan query credits exhausted: {}", message)),
+        Some(message) if status == StatusCode::UNAUTHORIZED => OsintError::InvalidCredentials(format!("SHODAN_API_KEY: {}", message)),
+        Some(message) => OsintError::ProviderError(format!("Shodan: {}", message)),
+        None if status == StatusCode::TOO_MANY_REQUESTS => OsintError::RetriesExhausted(safe_url),
+        None => OsintError::ApiStatus { status, url: safe_url },
+    }
+}
+
+/// The hosts of one page of search results, skipping any without an IP.
+pub fn parse_matches(body: &Value) -> Vec<ShodanMatch> {
+    body["matches"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|host| {
+            let text = |key: &str| host[key].as_str().map(str::to_string);
+            Some(ShodanMatch {
+                ip: text("ip_str")?,
+                port: host["port"].as_u64(),
+                hostnames: host["hostnames"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect(),
+                org: text("org"),
+                product: text("product"),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use hickory_resolver::proto::rr::RData;
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::dns;
+use crate::OsintError;
+
+const BUILTIN_WORDLIST: &str = include_str!("subbrute_words.txt");
+/// Random labels resolved up front to detect wildcard DNS.
+const WILDCARD_PROBES: usize = 3;
+/// How long one lookup, retries included, may take.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Words from `path`, or the built-in list: one per line, `#` comments and
+/// blank lines ignored, duplicates dropped.
+pub fn load_wordlist(path: Option<&Path>) -> Result<Vec<String>, OsintError> {
+    let text = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => BUILTIN_WORDLIST.to_string(),
+    };
+    let mut seen = BTreeSet::new();
+    Ok(text
+        .lines()
+        .map(|line| line.trim().trim_matches('.').to_ascii_lowercase())
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .filter(|word| seen.insert(word.clone()))
+        .collect())
+}
+
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub addresses: BTreeSet<String>,
+    pub cnames: BTreeSet<String>,
+}
+
+impl Answers {
+    fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.cnames.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Host {
+    pub name: String,
+    #[serde(flatten)]
+    pub answers: Answers,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Wildcard {
+    pub detected: bool,
+    /// What random labels resolve to; hosts answering only this are dropped.
+    pub answers: Answers,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SubbruteReport {
+    pub domain: String,
+    pub wildcard: Wildcard,
+    pub tried: usize,
+    /// Lookups that failed with something other than "no such name".
+    pub errors: usize,
+    pub hosts: Vec<Host>,
+}
+
+pub async fn fetch_subbrute(domain: &str, words: Vec<String>, concurrency: usize) -> Result<Value, OsintError> {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let names: Vec<String> = words.iter().map(|word| format!("{}.{}", word, domain)).collect();
+    let mut resolution = resolve_hosts(&dns::resolver(), names, concurrency, LOOKUP_TIMEOUT).await;
+    let wildcard = resolution.wildcards.remove(&domain).unwrap_or_default();
+    let report = SubbruteReport {
+        domain,
+        wildcard: Wildcard { detected: !wildcard.is_empty(), answers: wildcard },
+        tried: resolution.tried,
+        errors: resolution.errors + resolution.timed_out,
+        hosts: resolution.hosts,
+    };
+    Ok(serde_json::to_value(report)?)
+}
+
+/// What `resolve_hosts` found.
+#[derive(Serialize, Debug)]
+pub struct Resolution {
+    pub tried: usize,
+    /// Lookups that failed with something other than "no such name".
+    pub errors: usize,
+    /// Lookups still unanswered after the timeout.
+    pub timed_out: usize,
+    /// The parent domains whose random labels resolve, and to what.
+    pub wildcards: BTreeMap<String, Answers>,
+    /// The names that resolve, less those only a wildcard answers.
+    pub hosts: Vec<Host>,
+}
+
+/// `--resolve` for crt.sh: which of its subdomains resolve, through the
+/// shared resolver.
+pub async fn resolve_names(names: Vec<String>, concurrency: usize) -> Result<Value, OsintError> {
+    Ok(serde_json::to_value(resolve_hosts(&dns::resolver(), names, concurrency, LOOKUP_TIMEOUT).await)?)
+}
+
+/// Resolves `names`, `concurrency` at a time, each lookup given up after
+/// `timeout`. Random labels under each name's parent domain are resolved
+/// first, and a host that answers only what they answer is dropped as the
+/// work of wildcard DNS.
+pub async fn resolve_hosts(resolver: &TokioAsyncResolver, names: Vec<String>, concurrency: usize, timeout: Duration) -> Resolution {
+    let concurrency = concurrency.max(1);
+    let parents: BTreeSet<String> = names.iter().filter_map(|name| name.split_once('.').map(|(_, parent)| parent.to_string())).filter(|parent| parent.contains('.')).collect();
+    let wildcards: BTreeMap<String, Answers> = stream::iter(parents)
+        .map(|parent| async move {
+            let mut answers = Answers::default();
+            for _ in 0..WILDCARD_PROBES {
+                let probe = format!("{}.{}", &Uuid::new_v4().simple().to_string()[..16], parent);
+                if let Ok(Ok(found)) = time::timeout(timeout, resolve(resolver, &probe)).await {
+                    answers.addresses.extend(found.addresses);
+                    answers.cnames.extend(found.cnames);
+                }
+            }
+            (parent, answers)
+        })
+        .buffer_unordered(concurrency)
+        .filter(|(_, answers)| futures::future::ready(!answers.is_empty()))
+        .collect()
+        .await;
+    for (parent, answers) in &wildcards {
+        warn!(%parent, addresses = ?answers.addresses, "wildcard DNS detected, filtering matching answers");
+    }
+
+    let total = names.len();
+    // Roughly ten progress lines however long the list is.
+    let progress_step = (total / 10).max(1);
+    let mut resolution = Resolution { tried: total, errors: 0, timed_out: 0, wildcards: BTreeMap::new(), hosts: Vec::new() };
+    let mut done = 0;
+    let mut lookups = stream::iter(names)
+        .map(|name| async move {
+            let result = time::timeout(timeout, resolve(resolver, &name)).await;
+            (name, result)
+        })
+        .buffer_unordered(concurrency);
+    while let Some((name, result)) = lookups.next().await {
+        done += 1;
+        if done % progress_step == 0 || done == total {
+            info!(done, total, found = resolution.hosts.len(), "resolution progress");
+        }
+        let wildcard = name.split_once('.').and_then(|(_, parent)| wildcards.get(parent));
+        match result {
+            Ok(Ok(answers)) if answers.is_empty() || wildcard.is_some_and(|wildcard| is_wildcard_answer(&answers, wildcard)) => {}
+            Ok(Ok(answers)) => resolution.hosts.push(Host { name, answers }),
+            Ok(Err(err)) => {
+                debug!(%name, %err, "lookup failed");
+                resolution.errors += 1;
+            }
+            Err(_) => {
+                debug!(%name, "lookup timed out");
+                resolution.timed_out += 1;
+            }
+        }
+    }
+    drop(lookups);
+    resolution.hosts.sort_by(|a, b| a.name.cmp(&b.name));
+    resolution.wildcards = wildcards;
+    resolution
+}
+
+/// A host whose every answer also came back for a random label only exists
+/// because of the wildcard.
+pub fn is_wildcard_answer(answers: &Answers, wildcard: &Answers) -> bool {
+    !wildcard.is_empty() && answers.addresses.is_subset(&wildcard.addresses) && answers.cnames.is_subset(&wildcard.cnames)
+}
+
+/// A, AAAA and CNAME answers for `name`; a name that does not exist has none.
+async fn resolve(resolver: &TokioAsyncResolver, name: &str) -> Result<Answers, OsintError> {
+    let lookup = match resolver.lookup_ip(name).await {
+        Ok(lookup) => lookup,
+        Err(err) if dns::is_no_records(&err) => return Ok(Answers::default()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut answers = Answers::default();
+    for record in lookup.as_lookup().records() {
+        match record.data() {
+            Some(RData::A(a)) => answers.addresses.insert(a.0.to_string()),
+            Some(RData::AAAA(aaaa)) => answers.addresses.insert(aaaa.0.to_string()),
+            Some(RData::CNAME(cname)) => answers.cnames.insert(cname.0.to_utf8().trim_end_matches('.').to_string()),
+            _ => false,
+        };
+    }
+    Ok(answers)
+}
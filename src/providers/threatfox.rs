@@ -0,0 +1,107 @@
+use std::env;
+
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::target::{self, TargetKind};
+use crate::OsintError;
+
+const THREATFOX_URL: &str = "https://threatfox-api.abuse.ch/api/v1/";
+const MALWAREBAZAAR_URL: &str = "https://mb-api.abuse.ch/api/v1/";
+
+/// ThreatFox's IOCs for an IP or domain, or MalwareBazaar's samples for a
+/// file hash. abuse.ch asks for `ABUSECH_AUTH_KEY` but it is optional here.
+pub async fn fetch_threatfox(target: &str) -> Result<Value, OsintError> {
+    let key = env::var("ABUSECH_AUTH_KEY").ok().filter(|key| !key.is_empty());
+    lookup_from(THREATFOX_URL, MALWAREBAZAAR_URL, key.as_deref(), target).await
+}
+
+/// The lookup against the given bases, so tests can point them at a mock
+/// server; a hash goes to MalwareBazaar, anything else to ThreatFox.
+pub async fn lookup_from(threatfox_url: &str, malwarebazaar_url: &str, key: Option<&str>, target: &str) -> Result<Value, OsintError> {
+    let client = crate::client_builder().build()?;
+    let with_key = |request: RequestBuilder| match key {
+        Some(key) => request.header("Auth-Key", key),
+        None => request,
+    };
+    let (service, url, request) = match target::classify(target) {
+        TargetKind::Hash => ("malwarebazaar", malwarebazaar_url, with_key(client.post(malwarebazaar_url).form(&[("query", "get_info"), ("hash", target)]))),
+        _ => ("threatfox", threatfox_url, with_key(client.post(threatfox_url).json(&json!({ "query": "search_ioc", "search_term": target })))),
+    };
+    let response = crate::send_with_retries(&client, request.build()?).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN if key.is_some() => return Err(OsintError::InvalidCredentials("ABUSECH_AUTH_KEY".to_string())),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(OsintError::MissingApiKey("ABUSECH_AUTH_KEY".to_string())),
+        status => return Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+    let body: Value = response.json().await?;
+    let matches = match service {
+        "malwarebazaar" => parse_samples(&body)?,
+        _ => parse_iocs(&body)?,
+    };
+    info!(service, matches = matches.len(), "abuse.ch lookup done");
+    Ok(json!({ "target": target, "service": service, "listed": !matches.is_empty(), "matches": matches }))
+}
+
+/// A `query_status` other than `ok` either means nothing is known, which is
+/// a clean result, or the query was refused.
+fn check_status(service: &str, body: &Value, not_found: &[&str]) -> Result<bool, OsintError> {
+    match body["query_status"].as_str().unwrap_or("unknown") {
+        "ok" => Ok(true),
+        status if not_found.contains(&status) => Ok(false),
+        status @ ("illegal_search_term" | "illegal_hash" | "invalid_hash") => Err(OsintError::InvalidTarget(format!("{} rejected the search term ({})", service, status))),
+        status @ ("unknown_auth_key" | "wrong_auth_key") => Err(OsintError::InvalidCredentials(format!("ABUSECH_AUTH_KEY ({})", status))),
+        status => Err(OsintError::ProviderError(format!("{}: {}", service, status))),
+    }
+}
+
+/// Reads a ThreatFox `search_ioc` answer.
+pub fn parse_iocs(body: &Value) -> Result<Vec<Value>, OsintError> {
+    if !check_status("ThreatFox", body, &["no_result"])? {
+        return Ok(Vec::new());
+    }
+    Ok(body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|ioc| {
+            json!({
+                "ioc": ioc["ioc"],
+                "ioc_type": ioc["ioc_type"],
+                "threat_type": ioc["threat_type"],
+                "family": ioc["malware_printable"].as_str().or(ioc["malware"].as_str()),
+                "confidence": ioc["confidence_level"],
+                "first_seen": ioc["first_seen"],
+                "last_seen": ioc["last_seen"],
+                "tags": ioc["tags"].as_array().cloned().unwrap_or_default(),
+                "reference": ioc["reference"].as_str().filter(|reference| !reference.is_empty()),
+            })
+        })
+        .collect())
+}
+
+/// Reads a MalwareBazaar `get_info` answer.
+pub fn parse_samples(body: &Value) -> Result<Vec<Value>, OsintError> {
+    if !check_status("MalwareBazaar", body, &["hash_not_found", "no_results"])? {
+        return Ok(Vec::new());
+    }
+    Ok(body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|sample| {
+            json!({
+                "sha256": sample["sha256_hash"],
+                "file_name": sample["file_name"],
+                "file_type": sample["file_type"],
+                "family": sample["signature"],
+                "confidence": Value::Null,
+                "first_seen": sample["first_seen"],
+                "last_seen": sample["last_seen"],
+                "tags": sample["tags"].as_array().cloned().unwrap_or_default(),
+            })
+        })
+        .collect())
+}
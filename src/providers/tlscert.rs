@@ -0,0 +1,236 @@
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tokio_rustls::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use tokio_rustls::rustls::client::WebPkiServerVerifier;
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{self, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use tracing::{debug, info};
+use x509_parser::extensions::GeneralName;
+use x509_parser::oid_registry::OidRegistry;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use super::dns;
+use crate::OsintError;
+
+/// Covers the TCP connect and the TLS handshake.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// A leaf expiring within this many days is reported as expiring soon.
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Serialize, Debug)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub sans: Vec<String>,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+    /// Negative once the certificate has expired.
+    pub days_until_expiry: i64,
+    pub expired: bool,
+    pub expiring_soon: bool,
+    pub serial: String,
+    pub signature_algorithm: String,
+    pub sha256: String,
+    pub self_signed: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TlsCertReport {
+    pub host: String,
+    pub address: SocketAddr,
+    /// The server name sent in the handshake; none for IP targets.
+    pub sni: Option<String>,
+    pub protocol: Option<String>,
+    pub cipher_suite: Option<String>,
+    /// Whether the chain verifies against the Mozilla root store for `host`.
+    pub trusted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_error: Option<String>,
+    /// The certificates as presented, leaf first.
+    pub chain: Vec<CertificateInfo>,
+    /// Leaf SANs at or under a domain target, wildcards stripped.
+    pub subdomains: Vec<String>,
+}
+
+pub async fn fetch_tlscert(target: &str, port: u16) -> Result<Value, OsintError> {
+    let host = target.trim_end_matches('.').to_ascii_lowercase();
+    let (ip, server_name) = match host.parse::<IpAddr>() {
+        Ok(ip) => (ip, ServerName::IpAddress(ip.into())),
+        Err(_) => {
+            let name = ServerName::try_from(host.clone()).map_err(|_| OsintError::InvalidTarget(format!("{} is not a valid TLS server name", host)))?;
+            (dns::resolve_ip(&dns::resolver(), &host).await?, name)
+        }
+    };
+    Ok(serde_json::to_value(inspect(host, SocketAddr::new(ip, port), server_name).await?)?)
+}
+
+/// The handshake with `address` as `host`, so tests can point it at a local
+/// listener.
+pub async fn inspect(host: String, address: SocketAddr, server_name: ServerName<'static>) -> Result<TlsCertReport, OsintError> {
+    let sni = host.parse::<IpAddr>().is_err().then(|| host.clone());
+    let verifier = Arc::new(InspectingVerifier::new()?);
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| OsintError::ProviderError(err.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+
+    info!(%address, "fetching TLS certificate");
+    let handshake = async {
+        let stream = TcpStream::connect(address).await.map_err(|err| connect_error(address, err))?;
+        TlsConnector::from(Arc::new(config)).connect(server_name, stream).await.map_err(|err| handshake_error(address, err))
+    };
+    let stream = timeout(CONNECT_TIMEOUT, handshake).await.map_err(|_| OsintError::Connection(format!("no TLS handshake with {} within {}s", address, CONNECT_TIMEOUT.as_secs())))??;
+    let (_, session) = stream.get_ref();
+
+    let now = Utc::now().timestamp();
+    let chain: Vec<CertificateInfo> = session.peer_certificates().unwrap_or_default().iter().filter_map(|der| describe(der, now)).collect();
+    let verdict = verifier.verdict.lock().unwrap().take();
+    let subdomains = match (&sni, chain.first()) {
+        (Some(domain), Some(leaf)) => names_under(&leaf.sans, domain),
+        _ => Vec::new(),
+    };
+    Ok(TlsCertReport {
+        host,
+        sni,
+        address,
+        protocol: session.protocol_version().and_then(|version| version.as_str()).map(str::to_string),
+        cipher_suite: session.negotiated_cipher_suite().and_then(|suite| suite.suite().as_str()).map(str::to_string),
+        trusted: matches!(verdict, Some(Ok(()))),
+        verification_error: verdict.and_then(Result::err),
+        chain,
+        subdomains,
+    })
+}
+
+fn connect_error(address: SocketAddr, err: std::io::Error) -> OsintError {
+    match err.kind() {
+        ErrorKind::ConnectionRefused => OsintError::Connection(format!("{} refused the connection", address)),
+        _ => OsintError::Connection(format!("could not connect to {}: {}", address, err)),
+    }
+}
+
+fn handshake_error(address: SocketAddr, err: std::io::Error) -> OsintError {
+    let tls = err.get_ref().and_then(|inner| inner.downcast_ref::<rustls::Error>());
+    match (err.kind(), tls) {
+        (_, Some(rustls::Error::InvalidMessage(_))) | (_, Some(rustls::Error::InappropriateMessage { .. })) => OsintError::Connection(format!("{} does not speak TLS", address)),
+        (ErrorKind::UnexpectedEof | ErrorKind::ConnectionReset, _) => OsintError::Connection(format!("{} closed the connection during the TLS handshake", address)),
+        _ => OsintError::Connection(format!("TLS handshake with {} failed: {}", address, err)),
+    }
+}
+
+/// `names` at or under `domain`, with `*.` wildcards reduced to the name they
+/// cover.
+pub fn names_under(names: &[String], domain: &str) -> Vec<String> {
+    let mut found: Vec<String> = names
+        .iter()
+        .map(|name| name.trim_start_matches("*.").to_ascii_lowercase())
+        .filter(|name| name == domain || name.ends_with(&format!(".{}", domain)))
+        .collect();
+    found.sort_unstable();
+    found.dedup();
+    found
+}
+
+/// Reads one DER certificate; `now` is a Unix timestamp.
+pub fn describe(der: &[u8], now: i64) -> Option<CertificateInfo> {
+    let (_, cert) = X509Certificate::from_der(der).map_err(|err| debug!(%err, "unparseable certificate in chain")).ok()?;
+    let sans = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|extension| {
+            extension
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name) => Some(name.to_string()),
+                    GeneralName::IPAddress(bytes) => ip_from_bytes(bytes).map(|ip| ip.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let not_before = cert.validity().not_before.timestamp();
+    let not_after = cert.validity().not_after.timestamp();
+    let days_until_expiry = (not_after - now).div_euclid(86_400);
+    let algorithm = &cert.signature_algorithm.algorithm;
+    let registry = OidRegistry::default().with_crypto().with_x509();
+    Some(CertificateInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        sans,
+        not_before: timestamp(not_before),
+        not_after: timestamp(not_after),
+        days_until_expiry,
+        expired: not_after < now,
+        expiring_soon: not_after >= now && days_until_expiry < EXPIRY_WARNING_DAYS,
+        serial: cert.raw_serial_as_string(),
+        signature_algorithm: registry.get(algorithm).map_or_else(|| algorithm.to_id_string(), |entry| entry.sn().to_string()),
+        sha256: Sha256::digest(der).iter().map(|byte| format!("{:02x}", byte)).collect(),
+        self_signed: cert.subject().as_raw() == cert.issuer().as_raw(),
+    })
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+fn timestamp(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0).map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Accepts any certificate so expired, self-signed and mismatched ones can
+/// still be inspected, and records what normal verification would have said.
+#[derive(Debug)]
+struct InspectingVerifier {
+    webpki: Arc<WebPkiServerVerifier>,
+    verdict: Mutex<Option<Result<(), String>>>,
+}
+
+impl InspectingVerifier {
+    fn new() -> Result<Self, OsintError> {
+        let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let provider: Arc<CryptoProvider> = Arc::new(ring::default_provider());
+        let webpki = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider).build().map_err(|err| OsintError::ProviderError(err.to_string()))?;
+        Ok(InspectingVerifier { webpki, verdict: Mutex::new(None) })
+    }
+}
+
+impl ServerCertVerifier for InspectingVerifier {
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, intermediates: &[CertificateDer<'_>], server_name: &ServerName<'_>, ocsp_response: &[u8], now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        let verdict = self.webpki.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now).map(|_| ()).map_err(|err| err.to_string());
+        *self.verdict.lock().unwrap() = Some(verdict);
+        Ok(ServerCertVerified::assertion())
+    }
+
+    // The handshake signatures are still checked: they prove the server
+    // holds the key of the certificate it presented.
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.webpki.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.webpki.supported_verify_schemes()
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs;
+
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{debug, info};
+
+use crate::OsintError;
+
+const URLSCAN_BASE_URL: &str = "https://urlscan.io/api/v1";
+
+pub const VISIBILITIES: &[&str] = &["public", "unlisted", "private"];
+
+/// How often the result endpoint is asked and for how long.
+#[derive(Debug, Clone, Copy)]
+pub struct Polling {
+    pub first_delay: Duration,
+    pub delay: Duration,
+    pub max_delay: Duration,
+    pub deadline: Duration,
+}
+
+impl Polling {
+    /// urlscan asks clients to wait before the first poll; scans rarely
+    /// finish sooner.
+    pub const DEFAULT: Polling = Polling { first_delay: Duration::from_secs(10), delay: Duration::from_secs(2), max_delay: Duration::from_secs(10), deadline: Duration::from_secs(180) };
+}
+
+/// Submits the target to urlscan.io, waits for the scan to finish and
+/// returns its verdicts, page metadata and contacted hosts.
+pub async fn fetch_urlscan(target: &str, visibility: &str, download_screenshot: bool) -> Result<Value, OsintError> {
+    let key = crate::api_key("URLSCAN_API_KEY")?;
+    scan_from(URLSCAN_BASE_URL, &key, target, visibility, download_screenshot, Polling::DEFAULT).await
+}
+
+/// The submission and polling against `base_url`, so tests can point them
+/// at a mock server.
+pub async fn scan_from(base_url: &str, key: &str, target: &str, visibility: &str, download_screenshot: bool, polling: Polling) -> Result<Value, OsintError> {
+    let url = super::http::target_url(target)?;
+    let client = crate::client_builder().build()?;
+
+    let uuid = submit(&client, base_url, key, url.as_str(), visibility).await?;
+    info!(%uuid, visibility, "urlscan submission accepted");
+    let result = poll(&client, base_url, key, &uuid, polling).await?;
+
+    let screenshot_url = result["task"]["screenshotURL"].as_str().map(str::to_string).unwrap_or_else(|| format!("https://urlscan.io/screenshots/{}.png", uuid));
+    let screenshot_path = if download_screenshot { Some(save_screenshot(&client, &screenshot_url, target).await?) } else { None };
+
+    Ok(json!({
+        "uuid": uuid,
+        "visibility": visibility,
+        "report_url": result["task"]["reportURL"],
+        "screenshot_url": screenshot_url,
+        "screenshot_path": screenshot_path,
+        "verdicts": {
+            "score": result["verdicts"]["overall"]["score"],
+            "malicious": result["verdicts"]["overall"]["malicious"],
+            "categories": result["verdicts"]["overall"]["categories"],
+            "brands": result["verdicts"]["overall"]["brands"],
+        },
+        "page": {
+            "url": result["page"]["url"],
+            "domain": result["page"]["domain"],
+            "ip": result["page"]["ip"],
+            "country": result["page"]["country"],
+            "server": result["page"]["server"],
+            "title": result["page"]["title"],
+            "status": result["page"]["status"],
+        },
+        "contacted_ips": result["lists"]["ips"],
+        "contacted_domains": result["lists"]["domains"],
+    }))
+}
+
+async fn submit(client: &Client, base_url: &str, key: &str, url: &str, visibility: &str) -> Result<String, OsintError> {
+    let submit_url = format!("{}/scan/", base_url);
+    let request = client.post(&submit_url).header("API-Key", key).json(&json!({ "url": url, "visibility": visibility })).build()?;
+    let response = crate::send_with_retries(client, request).await?;
+    match response.status() {
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED => return Err(OsintError::InvalidCredentials("URLSCAN_API_KEY".to_string())),
+        // Refused submissions (unresolvable or blocklisted hosts) explain themselves.
+        StatusCode::BAD_REQUEST => {
+            let body: Value = response.json().await.unwrap_or_default();
+            let message = body["message"].as_str().unwrap_or("submission refused");
+            return Err(OsintError::ProviderError(format!("urlscan refused {}: {}", url, message)));
+        }
+        status => return Err(OsintError::ApiStatus { status, url: submit_url }),
+    }
+    let accepted: Value = response.json().await?;
+    accepted["uuid"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| OsintError::ProviderError("urlscan accepted the submission without returning a uuid".to_string()))
+}
+
+/// Polls the result endpoint, which answers 404 until the scan is processed,
+/// backing off from the polling delay up to its maximum.
+async fn poll(client: &Client, base_url: &str, key: &str, uuid: &str, polling: Polling) -> Result<Value, OsintError> {
+    let result_url = format!("{}/result/{}/", base_url, uuid);
+    let started = Instant::now();
+    let mut delay = polling.delay;
+    sleep(polling.first_delay).await;
+    loop {
+        let request = client.get(&result_url).header("API-Key", key).build()?;
+        let response = crate::send_with_retries(client, request).await?;
+        match response.status() {
+            status if status.is_success() => return Ok(response.json().await?),
+            StatusCode::NOT_FOUND => debug!(%uuid, elapsed_secs = started.elapsed().as_secs(), "urlscan result not ready"),
+            status => return Err(OsintError::ApiStatus { status, url: result_url }),
+        }
+        if started.elapsed() >= polling.deadline {
+            return Err(OsintError::ProviderError(format!("urlscan scan {} did not finish within {:?}", uuid, polling.deadline)));
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(polling.max_delay);
+    }
+}
+
+async fn save_screenshot(client: &Client, screenshot_url: &str, target: &str) -> Result<String, OsintError> {
+    let response = crate::send_with_retries(client, client.get(screenshot_url).build()?).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: screenshot_url.to_string() });
+    }
+    let png = response.bytes().await.map_err(|err| err.without_url())?;
+    let path = crate::output_path(&format!("{}_urlscan.png", target.replace(['/', ':'], "_"))).display().to_string();
+    fs::write(&path, &png)?;
+    info!(%path, bytes = png.len(), "screenshot saved");
+    Ok(path)
+}
@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info};
+
+use crate::OsintError;
+
+const BUILTIN_SITES: &str = include_str!("username_sites.json");
+const MAX_CONCURRENT_CHECKS: usize = 8;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A platform to probe. `url` contains a `{username}` placeholder. A profile
+/// exists when the site answers `expected_status` (any success by default)
+/// and, for sites that answer 200 for missing profiles too, the body lacks
+/// `not_found_marker`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Site {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub expected_status: Option<u16>,
+    #[serde(default)]
+    pub not_found_marker: Option<String>,
+}
+
+/// A TOML site list: `[[site]]` tables added to the built-in list.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct SiteFile {
+    #[serde(default)]
+    site: Vec<Site>,
+}
+
+/// How far a check's answer can be trusted: `high` when the page was read
+/// for the not-found marker, `medium` from the status code alone, `low` when
+/// the check failed.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProfileCheck {
+    pub platform: String,
+    pub username: String,
+    pub url: String,
+    pub exists: bool,
+    pub confidence: Confidence,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The built-in site list, replaced by a `.json` file's or extended by a
+/// `.toml` file's, where a site named like a built-in one replaces it.
+pub fn load_sites(path: Option<&Path>) -> Result<Vec<Site>, OsintError> {
+    let mut sites: Vec<Site> = serde_json::from_str(BUILTIN_SITES)?;
+    let Some(path) = path else {
+        return Ok(sites);
+    };
+    let text = fs::read_to_string(path)?;
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml")) {
+        let file: SiteFile = toml::from_str(&text).map_err(|err| OsintError::Config(format!("{}: {}", path.display(), err.message())))?;
+        for site in file.site {
+            sites.retain(|builtin| !builtin.name.eq_ignore_ascii_case(&site.name));
+            sites.push(site);
+        }
+        return Ok(sites);
+    }
+    sites = serde_json::from_str(&text)?;
+    Ok(sites)
+}
+
+/// The handles to try for `input`: the handle itself, or for an email the
+/// local part with and without its dots and trailing digits, as in
+/// `john.doe42`, `johndoe42`, `john.doe`, `johndoe`. A `+tag` is dropped.
+pub fn candidates(input: &str) -> Vec<String> {
+    let Some((local, _)) = input.rsplit_once('@') else {
+        return vec![input.to_string()];
+    };
+    let local = local.split('+').next().unwrap_or_default().to_ascii_lowercase();
+    let undotted = local.replace('.', "");
+    let mut names = Vec::new();
+    for name in [local.clone(), undotted.clone(), local.trim_end_matches(|c: char| c.is_ascii_digit()).to_string(), undotted.trim_end_matches(|c: char| c.is_ascii_digit()).to_string()] {
+        let name = name.trim_matches('.').to_string();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Checks every candidate handle of `input` (see [`candidates`]) on every
+/// site, a bounded number at a time.
+pub async fn fetch_username(input: &str, sites: Vec<Site>) -> Result<Value, OsintError> {
+    let usernames = candidates(input);
+    if usernames.is_empty() {
+        return Err(OsintError::InvalidTarget(format!("no username in {}", input)));
+    }
+    if let Some(bad) = usernames.iter().find(|username| username.is_empty() || !username.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))) {
+        return Err(OsintError::InvalidTarget(format!("{} is not a plausible username", bad)));
+    }
+
+    let client = crate::client_builder().timeout(REQUEST_TIMEOUT).build()?;
+    let probes: Vec<(&Site, &str)> = usernames.iter().flat_map(|username| sites.iter().map(move |site| (site, username.as_str()))).collect();
+    let mut checks: Vec<ProfileCheck> = stream::iter(probes)
+        .map(|(site, username)| check_site(&client, site, username))
+        .buffer_unordered(MAX_CONCURRENT_CHECKS)
+        .collect()
+        .await;
+    checks.sort_by(|a, b| a.platform.cmp(&b.platform).then_with(|| a.username.cmp(&b.username)));
+    info!(usernames = usernames.len(), sites = sites.len(), found = checks.iter().filter(|check| check.exists).count(), "username sweep done");
+    Ok(serde_json::to_value(checks)?)
+}
+
+async fn check_site(client: &Client, site: &Site, username: &str) -> ProfileCheck {
+    let url = site.url.replace("{username}", username);
+    let outcome = async {
+        let response = crate::send_with_retries(client, client.get(&url).build()?).await?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(OsintError::RetriesExhausted(url.clone()));
+        }
+        let status_matches = match site.expected_status {
+            Some(expected) => status.as_u16() == expected,
+            None => status.is_success(),
+        };
+        let Some(marker) = site.not_found_marker.as_deref().filter(|_| status_matches) else {
+            return Ok((status_matches, Confidence::Medium));
+        };
+        let body = response.text().await.map_err(|err| err.without_url())?;
+        Ok::<_, OsintError>((!body.contains(marker), Confidence::High))
+    }
+    .await;
+
+    debug!(platform = %site.name, username, ?outcome, "profile checked");
+    let (exists, confidence, error) = match outcome {
+        Ok((exists, confidence)) => (exists, confidence, None),
+        Err(err) => (false, Confidence::Low, Some(err.to_string())),
+    };
+    ProfileCheck { platform: site.name.clone(), username: username.to_string(), url, exists, confidence, error }
+}
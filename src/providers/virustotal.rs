@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use crate::ratelimit::RateLimiter;
+use crate::target::{self, TargetKind};
+use crate::{OsintError, RetryPolicy};
+
+const VT_BASE_URL: &str = "https://www.virustotal.com/api/v3";
+/// The public API allows four lookups a minute.
+const VT_REQUESTS_PER_MINUTE: u32 = 4;
+
+fn limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| RateLimiter::per_minute(VT_REQUESTS_PER_MINUTE))
+}
+
+pub async fn fetch_virustotal(target: &str) -> Result<Value, OsintError> {
+    let key = crate::api_key("VT_API_KEY")?;
+    lookup_from(VT_BASE_URL, &key, target, limiter()).await
+}
+
+/// The lookup against `base_url`, paced by `limiter`, so tests can point it
+/// at a mock server without waiting out the free tier's pace.
+pub async fn lookup_from(base_url: &str, key: &str, target: &str, limiter: &RateLimiter) -> Result<Value, OsintError> {
+    let kind = target::classify(target);
+    let collection = match kind {
+        TargetKind::Domain => "domains",
+        TargetKind::Ip => "ip_addresses",
+        TargetKind::Hash => "files",
+        got => return Err(OsintError::InvalidTarget(format!("virustotal cannot look up {} ({})", target, got))),
+    };
+
+    let object = get(limiter, key, &format!("{}/{}/{}", base_url, collection, target)).await?;
+    // Files have no DNS resolutions to speak of.
+    let resolutions = match kind {
+        TargetKind::Hash => Vec::new(),
+        _ => {
+            let history = get(limiter, key, &format!("{}/{}/{}/resolutions?limit=40", base_url, collection, target)).await?;
+            history["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|resolution| {
+                    let attributes = &resolution["attributes"];
+                    json!({
+                        "date": attributes["date"],
+                        "host_name": attributes["host_name"],
+                        "ip_address": attributes["ip_address"],
+                    })
+                })
+                .collect()
+        }
+    };
+
+    let attributes = &object["data"]["attributes"];
+    Ok(json!({
+        "kind": collection,
+        "last_analysis_stats": attributes["last_analysis_stats"],
+        "reputation": attributes["reputation"],
+        "categories": attributes["categories"],
+        "resolutions": resolutions,
+        "raw": object,
+    }))
+}
+
+async fn get(limiter: &RateLimiter, key: &str, url: &str) -> Result<Value, OsintError> {
+    limiter.acquire().await;
+    let client = crate::client_builder().build()?;
+    let request = client.get(url).header("x-apikey", key).build()?;
+    // Requests are already paced to the per-minute limit, so a 429 means the
+    // daily quota is spent and will not clear by waiting.
+    let response = crate::send_with_policy(&client, request, RetryPolicy::SINGLE_ATTEMPT).await?;
+    match response.status() {
+        status if status.is_success() => Ok(response.json().await?),
+        StatusCode::TOO_MANY_REQUESTS => {
+            let body: Value = response.json().await.unwrap_or_default();
+            let message = body["error"]["message"].as_str().unwrap_or("quota exceeded");
+            Err(OsintError::QuotaExhausted(format!("VirusTotal quota for VT_API_KEY exhausted ({})", message)))
+        }
+        StatusCode::UNAUTHORIZED => Err(OsintError::InvalidCredentials("VT_API_KEY".to_string())),
+        status => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+    }
+}
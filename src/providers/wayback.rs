@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::OsintError;
+
+const CDX_URL: &str = "https://web.archive.org/cdx/search/cdx";
+/// The CDX server is slow on large domains.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// Extensions of files that often leak data when an old copy is archived.
+const INTERESTING_EXTENSIONS: &[&str] = &[
+    ".sql", ".bak", ".backup", ".old", ".env", ".log", ".conf", ".config", ".ini", ".db", ".sqlite", ".zip", ".tar", ".tar.gz", ".tgz", ".gz", ".7z", ".rar", ".swp", ".pem", ".key",
+];
+
+#[derive(Serialize, Debug)]
+pub struct ArchivedUrl {
+    pub url: String,
+    pub first_capture: Option<String>,
+    pub last_capture: Option<String>,
+    pub status: Option<String>,
+    pub captures: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interesting_extension: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct WaybackReport {
+    pub domain: String,
+    pub total_urls: usize,
+    pub limit_reached: bool,
+    pub interesting: BTreeMap<&'static str, Vec<String>>,
+    pub urls: Vec<ArchivedUrl>,
+}
+
+pub async fn fetch_wayback(domain: &str, limit: usize) -> Result<Value, OsintError> {
+    let client = crate::client_builder().timeout(REQUEST_TIMEOUT).build()?;
+    let request = client
+        .get(CDX_URL)
+        .query(&[
+            ("url", format!("{}/*", domain).as_str()),
+            ("output", "json"),
+            ("collapse", "urlkey"),
+            ("fl", "original,timestamp,statuscode"),
+            // With collapsing, these add each URL's capture count and last capture.
+            ("showGroupCount", "true"),
+            ("lastSkipTimestamp", "true"),
+            ("limit", &limit.to_string()),
+        ])
+        .build()?;
+    let mut response = crate::send_with_retries(&client, request).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(OsintError::ApiStatus { status, url: CDX_URL.to_string() });
+    }
+
+    // Parse row by row as chunks arrive, so large domains never sit in memory as one body.
+    let mut parser = CdxParser::new(domain);
+    let mut pending = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|err| err.without_url())? {
+        pending.extend_from_slice(&chunk);
+        while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=newline).collect();
+            parser.feed(&String::from_utf8_lossy(&line))?;
+        }
+    }
+    parser.feed(&String::from_utf8_lossy(&pending))?;
+
+    let mut report = parser.finish();
+    report.limit_reached = report.total_urls >= limit;
+    if report.limit_reached {
+        warn!(%domain, limit, "wayback results truncated, raise --wayback-limit for more");
+    }
+    Ok(serde_json::to_value(report)?)
+}
+
+/// Incremental parser for CDX JSON output: an array of string arrays with
+/// one row per line, the first row naming the columns.
+pub struct CdxParser {
+    columns: Option<Vec<String>>,
+    report: WaybackReport,
+}
+
+impl CdxParser {
+    pub fn new(domain: &str) -> Self {
+        CdxParser { columns: None, report: WaybackReport { domain: domain.to_string(), ..WaybackReport::default() } }
+    }
+
+    /// Consumes one line of the response.
+    pub fn feed(&mut self, line: &str) -> Result<(), OsintError> {
+        let mut row = line.trim().trim_end_matches(',');
+        // The outer array's brackets share the first and last lines.
+        if row.starts_with("[[") {
+            row = &row[1..];
+        }
+        if row.ends_with("]]") {
+            row = &row[..row.len() - 1];
+        }
+        if row.is_empty() || row == "[]" {
+            return Ok(());
+        }
+        let fields: Vec<String> = serde_json::from_str(row).map_err(|err| OsintError::ProviderError(format!("unreadable Wayback CDX row: {}", err)))?;
+
+        let Some(columns) = &self.columns else {
+            debug!(?fields, "wayback CDX columns");
+            self.columns = Some(fields);
+            return Ok(());
+        };
+        let field = |name: &str| columns.iter().position(|column| column == name).and_then(|index| fields.get(index)).cloned();
+        let Some(url) = field("original") else {
+            return Ok(());
+        };
+        let first_capture = field("timestamp");
+        let interesting_extension = interesting_extension(&url);
+        if let Some(extension) = interesting_extension {
+            self.report.interesting.entry(extension).or_default().push(url.clone());
+        }
+        self.report.urls.push(ArchivedUrl {
+            last_capture: field("endtimestamp").or_else(|| first_capture.clone()).map(|ts| iso_timestamp(&ts)),
+            first_capture: first_capture.map(|ts| iso_timestamp(&ts)),
+            status: field("statuscode").filter(|status| status != "-"),
+            captures: field("groupcount").and_then(|count| count.parse().ok()),
+            interesting_extension,
+            url,
+        });
+        self.report.total_urls += 1;
+        Ok(())
+    }
+
+    pub fn finish(self) -> WaybackReport {
+        self.report
+    }
+}
+
+/// The risky extension a URL's path ends in, ignoring any query string.
+pub fn interesting_extension(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase();
+    // Longest first, so `.tar.gz` wins over `.gz`.
+    let mut candidates: Vec<&'static str> = INTERESTING_EXTENSIONS.iter().copied().filter(|extension| path.ends_with(extension)).collect();
+    candidates.sort_by_key(|extension| std::cmp::Reverse(extension.len()));
+    candidates.first().copied()
+}
+
+/// CDX timestamps are `YYYYMMDDhhmmss`; anything else is returned as is.
+fn iso_timestamp(raw: &str) -> String {
+    if raw.len() != 14 || !raw.bytes().all(|b| b.is_ascii_digit()) {
+        return raw.to_string();
+    }
+    format!("{}-{}-{}T{}:{}:{}Z", &raw[0..4], &raw[4..6], &raw[6..8], &raw[8..10], &raw[10..12], &raw[12..14])
+}
@@ -0,0 +1,312 @@
+use std::env;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use super::{port43, rdap};
+use crate::OsintError;
+
+const WHOISVU_URL: &str = "https://api.whois.vu/";
+const WHOISXML_URL: &str = "https://www.whoisxmlapi.com/whoisserver/WhoisService";
+
+/// Values of `--whois-provider`: `auto` tries whois.vu, then WhoisXML when
+/// `WHOISXML_API_KEY` is set, then RDAP, then the registries' WHOIS servers.
+pub const SOURCES: &[&str] = &["auto", "whoisvu", "whoisxml", "rdap", "port43"];
+
+/// Registration details in one shape whichever service answered.
+#[derive(Serialize, Debug, Default)]
+pub struct WhoisRecord {
+    pub domain: String,
+    /// The service that answered, `whoisvu`, `whoisxml`, `rdap` or `port43`.
+    pub source: String,
+    /// False when the service reports the domain as available to register.
+    pub registered: bool,
+    pub registrar: Option<String>,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    pub expires: Option<String>,
+    pub status: Vec<String>,
+    pub nameservers: Vec<String>,
+    pub registrant_org: Option<String>,
+    pub registrant_country: Option<String>,
+    /// Fields the service withheld for privacy, such as `Registrant
+    /// Organization`, which are then left empty above.
+    pub redacted: Vec<String>,
+    /// The registrar's server a registry answer referred to, RDAP or WHOIS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referral: Option<String>,
+    /// The unparsed WHOIS text, when the service returns one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+}
+
+impl WhoisRecord {
+    fn is_empty(&self) -> bool {
+        self.registrar.is_none() && self.created.is_none() && self.expires.is_none() && self.nameservers.is_empty()
+    }
+}
+
+/// A service the whois type can ask, with where to reach it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WhoisProvider {
+    /// whois.vu at the given base URL.
+    WhoisVu(String),
+    /// The WhoisXML API service at `url`, with its key.
+    WhoisXml { url: String, key: String },
+    Rdap,
+    /// WHOIS over TCP: the registry's server for the TLD, or `server`, on
+    /// `port`.
+    Port43 { server: Option<String>, port: u16 },
+}
+
+impl WhoisProvider {
+    /// The name `source` records, as `--whois-provider` takes it.
+    pub fn id(&self) -> &'static str {
+        match self {
+            WhoisProvider::WhoisVu(_) => "whoisvu",
+            WhoisProvider::WhoisXml { .. } => "whoisxml",
+            WhoisProvider::Rdap => "rdap",
+            WhoisProvider::Port43 { .. } => "port43",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            WhoisProvider::WhoisVu(_) => "whois.vu",
+            WhoisProvider::WhoisXml { .. } => "WhoisXML",
+            WhoisProvider::Rdap => "RDAP",
+            WhoisProvider::Port43 { .. } => "WHOIS",
+        }
+    }
+
+    pub async fn lookup(&self, domain: &str) -> Result<WhoisRecord, OsintError> {
+        match self {
+            WhoisProvider::WhoisVu(base_url) => fetch_whoisvu_from(base_url, domain).await,
+            WhoisProvider::WhoisXml { url, key } => fetch_whoisxml_from(url, key, domain).await,
+            WhoisProvider::Rdap => rdap::domain_record(domain).await,
+            WhoisProvider::Port43 { server, port } => port43::lookup_from(server.as_deref().unwrap_or_else(|| port43::server_for(domain)), *port, domain, port43::QUERY_TIMEOUT).await,
+        }
+    }
+}
+
+/// The providers a `--whois-provider` value asks, in order. `auto` leaves
+/// WhoisXML out without a key; naming it needs one.
+pub fn providers(choice: &str) -> Result<Vec<WhoisProvider>, OsintError> {
+    let whoisxml_key = env::var("WHOISXML_API_KEY").ok().filter(|key| !key.is_empty());
+    let whoisxml = |key: String| WhoisProvider::WhoisXml { url: WHOISXML_URL.to_string(), key };
+    let port43 = WhoisProvider::Port43 { server: None, port: port43::WHOIS_PORT };
+    Ok(match choice {
+        "whoisvu" => vec![WhoisProvider::WhoisVu(WHOISVU_URL.to_string())],
+        "whoisxml" => vec![whoisxml(whoisxml_key.ok_or_else(|| OsintError::MissingApiKey("WHOISXML_API_KEY".to_string()))?)],
+        "rdap" => vec![WhoisProvider::Rdap],
+        "port43" => vec![port43],
+        _ => [Some(WhoisProvider::WhoisVu(WHOISVU_URL.to_string())), whoisxml_key.map(whoisxml), Some(WhoisProvider::Rdap), Some(port43)].into_iter().flatten().collect(),
+    })
+}
+
+pub async fn fetch_whois(domain: &str, choice: &str) -> Result<Value, OsintError> {
+    let record = lookup_with_fallback(domain, &providers(choice)?).await?;
+    Ok(serde_json::to_value(record)?)
+}
+
+/// Asks each provider in turn until one has registration data. An empty
+/// answer is kept in case none does; "not registered" is an answer, not
+/// missing data. When every provider fails, so does the lookup.
+pub async fn lookup_with_fallback(domain: &str, providers: &[WhoisProvider]) -> Result<WhoisRecord, OsintError> {
+    let mut empty = None;
+    let mut errors = Vec::new();
+    for (index, provider) in providers.iter().enumerate() {
+        let next = providers.get(index + 1).map(WhoisProvider::name);
+        match provider.lookup(domain).await {
+            Ok(record) if !record.is_empty() || !record.registered => {
+                info!(%domain, provider = provider.id(), "whois lookup answered");
+                return Ok(record);
+            }
+            Ok(record) => {
+                if let Some(next) = next {
+                    warn!(%domain, "{} returned no registration data, falling back to {}", provider.name(), next);
+                }
+                empty.get_or_insert((provider.name(), record));
+            }
+            Err(err) => {
+                if let Some(next) = next {
+                    warn!(%domain, %err, "{} failed, falling back to {}", provider.name(), next);
+                }
+                errors.push((provider.name(), err));
+            }
+        }
+    }
+    if let Some((name, record)) = empty {
+        if !errors.is_empty() {
+            warn!(%domain, "keeping the empty {} answer", name);
+        }
+        return Ok(record);
+    }
+    if errors.len() == 1 {
+        return Err(errors.remove(0).1);
+    }
+    Err(OsintError::ProviderError(errors.iter().map(|(name, err)| format!("{}: {}", name, err)).collect::<Vec<_>>().join("; ")))
+}
+
+/// The whois.vu lookup against `base_url`, so tests can point it at a mock
+/// server.
+pub async fn fetch_whoisvu_from(base_url: &str, domain: &str) -> Result<WhoisRecord, OsintError> {
+    let url = format!("{}?q={}", base_url, domain);
+    let response = crate::fetch_with_retries(&url, HeaderMap::new()).await?;
+    parse_whoisvu(domain, &serde_json::from_str(&response)?)
+}
+
+/// The WhoisXML API lookup against `url`, so tests can point it at a mock
+/// server.
+pub async fn fetch_whoisxml_from(url: &str, key: &str, domain: &str) -> Result<WhoisRecord, OsintError> {
+    let client = crate::client_builder().build()?;
+    let request = client.get(url).query(&[("apiKey", key), ("domainName", domain), ("outputFormat", "JSON"), ("da", "2")]).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    let message = body["ErrorMessage"]["msg"].as_str().unwrap_or_default();
+    // Credits running out and a bad key are both 403s, told apart by the message.
+    if message.to_ascii_lowercase().contains("credit") || message.to_ascii_lowercase().contains("balance") {
+        return Err(OsintError::QuotaExhausted(format!("WhoisXML: {}", message)));
+    }
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(OsintError::InvalidCredentials(format!("WHOISXML_API_KEY ({})", if message.is_empty() { status.as_str() } else { message }))),
+        status if !status.is_success() => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+        _ if !message.is_empty() => Err(OsintError::ProviderError(format!("WhoisXML: {}", message))),
+        _ => Ok(parse_whoisxml(domain, &body)),
+    }
+}
+
+/// Reads a whois.vu answer: Unix timestamps for the dates, plus the raw WHOIS
+/// text, which supplies whatever the structured fields leave out. whois.vu
+/// also answers 200 to failures, with an `error` field, and to unregistered
+/// domains, with `available: true`.
+pub fn parse_whoisvu(domain: &str, body: &Value) -> Result<WhoisRecord, OsintError> {
+    if let Some(error) = body.get("error").filter(|error| !error.is_null() && *error != false) {
+        let message = error.as_str().map_or_else(|| error.to_string(), str::to_string);
+        return Err(OsintError::ProviderError(format!("whois.vu: {}", message)));
+    }
+    if body["available"] == true || body["available"] == "yes" {
+        info!(%domain, "whois.vu reports the domain as not registered");
+        return Ok(WhoisRecord { domain: domain.to_string(), source: "whoisvu".to_string(), registered: false, ..WhoisRecord::default() });
+    }
+    let raw = body["whois"].as_str().filter(|text| !text.trim().is_empty()).map(str::to_string);
+    let field = |label: &str| {
+        raw.iter()
+            .flat_map(|text| text.lines())
+            .filter_map(|line| line.trim().split_once(':'))
+            .filter(|(key, value)| key.trim().eq_ignore_ascii_case(label) && !value.trim().is_empty())
+            .map(|(_, value)| value.trim().to_string())
+            .collect::<Vec<_>>()
+    };
+    let date = |key: &str, label: &str| body[key].as_i64().and_then(timestamp).or_else(|| field(label).into_iter().next());
+
+    let mut nameservers: Vec<String> = field("Name Server").into_iter().map(|name| name.trim_end_matches('.').to_ascii_lowercase()).collect();
+    // Thick registries repeat the registry's block in the registrar's.
+    nameservers.sort_unstable();
+    nameservers.dedup();
+    let mut status: Vec<String> = field("Domain Status").into_iter().map(|status| status.split_whitespace().next().unwrap_or_default().to_string()).collect();
+    status.sort_unstable();
+    status.dedup();
+    let mut redacted = Vec::new();
+    Ok(WhoisRecord {
+        domain: domain.to_string(),
+        source: "whoisvu".to_string(),
+        registered: true,
+        registrar: body["registrar"].as_str().map(str::to_string).or_else(|| field("Registrar").into_iter().next()),
+        created: date("created", "Creation Date"),
+        updated: date("updated", "Updated Date"),
+        expires: date("expires", "Registry Expiry Date"),
+        status,
+        nameservers,
+        registrant_org: disclosed("Registrant Organization", field("Registrant Organization").into_iter().next(), &mut redacted),
+        registrant_country: disclosed("Registrant Country", field("Registrant Country").into_iter().next(), &mut redacted),
+        redacted,
+        referral: None,
+        raw,
+    })
+}
+
+/// Reads a WhoisXML `WhoisRecord`. Fields the registrar's block leaves out
+/// are taken from the registry's, under `registryData`.
+pub fn parse_whoisxml(domain: &str, body: &Value) -> WhoisRecord {
+    let record = &body["WhoisRecord"];
+    let text = |key: &str| [&record[key], &record["registryData"][key]].into_iter().find_map(|value| value.as_str().map(str::trim).filter(|text| !text.is_empty()).map(str::to_string));
+    if record["domainAvailability"] == "AVAILABLE" {
+        info!(%domain, "WhoisXML reports the domain as not registered");
+        return WhoisRecord { domain: domain.to_string(), source: "whoisxml".to_string(), registered: false, ..WhoisRecord::default() };
+    }
+    let list = |key: &str, inner: Option<&str>| {
+        [&record[key], &record["registryData"][key]]
+            .into_iter()
+            .map(|value| match inner {
+                Some(inner) => &value[inner],
+                None => value,
+            })
+            .find_map(|value| match value {
+                Value::Array(items) if !items.is_empty() => Some(items.iter().filter_map(Value::as_str).map(str::to_string).collect::<Vec<_>>()),
+                Value::String(text) if !text.trim().is_empty() => Some(text.split_whitespace().map(str::to_string).collect()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+    let mut nameservers: Vec<String> = list("nameServers", Some("hostNames")).into_iter().map(|name| name.trim_end_matches('.').to_ascii_lowercase()).collect();
+    nameservers.sort_unstable();
+    nameservers.dedup();
+    let mut status = list("status", None);
+    status.sort_unstable();
+    status.dedup();
+    let mut redacted = Vec::new();
+    let registrant = |key: &str| [&record["registrant"][key], &record["registryData"]["registrant"][key]].into_iter().find_map(|value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string));
+    WhoisRecord {
+        domain: text("domainName").map_or_else(|| domain.to_string(), |name| name.to_ascii_lowercase()),
+        source: "whoisxml".to_string(),
+        registered: true,
+        registrar: text("registrarName"),
+        created: text("createdDate").map(iso_date),
+        updated: text("updatedDate").map(iso_date),
+        expires: text("expiresDate").map(iso_date),
+        status,
+        nameservers,
+        registrant_org: disclosed("Registrant Organization", registrant("organization"), &mut redacted),
+        registrant_country: disclosed("Registrant Country", registrant("country"), &mut redacted),
+        redacted,
+        referral: None,
+        raw: text("rawText"),
+    }
+}
+
+/// Placeholders registries put in place of contact details withheld for
+/// privacy, matched case-insensitively.
+const REDACTION_MARKERS: &[&str] = &["redacted", "withheld", "not disclosed", "data protected", "gdpr masked"];
+
+fn is_redacted(value: &str) -> bool {
+    let value = value.to_ascii_lowercase();
+    REDACTION_MARKERS.iter().any(|marker| value.contains(marker))
+}
+
+/// `value` unless it is a redaction placeholder, in which case `label` is
+/// noted in `redacted` instead.
+pub fn disclosed(label: &str, value: Option<String>, redacted: &mut Vec<String>) -> Option<String> {
+    match value {
+        Some(value) if is_redacted(&value) => {
+            redacted.push(label.to_string());
+            None
+        }
+        value => value,
+    }
+}
+
+/// WhoisXML and WHOIS dates as RFC 3339 in UTC, as the other providers give them;
+/// ones it cannot read are kept as they are.
+pub(super) fn iso_date(date: String) -> String {
+    DateTime::parse_from_str(&date, "%Y-%m-%dT%H:%M:%S%z").or_else(|_| DateTime::parse_from_rfc3339(&date)).map_or(date, |parsed| parsed.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn timestamp(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0).map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::paginate::paginate;
+use crate::OsintError;
+
+const ZOOMEYE_BASE_URL: &str = "https://api.zoomeye.org";
+/// ZoomEye returns host search results in pages of this many matches.
+const PAGE_SIZE: usize = 20;
+/// Banner characters kept per service; full banners can run to kilobytes.
+const BANNER_EXCERPT_CHARS: usize = 512;
+
+/// The services ZoomEye has seen on an IP, or on the hosts of a domain,
+/// reading up to `max_pages` pages of results.
+pub async fn fetch_zoomeye(target: &str, max_pages: u32, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let key = crate::api_key("ZOOMEYE_API_KEY")?;
+    search(ZOOMEYE_BASE_URL, &key, target, max_pages, max_results).await
+}
+
+/// The host search query for a target: `ip:"…"` for an IP, `site:"…"` for a
+/// domain.
+pub fn query(target: &str) -> String {
+    match target.parse::<IpAddr>() {
+        Ok(ip) => format!("ip:\"{}\"", ip),
+        Err(_) => format!("site:\"{}\"", target.trim_end_matches('.')),
+    }
+}
+
+/// The search against `base_url`, so tests can point it at a mock server.
+/// The result has the shape of a Shodan host record: `ports`, `hostnames`
+/// and the per-service `data`, plus the search totals.
+pub async fn search(base_url: &str, key: &str, target: &str, max_pages: u32, max_results: Option<usize>) -> Result<Value, OsintError> {
+    let query = query(target);
+    let client = crate::client_builder().build()?;
+    let fetch = |page| search_page(&client, base_url, key, &query, page);
+    let results = paginate(1, max_results, fetch, |body: &Value, &page: &u32, before| {
+        let total = body["total"].as_u64().unwrap_or_default();
+        let services: Vec<Value> = body["matches"].as_array().into_iter().flatten().map(service).collect();
+        debug!(page, total, fetched = before + services.len(), "zoomeye search page read");
+        let more = services.len() >= PAGE_SIZE && ((before + services.len()) as u64) < total && page < max_pages;
+        (services, more.then_some(page + 1))
+    })
+    .await?;
+    let total = results.first["total"].as_u64().unwrap_or_default();
+    info!(query, total, services = results.items.len(), "zoomeye search done");
+    Ok(host(target, &query, total, results.pages, results.capped, results.items))
+}
+
+async fn search_page(client: &Client, base_url: &str, key: &str, query: &str, page: u32) -> Result<Value, OsintError> {
+    let url = Url::parse_with_params(&format!("{}/host/search", base_url), [("query", query), ("page", &page.to_string())]).map_err(|err| OsintError::InvalidTarget(err.to_string()))?;
+    let response = crate::send_with_retries(client, client.get(url.clone()).header("API-KEY", key).build()?).await?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    match status.is_success() {
+        true => Ok(body),
+        false => Err(api_error(status, &body, &url)),
+    }
+}
+
+/// ZoomEye names failures in an `error` field. Running out of credits is
+/// told apart from a bad key, since it answers 402 or 403 for both.
+fn api_error(status: StatusCode, body: &Value, url: &Url) -> OsintError {
+    let error = body["error"].as_str().unwrap_or_default();
+    let message = body["message"].as_str().unwrap_or(error);
+    let out_of_credits = ["credit", "quota", "insufficent", "insufficient"].iter().any(|word| error.contains(word));
+    match status {
+        StatusCode::PAYMENT_REQUIRED => OsintError::QuotaExhausted(format!("ZoomEye credits exhausted: {}", message)),
+        _ if out_of_credits => OsintError::QuotaExhausted(format!("ZoomEye credits exhausted: {}", message)),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => OsintError::InvalidCredentials(format!("ZOOMEYE_API_KEY: {}", message)),
+        _ if !message.is_empty() => OsintError::ProviderError(format!("ZoomEye: {}", message)),
+        status => OsintError::ApiStatus { status, url: url.to_string() },
+    }
+}
+
+/// One match as a Shodan service entry: `product` is ZoomEye's `app` and
+/// `data` an excerpt of the banner, with the location under `location`.
+pub fn service(host: &Value) -> Value {
+    let portinfo = &host["portinfo"];
+    let geoinfo = &host["geoinfo"];
+    let text = |value: &Value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+    let hostnames: Vec<String> = [&host["rdns"], &portinfo["hostname"]].into_iter().filter_map(text).collect();
+    json!({
+        "ip_str": host["ip"],
+        "port": portinfo["port"],
+        "transport": text(&portinfo["transport"]).unwrap_or_else(|| "tcp".to_string()),
+        "service": text(&portinfo["service"]),
+        "product": text(&portinfo["app"]),
+        "version": text(&portinfo["version"]),
+        "data": text(&portinfo["banner"]).map(|banner| banner.chars().take(BANNER_EXCERPT_CHARS).collect::<String>()),
+        "hostnames": hostnames,
+        "org": text(&geoinfo["organization"]),
+        "isp": text(&geoinfo["isp"]),
+        "asn": geoinfo["asn"],
+        "location": {
+            "country_name": geoinfo["country"]["names"]["en"],
+            "country_code": geoinfo["country"]["code"],
+            "city": geoinfo["city"]["names"]["en"],
+            "latitude": geoinfo["location"]["lat"],
+            "longitude": geoinfo["location"]["lon"],
+        },
+        "timestamp": host["timestamp"],
+    })
+}
+
+/// The services gathered into a host record; the top-level fields come from
+/// the first service, as they would for a single IP.
+fn host(target: &str, query: &str, total: u64, pages: u32, capped: bool, services: Vec<Value>) -> Value {
+    let ports: BTreeSet<u64> = services.iter().filter_map(|service| service["port"].as_u64()).collect();
+    let hostnames: BTreeSet<&str> = services.iter().flat_map(|service| service["hostnames"].as_array().into_iter().flatten().filter_map(Value::as_str)).collect();
+    let first = services.first().cloned().unwrap_or(Value::Null);
+    json!({
+        "ip_str": target.parse::<IpAddr>().ok().map(|ip| ip.to_string()),
+        "ports": ports,
+        "hostnames": hostnames,
+        "org": first["org"],
+        "isp": first["isp"],
+        "asn": first["asn"],
+        "country_name": first["location"]["country_name"],
+        "city": first["location"]["city"],
+        "data": services,
+        "query": query,
+        "total": total,
+        "pages_fetched": pages,
+        "capped": capped,
+    })
+}
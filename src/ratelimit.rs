@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::debug;
+
+/// Spaces out requests to a single source so they never exceed its quota.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        RateLimiter { interval: Duration::from_secs(60) / requests.max(1), next_slot: Mutex::new(None) }
+    }
+
+    pub fn per_second(requests: u32) -> Self {
+        RateLimiter { interval: Duration::from_secs(1) / requests.max(1), next_slot: Mutex::new(None) }
+    }
+
+    /// Waits until the next request slot is free and claims it.
+    pub async fn acquire(&self) {
+        self.acquire_many(1).await;
+    }
+
+    /// Like `acquire` for `slots` requests sent together; the slot after them
+    /// opens that many intervals later.
+    pub async fn acquire_many(&self, slots: u32) {
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        if let Some(slot) = *next_slot {
+            if slot > now {
+                debug!(wait_ms = (slot - now).as_millis() as u64, "waiting for rate limit slot");
+                sleep_until(slot).await;
+            }
+        }
+        *next_slot = Some(Instant::now() + self.interval * slots);
+    }
+}
+
+/// A `RateLimiter` for each host, created on first use, pacing every request
+/// under `--rate-limit`.
+pub struct HostRateLimiter {
+    requests: u32,
+    hosts: std::sync::Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl HostRateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        HostRateLimiter { requests, hosts: std::sync::Mutex::new(HashMap::new()) }
+    }
+
+    /// Waits for the next request slot of `host`.
+    pub async fn acquire(&self, host: &str) {
+        let limiter = self.hosts.lock().unwrap().entry(host.to_string()).or_insert_with(|| Arc::new(RateLimiter::per_minute(self.requests))).clone();
+        limiter.acquire().await;
+    }
+}
+
+/// Counts of the requests sent this run, for tuning `--global-rps`.
+#[derive(Default)]
+pub struct RequestMetrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    rate_limited: AtomicU64,
+}
+
+/// A snapshot of `RequestMetrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RequestTotals {
+    pub requests: u64,
+    pub retries: u64,
+    pub rate_limited: u64,
+}
+
+impl RequestMetrics {
+    pub const fn new() -> Self {
+        RequestMetrics { requests: AtomicU64::new(0), retries: AtomicU64::new(0), rate_limited: AtomicU64::new(0) }
+    }
+
+    /// One request sent; `retry` when it repeats an earlier attempt.
+    pub fn record_attempt(&self, retry: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if retry {
+            self.retries.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// One 429 answer.
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn totals(&self) -> RequestTotals {
+        RequestTotals { requests: self.requests.load(Ordering::Relaxed), retries: self.retries.load(Ordering::Relaxed), rate_limited: self.rate_limited.load(Ordering::Relaxed) }
+    }
+}
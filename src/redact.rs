@@ -0,0 +1,90 @@
+use std::net::IpAddr;
+
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::target;
+
+/// Field names whose values are always masked, compared case-insensitively.
+pub const DEFAULT_SENSITIVE_KEYS: &[&str] = &["password", "passwd", "secret", "token", "api_key", "apikey", "phone", "fax", "street", "address", "postal_code"];
+
+/// Characters that end a word when scanning free text for emails and IPs.
+const WORD_BREAKS: &str = ",;<>()[]{}\"'";
+
+/// Masks PII in a report. Every masked value becomes a salted hash token, so
+/// the same input always maps to the same token under the same salt.
+pub struct Redactor {
+    salt: String,
+    sensitive_keys: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new(salt: String, extra_keys: &[String]) -> Self {
+        let sensitive_keys = DEFAULT_SENSITIVE_KEYS.iter().map(|key| key.to_string()).chain(extra_keys.iter().map(|key| key.to_ascii_lowercase())).collect();
+        Redactor { salt, sensitive_keys }
+    }
+
+    pub fn redact(&self, value: &Value) -> Value {
+        match value {
+            Value::String(text) => Value::String(self.redact_text(text)),
+            Value::Array(items) => Value::Array(items.iter().map(|item| self.redact(item)).collect()),
+            Value::Object(map) => {
+                let redacted: Map<String, Value> = map
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = if self.is_sensitive(key) { Value::String(format!("redacted-{}", self.token(&value.to_string()))) } else { self.redact(value) };
+                        (key.clone(), value)
+                    })
+                    .collect();
+                Value::Object(redacted)
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive_keys.iter().any(|sensitive| sensitive.eq_ignore_ascii_case(key))
+    }
+
+    /// Masks each email address and IP found in `text`, leaving the rest intact.
+    fn redact_text(&self, text: &str) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() || WORD_BREAKS.contains(c) {
+                redacted.push_str(&self.redact_word(&word));
+                redacted.push(c);
+                word.clear();
+            } else {
+                word.push(c);
+            }
+        }
+        redacted.push_str(&self.redact_word(&word));
+        redacted
+    }
+
+    fn redact_word(&self, word: &str) -> String {
+        if let Ok(ip) = word.parse::<IpAddr>() {
+            return self.mask_ip(ip);
+        }
+        match word.rsplit_once('@') {
+            Some((local, domain)) if target::is_email(word) => format!("{}@{}", self.token(local), domain),
+            _ => word.to_string(),
+        }
+    }
+
+    /// Keeps the first octet (or first IPv6 group) so the network is still
+    /// recognisable.
+    fn mask_ip(&self, ip: IpAddr) -> String {
+        let text = ip.to_string();
+        match ip {
+            IpAddr::V4(v4) => format!("{}.x.x.x-{}", v4.octets()[0], self.token(&text)),
+            IpAddr::V6(v6) => format!("{:x}:x-{}", v6.segments()[0], self.token(&text)),
+        }
+    }
+
+    fn token(&self, value: &str) -> String {
+        let digest = Sha256::new().chain_update(self.salt.as_bytes()).chain_update([0]).chain_update(value.as_bytes()).finalize();
+        digest[..6].iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::findings::{self, Finding};
+use crate::iocs;
+use crate::OsintError;
+
+/// JSON Schema of the report envelope, printed by the `schema` subcommand.
+pub const SCHEMA: &str = include_str!("report.schema.json");
+
+/// Wraps a source's data in the report envelope downstream tools code against.
+pub fn envelope(target: &str, source: &str, data: &Value, analysis: Option<&str>, findings: &[Finding]) -> Value {
+    let mut report = json!({
+        "target": target,
+        "source": source,
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        "data": data,
+        "iocs": iocs::extract(data),
+        "risk_score": findings::risk_score(findings),
+        "findings": findings,
+    });
+    if let Some(analysis) = analysis {
+        report["analysis"] = json!(analysis);
+    }
+    report
+}
+
+/// What `--error-format json` emits when a scan fails. `source` is the
+/// requested type; both it and `target` are null for subcommands.
+pub fn error_report(target: Option<&str>, source: Option<&str>, kind: &str, message: &str, exit_code: u8) -> Value {
+    json!({
+        "target": target,
+        "source": source,
+        "error_kind": kind,
+        "message": message,
+        "exit_code": exit_code,
+    })
+}
+
+/// Checks a report against `SCHEMA`, so format drift fails loudly here
+/// rather than in someone's integration.
+pub fn validate(report: &Value) -> Result<(), OsintError> {
+    let schema: Value = serde_json::from_str(SCHEMA)?;
+    let validator = jsonschema::validator_for(&schema).map_err(|err| OsintError::InvalidReport(format!("schema does not compile: {}", err)))?;
+    let errors: Vec<String> = validator.iter_errors(report).map(|err| format!("{} at {}", err, err.instance_path)).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(OsintError::InvalidReport(errors.join("; ")))
+    }
+}
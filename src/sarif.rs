@@ -0,0 +1,63 @@
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::findings::{self, Finding, Severity};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Maps a report's findings to a SARIF 2.1.0 log with one run: every kind
+/// of finding is a rule of the tool's driver, and each finding a result
+/// located at the target, so dashboards that read SARIF can show them.
+pub fn log(target: &str, source: &str, findings: &[Finding]) -> Value {
+    let rules: Vec<Value> = findings::RULES
+        .iter()
+        .map(|(id, name, description)| {
+            json!({
+                "id": id,
+                "name": name,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": "warning" },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings.iter().map(|finding| result(target, source, finding)).collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "automationDetails": { "id": format!("{}/{}/", source, target) },
+            "results": results,
+            "properties": { "target": target, "source": source, "risk_score": findings::risk_score(findings) },
+        }]
+    })
+}
+
+fn result(target: &str, source: &str, finding: &Finding) -> Value {
+    let level = match finding.severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    };
+    let fingerprint: String = Sha256::new().chain_update(target).chain_update([0]).chain_update(finding.rule).chain_update([0]).chain_update(&finding.message).finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+    let mut result = json!({
+        "ruleId": finding.rule,
+        "level": level,
+        "message": { "text": finding.message },
+        "locations": [{
+            "logicalLocations": [{ "name": target, "fullyQualifiedName": format!("{}:{}", source, target), "kind": "resource" }]
+        }],
+        // Keeps one finding the same result across scans.
+        "partialFingerprints": { "osintFinding/v1": fingerprint },
+    });
+    if let Some(index) = findings::RULES.iter().position(|(id, _, _)| *id == finding.rule) {
+        result["ruleIndex"] = json!(index);
+    }
+    result
+}
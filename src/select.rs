@@ -0,0 +1,76 @@
+use serde_json::{Map, Value};
+
+/// One `--fields` path, such as `data.ports` or `data.data[].port`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldPath(Vec<Step>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    /// `[]`: every element of an array.
+    Each,
+}
+
+/// Parses a dot path; `[]` after a key descends into each array element.
+pub fn parse_path(spec: &str) -> Result<FieldPath, String> {
+    let spec = spec.trim();
+    let mut steps = Vec::new();
+    for segment in spec.split('.') {
+        let key = segment.trim_end_matches("[]");
+        if key.is_empty() || key.contains(['[', ']']) {
+            return Err(format!("`{}` is not a field path like data.ports or data.data[].port", spec));
+        }
+        steps.push(Step::Key(key.to_string()));
+        steps.extend(std::iter::repeat_n(Step::Each, (segment.len() - key.len()) / 2));
+    }
+    Ok(FieldPath(steps))
+}
+
+/// Cuts `report` down to the given paths, keeping their place in the
+/// structure. Paths that match nothing are left out; an array element
+/// lacking the field becomes null so elements stay aligned across paths.
+pub fn project(report: &Value, paths: &[FieldPath]) -> Value {
+    let mut projected = Value::Object(Map::new());
+    for path in paths {
+        if let Some(selected) = select(report, &path.0) {
+            merge(&mut projected, selected);
+        }
+    }
+    projected
+}
+
+fn select(value: &Value, steps: &[Step]) -> Option<Value> {
+    let Some((step, rest)) = steps.split_first() else {
+        return Some(value.clone());
+    };
+    match step {
+        Step::Key(key) => {
+            let selected = select(value.as_object()?.get(key)?, rest)?;
+            Some(Value::Object(Map::from_iter([(key.clone(), selected)])))
+        }
+        Step::Each => Some(Value::Array(value.as_array()?.iter().map(|item| select(item, rest).unwrap_or(Value::Null)).collect())),
+    }
+}
+
+/// Adds `from` into `into`: objects key by key, arrays element by element.
+fn merge(into: &mut Value, from: Value) {
+    match (into, from) {
+        (Value::Object(into), Value::Object(from)) => {
+            for (key, value) in from {
+                match into.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        into.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(into), Value::Array(from)) => {
+            for (existing, value) in into.iter_mut().zip(from) {
+                merge(existing, value);
+            }
+        }
+        (_, Value::Null) => {}
+        (into, from) => *into = from,
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::findings::{Finding, Severity};
+use crate::iocs::Iocs;
+use crate::target::{self, TargetKind};
+use crate::OsintError;
+
+/// Namespace the STIX 2.1 spec fixes for deterministic SCO identifiers.
+const SCO_NAMESPACE: Uuid = uuid::uuid!("00abedb4-aa42-466c-9c01-fed23315a9b7");
+
+/// Rules whose findings are a verdict that the target itself is malicious.
+/// The rest, such as breach exposure or an expired certificate, describe a
+/// weakness of the target and must not be shared as indicators against it.
+const THREAT_RULES: &[&str] = &["malicious-verdict", "known-phish", "known-malicious-infrastructure", "threat-intelligence", "blocklisted"];
+
+/// Maps a report to a STIX 2.1 bundle: the target and every extracted
+/// indicator become observables tied together with `related-to`, and each
+/// threat verdict becomes an `indicator` based on the target.
+pub fn bundle(target: &str, iocs: &Iocs, findings: &[Finding]) -> Result<Value, OsintError> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let mut objects = Vec::new();
+    let mut seen = HashSet::new();
+
+    let root = observable(target.trim(), target::classify(target.trim()));
+    if let Some(root) = &root {
+        seen.insert(root["id"].clone());
+        objects.push(root.clone());
+    }
+
+    let extracted = [
+        (&iocs.domains, TargetKind::Domain),
+        (&iocs.ips, TargetKind::Ip),
+        (&iocs.urls, TargetKind::Url),
+        (&iocs.hashes, TargetKind::Hash),
+        (&iocs.emails, TargetKind::Email),
+    ];
+    for (values, kind) in extracted {
+        for object in values.iter().filter_map(|value| observable(value, kind)) {
+            if !seen.insert(object["id"].clone()) {
+                continue;
+            }
+            if let Some(root) = &root {
+                objects.push(relationship("related-to", &root["id"], &object["id"], &now));
+            }
+            objects.push(object);
+        }
+    }
+
+    if let Some(root) = &root {
+        for finding in findings.iter().filter(|finding| is_threat_verdict(finding)) {
+            let indicator = json!({
+                "type": "indicator",
+                "spec_version": "2.1",
+                "id": format!("indicator--{}", Uuid::new_v4()),
+                "created": now,
+                "modified": now,
+                "name": finding.message,
+                "indicator_types": ["malicious-activity"],
+                "pattern": pattern(root),
+                "pattern_type": "stix",
+                "valid_from": now,
+            });
+            objects.push(relationship("based-on", &indicator["id"], &root["id"], &now));
+            objects.push(indicator);
+        }
+    }
+
+    let bundle = json!({ "type": "bundle", "id": format!("bundle--{}", Uuid::new_v4()), "objects": objects });
+    validate(&bundle)?;
+    Ok(bundle)
+}
+
+/// A high-severity finding under one of `THREAT_RULES`. Lower ones are mere
+/// mentions, such as an OTX pulse or a policy blocklist, not verdicts.
+fn is_threat_verdict(finding: &Finding) -> bool {
+    THREAT_RULES.contains(&finding.rule) && finding.severity == Severity::High
+}
+
+/// The STIX cyber-observable for a value, or `None` for kinds STIX cannot
+/// express. The id is derived from the value, so repeats collapse.
+fn observable(value: &str, kind: TargetKind) -> Option<Value> {
+    let (kind, contributing) = match kind {
+        TargetKind::Domain => ("domain-name", json!({ "value": value.trim_end_matches('.').to_ascii_lowercase() })),
+        TargetKind::Ip if value.contains(':') => ("ipv6-addr", json!({ "value": value })),
+        TargetKind::Ip => ("ipv4-addr", json!({ "value": value })),
+        TargetKind::Email => ("email-addr", json!({ "value": value.to_ascii_lowercase() })),
+        TargetKind::Url => ("url", json!({ "value": value })),
+        TargetKind::Hash => {
+            let algorithm = match value.len() {
+                32 => "MD5",
+                40 => "SHA-1",
+                _ => "SHA-256",
+            };
+            ("file", json!({ "hashes": { algorithm: value.to_ascii_lowercase() } }))
+        }
+        TargetKind::Asn => ("autonomous-system", json!({ "number": target::asn_number(value)? })),
+        TargetKind::Phone | TargetKind::Unknown => return None,
+    };
+    let id = format!("{}--{}", kind, Uuid::new_v5(&SCO_NAMESPACE, contributing.to_string().as_bytes()));
+    let mut object = json!({ "type": kind, "spec_version": "2.1", "id": id });
+    for (key, value) in contributing.as_object().expect("contributing properties are an object") {
+        object[key] = value.clone();
+    }
+    Some(object)
+}
+
+fn relationship(relationship_type: &str, source: &Value, target: &Value, now: &str) -> Value {
+    json!({
+        "type": "relationship",
+        "spec_version": "2.1",
+        "id": format!("relationship--{}", Uuid::new_v4()),
+        "created": now,
+        "modified": now,
+        "relationship_type": relationship_type,
+        "source_ref": source,
+        "target_ref": target,
+    })
+}
+
+/// A STIX pattern matching the observable exactly.
+fn pattern(observable: &Value) -> String {
+    let escape = |value: &str| value.replace('\\', "\\\\").replace('\'', "\\'");
+    let kind = observable["type"].as_str().unwrap_or_default();
+    if let Some(number) = observable["number"].as_u64() {
+        return format!("[autonomous-system:number = {}]", number);
+    }
+    match observable["hashes"].as_object().and_then(|hashes| hashes.iter().next()) {
+        Some((algorithm, hash)) => format!("[file:hashes.'{}' = '{}']", algorithm, escape(hash.as_str().unwrap_or_default())),
+        None => format!("[{}:value = '{}']", kind, escape(observable["value"].as_str().unwrap_or_default())),
+    }
+}
+
+/// Checks the fields STIX 2.1 requires of each object we emit, and that every
+/// relationship points at an object inside the bundle.
+pub fn validate(bundle: &Value) -> Result<(), OsintError> {
+    let invalid = |reason: String| Err(OsintError::ProviderError(format!("invalid STIX bundle: {}", reason)));
+    if bundle["type"] != "bundle" || !is_identifier(&bundle["id"], "bundle") {
+        return invalid("bundle needs type `bundle` and a bundle-- id".to_string());
+    }
+    let Some(objects) = bundle["objects"].as_array() else {
+        return invalid("bundle has no objects array".to_string());
+    };
+    let ids: HashSet<&str> = objects.iter().filter_map(|object| object["id"].as_str()).collect();
+    for object in objects {
+        let kind = object["type"].as_str().unwrap_or_default();
+        let mut required = vec!["type", "spec_version", "id"];
+        match kind {
+            "indicator" => required.extend(["created", "modified", "pattern", "pattern_type", "valid_from"]),
+            "relationship" => required.extend(["created", "modified", "relationship_type", "source_ref", "target_ref"]),
+            "file" => required.push("hashes"),
+            "autonomous-system" => required.push("number"),
+            "domain-name" | "ipv4-addr" | "ipv6-addr" | "email-addr" | "url" => required.push("value"),
+            other => return invalid(format!("unexpected object type `{}`", other)),
+        }
+        if let Some(missing) = required.iter().find(|field| object[**field].is_null()) {
+            return invalid(format!("{} {} lacks `{}`", kind, object["id"], missing));
+        }
+        if object["spec_version"] != "2.1" || !is_identifier(&object["id"], kind) {
+            return invalid(format!("{} has a malformed id or spec_version", object["id"]));
+        }
+        for reference in ["source_ref", "target_ref"] {
+            if let Some(id) = object[reference].as_str() {
+                if !ids.contains(id) {
+                    return invalid(format!("{} points at {} outside the bundle", object["id"], id));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `<type>--<UUID>`, as STIX identifiers must be.
+fn is_identifier(id: &Value, kind: &str) -> bool {
+    id.as_str()
+        .and_then(|id| id.strip_prefix(kind))
+        .and_then(|rest| rest.strip_prefix("--"))
+        .is_some_and(|uuid| Uuid::parse_str(uuid).is_ok())
+}
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::OsintError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY,
+    target TEXT NOT NULL,
+    source TEXT NOT NULL,
+    timestamp TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    risk_score INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS scans_target_timestamp ON scans (target, timestamp);
+CREATE INDEX IF NOT EXISTS scans_timestamp ON scans (timestamp);
+";
+
+/// Scan history kept in a SQLite database given with `--db`.
+pub struct Store {
+    conn: Connection,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ScanSummary {
+    pub id: i64,
+    pub target: String,
+    pub source: String,
+    pub timestamp: String,
+    pub risk_score: u32,
+}
+
+impl Store {
+    /// Opens the database at `path`, creating it and its schema if needed.
+    pub fn open(path: &Path) -> Result<Store, OsintError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Records one scan and returns its id.
+    pub fn record(&self, target: &str, source: &str, data: &Value, risk_score: u32) -> Result<i64, OsintError> {
+        self.conn.execute(
+            "INSERT INTO scans (target, source, risk_score, data) VALUES (?1, ?2, ?3, ?4)",
+            params![target, source, risk_score, data.to_string()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Lists the scans of `target`, newest first.
+    pub fn scans_for(&self, target: &str) -> Result<Vec<ScanSummary>, OsintError> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, target, source, timestamp, risk_score FROM scans WHERE target = ?1 ORDER BY timestamp DESC, id DESC",
+        )?;
+        let scans = statement
+            .query_map(params![target], |row| {
+                Ok(ScanSummary { id: row.get(0)?, target: row.get(1)?, source: row.get(2)?, timestamp: row.get(3)?, risk_score: row.get(4)? })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(scans)
+    }
+
+    /// The newest scan of `target` with `source` from the last `within`
+    /// seconds, with its stored data.
+    pub fn latest(&self, target: &str, source: &str, within: u64) -> Result<Option<(ScanSummary, Value)>, OsintError> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT id, target, source, timestamp, risk_score, data FROM scans
+                 WHERE target = ?1 AND source = ?2 AND timestamp >= strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?3)
+                 ORDER BY timestamp DESC, id DESC LIMIT 1",
+                params![target, source, format!("-{} seconds", within)],
+                |row| {
+                    let summary = ScanSummary { id: row.get(0)?, target: row.get(1)?, source: row.get(2)?, timestamp: row.get(3)?, risk_score: row.get(4)? };
+                    Ok((summary, row.get::<_, String>(5)?))
+                },
+            )
+            .optional()?;
+        row.map(|(summary, data)| Ok((summary, serde_json::from_str(&data)?))).transpose()
+    }
+
+    /// Fetches the stored report of one scan.
+    pub fn scan(&self, id: i64) -> Result<Option<Value>, OsintError> {
+        let data: Option<String> = self.conn.query_row("SELECT data FROM scans WHERE id = ?1", params![id], |row| row.get(0)).optional()?;
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+
+/// Notes kept in a summary; the rest are counted.
+const MAX_NOTES: usize = 10;
+/// Keys holding verbose provider payloads that repeat the normalized fields.
+const VERBOSE_KEYS: &[&str] = &["raw", "certificates", "headers"];
+
+/// Shrinks a report so it fits a model's context window: verbose raw blobs
+/// are dropped, long arrays keep their first items plus a count, and long
+/// strings are clipped. Everything else is kept as is.
+#[derive(Debug, Clone, Copy)]
+pub struct DataSummarizer {
+    pub max_array_items: usize,
+    pub max_string_chars: usize,
+}
+
+/// A condensed report and a description of what was cut from it.
+#[derive(Debug)]
+pub struct Summary {
+    pub data: Value,
+    pub notes: Vec<String>,
+}
+
+impl Default for DataSummarizer {
+    fn default() -> Self {
+        DataSummarizer { max_array_items: 50, max_string_chars: 2000 }
+    }
+}
+
+impl DataSummarizer {
+    /// A stricter summarizer for when this one's output is still too large.
+    pub fn tighter(self) -> Option<DataSummarizer> {
+        (self.max_array_items > 5).then(|| DataSummarizer { max_array_items: self.max_array_items / 2, max_string_chars: (self.max_string_chars / 2).max(200) })
+    }
+
+    /// `data` as JSON, condensed with this summarizer and then ever tighter
+    /// ones until `fits` accepts it or none is tighter. The notes are those
+    /// of the last cut, and empty when `data` fit as it was.
+    pub fn fit(self, data: &Value, fits: impl Fn(&str) -> bool) -> (String, Vec<String>) {
+        let mut payload = data.to_string();
+        let mut notes = Vec::new();
+        let mut summarizer = self;
+        while !fits(&payload) {
+            let summary = summarizer.summarize(data);
+            payload = summary.data.to_string();
+            notes = summary.notes;
+            match summarizer.tighter() {
+                Some(tighter) => summarizer = tighter,
+                None => break,
+            }
+        }
+        (payload, notes)
+    }
+
+    pub fn summarize(&self, data: &Value) -> Summary {
+        let mut notes = Vec::new();
+        let data = self.condense("report", data, &mut notes);
+        // Nested arrays repeat the same note once per parent item.
+        let mut seen = HashSet::new();
+        notes.retain(|note| seen.insert(note.clone()));
+        if notes.len() > MAX_NOTES {
+            let extra = notes.len() - MAX_NOTES;
+            notes.truncate(MAX_NOTES);
+            notes.push(format!("{} further cuts", extra));
+        }
+        Summary { data, notes }
+    }
+
+    fn condense(&self, key: &str, value: &Value, notes: &mut Vec<String>) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut condensed = Map::new();
+                for (child_key, child) in map {
+                    if VERBOSE_KEYS.contains(&child_key.as_str()) {
+                        notes.push(format!("{}.{} dropped", key, child_key));
+                        continue;
+                    }
+                    condensed.insert(child_key.clone(), self.condense(child_key, child, notes));
+                }
+                Value::Object(condensed)
+            }
+            Value::Array(items) if items.len() > self.max_array_items => {
+                notes.push(format!("{} {} total, showing first {}", items.len(), key, self.max_array_items));
+                let mut kept: Vec<Value> = items.iter().take(self.max_array_items).map(|item| self.condense(key, item, notes)).collect();
+                kept.push(Value::String(format!("... {} more omitted", items.len() - self.max_array_items)));
+                Value::Array(kept)
+            }
+            Value::Array(items) => Value::Array(items.iter().map(|item| self.condense(key, item, notes)).collect()),
+            Value::String(text) if text.chars().count() > self.max_string_chars => {
+                notes.push(format!("{} clipped to {} characters", key, self.max_string_chars));
+                Value::String(text.chars().take(self.max_string_chars).chain("...".chars()).collect())
+            }
+            other => other.clone(),
+        }
+    }
+}
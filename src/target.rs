@@ -0,0 +1,258 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr};
+
+use url::{Host, Url};
+
+use crate::OsintError;
+
+/// Characters RFC 5322 allows unquoted in an email local part, besides
+/// alphanumerics and dots.
+const LOCAL_PART_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Ip,
+    Domain,
+    Email,
+    Url,
+    Hash,
+    Asn,
+    Phone,
+    Unknown,
+}
+
+impl fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TargetKind::Ip => "an IP address",
+            TargetKind::Domain => "a domain",
+            TargetKind::Email => "an email address",
+            TargetKind::Url => "a URL",
+            TargetKind::Hash => "a file hash",
+            TargetKind::Asn => "an AS number",
+            TargetKind::Phone => "a phone number",
+            TargetKind::Unknown => "unrecognised input",
+        })
+    }
+}
+
+/// The kinds a recon type accepts, rendered as "a domain or an IP address".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Expected(pub &'static [TargetKind]);
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, kind) in self.0.iter().enumerate() {
+            match i {
+                0 => {}
+                i if i + 1 == self.0.len() => f.write_str(" or ")?,
+                _ => f.write_str(", ")?,
+            }
+            write!(f, "{}", kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Suffix for mismatch errors pointing at the type that fits the target.
+pub fn suggestion(got: &TargetKind) -> &'static str {
+    match got {
+        TargetKind::Ip => " (did you mean `shodan`?)",
+        TargetKind::Domain => " (did you mean `whois`?)",
+        TargetKind::Email => " (did you mean `hibp`?)",
+        TargetKind::Url => " (did you mean `http`?)",
+        TargetKind::Hash => " (did you mean `virustotal`?)",
+        TargetKind::Asn => " (did you mean `rdap`?)",
+        TargetKind::Phone => " (did you mean `phone`?)",
+        TargetKind::Unknown => "",
+    }
+}
+
+/// Works out what a target looks like from its shape alone.
+pub fn classify(target: &str) -> TargetKind {
+    if target.parse::<IpAddr>().is_ok() {
+        TargetKind::Ip
+    } else if target.contains("://") {
+        TargetKind::Url
+    } else if is_hash(target) {
+        TargetKind::Hash
+    } else if asn_number(target).is_some() {
+        TargetKind::Asn
+    } else if target.contains('@') {
+        TargetKind::Email
+    } else if target.trim_start().starts_with('+') {
+        TargetKind::Phone
+    } else if is_hostname(target) {
+        TargetKind::Domain
+    } else {
+        TargetKind::Unknown
+    }
+}
+
+fn accepted_kinds(recon_type: &str) -> &'static [TargetKind] {
+    match recon_type {
+        "whois" | "emailauth" | "dns" | "axfr" => &[TargetKind::Domain],
+        // Shodan resolves hostnames to an address before the lookup; ZoomEye
+        // searches a domain's hosts with `site:` and BinaryEdge lists its
+        // subdomains.
+        "shodan" | "zoomeye" | "binaryedge" => &[TargetKind::Ip, TargetKind::Domain],
+        "hibp" | "leakcheck" | "emailrep" | "emailverify" | "gravatar" => &[TargetKind::Email],
+        "phone" => &[TargetKind::Phone],
+        "github" | "dork" => &[TargetKind::Domain, TargetKind::Email],
+        "intelx" => &[TargetKind::Email, TargetKind::Domain, TargetKind::Ip],
+        "crtsh" | "subbrute" | "securitytrails" | "hunter" | "company" | "wayback" | "robots" | "dorks" => &[TargetKind::Domain],
+        "censys" | "reversedns" | "abuseipdb" | "greynoise" | "geoip" | "ipinfo" | "anonymity" | "dnsbl" => &[TargetKind::Ip],
+        "otx" | "asn" | "tlscert" | "onyphe" => &[TargetKind::Domain, TargetKind::Ip],
+        "rdap" => &[TargetKind::Domain, TargetKind::Ip, TargetKind::Asn],
+        "virustotal" | "threatfox" => &[TargetKind::Domain, TargetKind::Ip, TargetKind::Hash],
+        "http" | "favicon" | "screenshot" | "urlscan" => &[TargetKind::Domain, TargetKind::Ip, TargetKind::Url],
+        "phish" => &[TargetKind::Domain, TargetKind::Url],
+        _ => &[],
+    }
+}
+
+/// Checks that `target` suits `recon_type` before anything touches the
+/// network or the environment.
+pub fn validate(recon_type: &str, target: &str) -> Result<(), OsintError> {
+    let accepted = accepted_kinds(recon_type);
+    if accepted.is_empty() {
+        return Ok(());
+    }
+    let got = classify(target);
+    if !accepted.contains(&got) {
+        return Err(OsintError::TargetMismatch { target: target.to_string(), expected: Expected(accepted), got });
+    }
+    match got {
+        TargetKind::Email if !is_email(target) => Err(OsintError::InvalidTarget(format!("{} is not a valid email address", target))),
+        TargetKind::Phone if normalize_phone(target).is_none() => Err(OsintError::InvalidTarget(format!("{} is not an E.164 phone number (+ and country code, then 8 to 15 digits in all)", target))),
+        TargetKind::Domain if matches!(recon_type, "whois" | "emailauth") && !is_registrable_domain(target) => {
+            Err(OsintError::InvalidTarget(format!("{} is not a registrable domain", target)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `raw` in the form the providers of `recon_type` expect, before it is
+/// validated: domains, and the domain of an email, are lowercased and
+/// IDNs converted to punycode, IP addresses written the standard way
+/// (`2001:db8::1`, also from `[2001:db8::1]`), and a URL given to a type
+/// that takes no URLs is cut down to its host. `all` keeps URLs, for the
+/// sources that take them. Free-form targets and other kinds are only
+/// trimmed.
+pub fn normalize_target(raw: &str, recon_type: &str) -> Result<String, OsintError> {
+    let raw = raw.trim();
+    let accepted = accepted_kinds(recon_type);
+    if accepted.is_empty() && recon_type != "all" {
+        return Ok(raw.to_string());
+    }
+    let takes_urls = recon_type == "all" || accepted.contains(&TargetKind::Url);
+    if let Some(ip) = raw.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).and_then(|ip| ip.parse::<Ipv6Addr>().ok()) {
+        return Ok(ip.to_string());
+    }
+    match classify(raw) {
+        TargetKind::Ip => Ok(raw.parse::<IpAddr>().map_or_else(|_| raw.to_string(), |ip| ip.to_string())),
+        TargetKind::Url if !takes_urls => url_host(raw),
+        // `example.com/login`, `example.com:8443` or `[2001:db8::1]:8443`,
+        // pasted without a scheme.
+        TargetKind::Unknown if !takes_urls && (raw.starts_with('[') || raw.contains(['/', ':']) && !raw.contains("::")) => url_host(&format!("http://{}", raw)).or_else(|_| Ok(raw.to_string())),
+        TargetKind::Domain => ascii_domain(raw),
+        TargetKind::Email => match raw.rsplit_once('@') {
+            Some((local, domain)) if is_hostname(domain) => Ok(format!("{}@{}", local, ascii_domain(domain)?)),
+            _ => Ok(raw.to_string()),
+        },
+        _ => Ok(raw.to_string()),
+    }
+}
+
+/// What a URL points at: its host as a punycode domain or an IP address.
+fn url_host(raw: &str) -> Result<String, OsintError> {
+    let url = Url::parse(raw).map_err(|err| OsintError::InvalidTarget(format!("{} is not a valid URL: {}", raw, err)))?;
+    match url.host() {
+        Some(Host::Domain(domain)) => ascii_domain(domain),
+        Some(Host::Ipv4(ip)) => Ok(ip.to_string()),
+        Some(Host::Ipv6(ip)) => Ok(ip.to_string()),
+        None => Err(OsintError::InvalidTarget(format!("{} has no host to look up", raw))),
+    }
+}
+
+/// A domain lowercased, without its trailing dot, and as punycode.
+fn ascii_domain(name: &str) -> Result<String, OsintError> {
+    idna::domain_to_ascii(name.trim_end_matches('.')).map_err(|_| OsintError::InvalidTarget(format!("{} is not a valid internationalized domain", name)))
+}
+
+/// Whether `all` should run `recon_type` against `target`. Types that take
+/// free-form input, like usernames, never apply.
+pub fn applies(recon_type: &str, target: &str) -> bool {
+    !accepted_kinds(recon_type).is_empty() && validate(recon_type, target).is_ok()
+}
+
+/// The number of an `AS15169`-style target, case-insensitively.
+pub fn asn_number(value: &str) -> Option<u32> {
+    let digits = value.get(2..).filter(|_| value[..2].eq_ignore_ascii_case("as"))?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// `target` as an E.164 number, `+` and up to 15 digits, once the spaces,
+/// dashes, dots and parentheses of written-out numbers are dropped.
+pub fn normalize_phone(target: &str) -> Option<String> {
+    let compact: String = target.chars().filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')')).collect();
+    let digits = compact.strip_prefix('+')?;
+    let plausible = (8..=15).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit()) && !digits.starts_with('0');
+    plausible.then(|| format!("+{}", digits))
+}
+
+/// Hex digests of the lengths of MD5, SHA-1 and SHA-256.
+pub fn is_hash(value: &str) -> bool {
+    matches!(value.len(), 32 | 40 | 64) && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// LDH hostname syntax, accepting IDNs through their punycode form.
+pub fn is_hostname(name: &str) -> bool {
+    let Ok(ascii) = idna::domain_to_ascii(name.trim_end_matches('.')) else {
+        return false;
+    };
+    !ascii.is_empty()
+        && ascii.len() <= 253
+        && ascii.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// A hostname under a known public suffix that is not itself a suffix.
+pub fn is_registrable_domain(name: &str) -> bool {
+    if !is_hostname(name) {
+        return false;
+    }
+    let Ok(ascii) = idna::domain_to_ascii(name.trim_end_matches('.')) else {
+        return false;
+    };
+    psl::domain(ascii.as_bytes()).is_some_and(|domain| domain.suffix().is_known())
+}
+
+/// Syntactic address check: a dot-atom or quoted local part and a
+/// hostname with at least two labels.
+pub fn is_email(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    is_local_part(local) && domain.contains('.') && is_hostname(domain)
+}
+
+fn is_local_part(local: &str) -> bool {
+    if local.is_empty() || local.len() > 64 {
+        return false;
+    }
+    if let Some(quoted) = local.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        return !quoted.is_empty() && quoted.chars().all(|c| c != '"' && c != '\\' && !c.is_control());
+    }
+    local.split('.').all(|atom| {
+        !atom.is_empty() && atom.chars().all(|c| c.is_alphanumeric() || LOCAL_PART_SPECIALS.contains(c))
+    })
+}
@@ -0,0 +1,87 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde_json::{json, Value};
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::OsintError;
+
+const TAXII_MEDIA_TYPE: &str = "application/taxii+json;version=2.1";
+const POLL_DELAY: Duration = Duration::from_secs(1);
+const MAX_POLL_DELAY: Duration = Duration::from_secs(10);
+const POLL_DEADLINE: Duration = Duration::from_secs(120);
+
+/// What the server made of a push, read from its final status resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ingested {
+    pub total: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+/// POSTs the objects of a STIX `bundle` to `collection` under the TAXII 2.1
+/// API root `api_root`, then follows the status resource until the server
+/// has processed all of them. TAXII 2.1 takes the objects in an envelope
+/// rather than as a bundle.
+///
+/// `auth` is `user:password` for basic authentication, or a bearer token.
+pub async fn push(api_root: &Url, collection: &str, auth: Option<&str>, bundle: &Value) -> Result<Ingested, OsintError> {
+    let root = api_root.as_str().trim_end_matches('/');
+    let url = format!("{}/collections/{}/objects/", root, collection);
+    let client = crate::client_builder().build()?;
+    let envelope = json!({ "objects": bundle["objects"] });
+    let request = authorized(client.post(&url), auth).header("Content-Type", TAXII_MEDIA_TYPE).body(envelope.to_string()).build()?;
+    let response = crate::send_with_retries(&client, request).await?;
+    let mut status = read_status(response, &url).await?;
+    info!(collection, id = %status["id"], status = %status["status"], "TAXII objects posted");
+
+    let started = Instant::now();
+    let mut delay = POLL_DELAY;
+    while status["status"] != "complete" {
+        if started.elapsed() >= POLL_DEADLINE {
+            return Err(OsintError::ProviderError(format!("TAXII server did not finish ingesting within {:?} ({} objects still pending)", POLL_DEADLINE, status["pending_count"])));
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_POLL_DELAY);
+        let id = status["id"].as_str().map(str::to_string).ok_or_else(|| OsintError::ProviderError("TAXII status resource has no id".to_string()))?;
+        let status_url = format!("{}/status/{}/", root, id);
+        let response = crate::send_with_retries(&client, authorized(client.get(&status_url), auth).build()?).await?;
+        status = read_status(response, &status_url).await?;
+        debug!(%id, pending = %status["pending_count"], "TAXII status polled");
+    }
+
+    for failure in status["failures"].as_array().into_iter().flatten() {
+        warn!(object = %failure["id"], message = %failure["message"], "TAXII server rejected object");
+    }
+    let count = |field: &str| status[field].as_u64().unwrap_or_default();
+    let ingested = Ingested { total: count("total_count"), succeeded: count("success_count"), failed: count("failure_count") };
+    info!(collection, total = ingested.total, succeeded = ingested.succeeded, failed = ingested.failed, "TAXII push done");
+    Ok(ingested)
+}
+
+fn authorized(request: RequestBuilder, auth: Option<&str>) -> RequestBuilder {
+    let request = request.header("Accept", TAXII_MEDIA_TYPE);
+    match auth {
+        Some(auth) => match auth.split_once(':') {
+            Some((user, password)) => request.basic_auth(user, Some(password)),
+            None => request.bearer_auth(auth),
+        },
+        None => request,
+    }
+}
+
+/// The status resource of a push or status answer.
+async fn read_status(response: Response, url: &str) -> Result<Value, OsintError> {
+    match response.status() {
+        status if status.is_success() => Ok(response.json().await?),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(OsintError::InvalidCredentials("--taxii-auth".to_string())),
+        // TAXII servers explain refusals in an error message resource.
+        status => {
+            let body: Value = response.json().await.unwrap_or_default();
+            match body["title"].as_str() {
+                Some(title) => Err(OsintError::ProviderError(format!("TAXII server refused the request ({}): {}", status.as_u16(), body["description"].as_str().unwrap_or(title)))),
+                None => Err(OsintError::ApiStatus { status, url: url.to_string() }),
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde_json::Value;
+
+use crate::findings::{self, Finding};
+
+/// Characters of the key finding shown; the full message is in the report.
+const KEY_FINDING_CHARS: usize = 60;
+const HEADINGS: [&str; 5] = ["Target", "Sources", "Risk", "Key finding", "Status"];
+
+/// One target's line in the summary printed after a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub target: String,
+    pub sources: usize,
+    pub risk: u32,
+    pub key_finding: String,
+    pub status: String,
+}
+
+impl Row {
+    /// The line for a finished scan: the sources `all` ran (one otherwise),
+    /// the risk score and the most severe finding.
+    pub fn scanned(target: &str, recon_type: &str, data: &Value, findings: &[Finding], status: &str) -> Row {
+        let sources = match recon_type {
+            "all" => data["sources"].as_object().map_or(0, |sources| sources.len()),
+            _ => 1,
+        };
+        // The first of the most severe findings.
+        let key = findings.iter().rev().max_by_key(|finding| finding.severity).map(|finding| finding.message.as_str()).unwrap_or("-");
+        let key_finding = match key.chars().count() > KEY_FINDING_CHARS {
+            true => format!("{}...", key.chars().take(KEY_FINDING_CHARS - 3).collect::<String>()),
+            false => key.to_string(),
+        };
+        Row { target: target.to_string(), sources, risk: findings::risk_score(findings), key_finding, status: status.to_string() }
+    }
+
+    /// The line for a scan that failed with the error of kind `kind`.
+    pub fn failed(target: &str, kind: &str) -> Row {
+        Row { target: target.to_string(), sources: 0, risk: 0, key_finding: "-".to_string(), status: format!("failed: {}", kind) }
+    }
+
+    fn cells(&self) -> [String; 5] {
+        [self.target.clone(), self.sources.to_string(), self.risk.to_string(), self.key_finding.clone(), self.status.clone()]
+    }
+}
+
+/// Riskiest first, then by target.
+pub fn sort(rows: &mut [Row]) {
+    rows.sort_by(|a, b| b.risk.cmp(&a.risk).then_with(|| a.target.cmp(&b.target)));
+}
+
+/// The rows as an aligned table for a terminal.
+pub fn table(rows: &[Row]) -> String {
+    let cells: Vec<[String; 5]> = rows.iter().map(Row::cells).collect();
+    let widths: Vec<usize> = (0..HEADINGS.len()).map(|column| cells.iter().map(|row| row[column].chars().count()).chain([HEADINGS[column].len()]).max().unwrap_or_default()).collect();
+    let line = |row: &[&str]| row.iter().zip(&widths).map(|(cell, &width)| format!("{:width$}", cell)).collect::<Vec<_>>().join("  ").trim_end().to_string();
+    let mut lines = vec![line(&HEADINGS), widths.iter().map(|&width| "-".repeat(width)).collect::<Vec<_>>().join("  ")];
+    lines.extend(cells.iter().map(|row| line(&row.each_ref().map(String::as_str))));
+    lines.join("\n")
+}
+
+/// The rows as tab-separated values with a heading line, for a pipe. Tabs
+/// and newlines inside a cell become spaces.
+pub fn tsv(rows: &[Row]) -> String {
+    let line = |cells: &[&str]| cells.iter().map(|cell| cell.replace(['\t', '\n', '\r'], " ")).collect::<Vec<_>>().join("\t");
+    let mut lines = vec![line(&HEADINGS)];
+    lines.extend(rows.iter().map(|row| line(&row.cells().each_ref().map(String::as_str))));
+    lines.join("\n")
+}